@@ -0,0 +1,673 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::fs;
+use std::net::IpAddr;
+
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+use super::{api_delete, api_get, api_path, build_client, load_api_context};
+use crate::cli::{
+    ApiCommonArgs, ApiConnectionsCloseArgs, ApiConnectionsCommand, ApiConnectionsListArgs,
+    ConnectionExportFormat, ConnectionGroupKey, ConnectionSortKey,
+};
+use crate::output::{is_json_mode, print_json, print_line};
+use crate::utils;
+
+pub fn run(
+    action: Option<ApiConnectionsCommand>,
+    list: ApiConnectionsListArgs,
+    common: ApiCommonArgs,
+) -> Result<()> {
+    match action {
+        None => cmd_list(list, common),
+        Some(ApiConnectionsCommand::Close(args)) => cmd_close(args, common),
+    }
+}
+
+/// `/connections` 中单条连接的规范化视图。
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct Connection {
+    pub(crate) id: String,
+    pub(crate) network: String,
+    pub(crate) host: String,
+    /// 目标地址 `ip:port`
+    pub(crate) destination: String,
+    pub(crate) source: String,
+    pub(crate) source_ip: String,
+    pub(crate) process: String,
+    pub(crate) process_path: String,
+    pub(crate) rule: String,
+    pub(crate) rule_payload: String,
+    /// 出站链，顺序为实际节点在前、策略组在后（与 mihomo 一致）
+    pub(crate) chains: Vec<String>,
+    pub(crate) upload: u64,
+    pub(crate) download: u64,
+    pub(crate) start: String,
+    /// 连接已持续的秒数，start 无法解析时为 None
+    pub(crate) duration_secs: Option<u64>,
+}
+
+impl Connection {
+    fn from_json(item: &JsonValue) -> Option<Self> {
+        let id = item.get("id")?.as_str()?.to_string();
+        let meta = item.get("metadata").cloned().unwrap_or(JsonValue::Null);
+        let text = |key: &str| {
+            meta.get(key)
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string()
+        };
+        let host = Some(text("host"))
+            .filter(|h| !h.is_empty())
+            .unwrap_or_else(|| text("sniffHost"));
+        let dest_ip = text("destinationIP");
+        let destination = join_host_port(&dest_ip, &text("destinationPort"));
+        let source_ip = text("sourceIP");
+        let source = join_host_port(&source_ip, &text("sourcePort"));
+        Some(Self {
+            id,
+            network: text("network"),
+            host: if host.is_empty() { dest_ip } else { host },
+            destination,
+            source,
+            source_ip,
+            process: text("process"),
+            process_path: text("processPath"),
+            rule: item
+                .get("rule")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string(),
+            rule_payload: item
+                .get("rulePayload")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string(),
+            chains: item
+                .get("chains")
+                .and_then(|v| v.as_array())
+                .map(|list| {
+                    list.iter()
+                        .filter_map(|v| v.as_str())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            upload: item.get("upload").and_then(|v| v.as_u64()).unwrap_or(0),
+            download: item.get("download").and_then(|v| v.as_u64()).unwrap_or(0),
+            duration_secs: item
+                .get("start")
+                .and_then(|v| v.as_str())
+                .and_then(utils::parse_rfc3339)
+                .map(|start| utils::now_unix().saturating_sub(start)),
+            start: item
+                .get("start")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string(),
+        })
+    }
+
+    /// 规则描述，例如 `DomainSuffix(example.com)`。
+    pub(crate) fn rule_label(&self) -> String {
+        if self.rule_payload.is_empty() {
+            self.rule.clone()
+        } else {
+            format!("{}({})", self.rule, self.rule_payload)
+        }
+    }
+
+    /// 出站链按入站到出站的方向展示：`PROXY -> 🇭🇰 香港 01`。
+    pub(crate) fn chain_label(&self) -> String {
+        self.chains
+            .iter()
+            .rev()
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(" -> ")
+    }
+}
+
+fn join_host_port(host: &str, port: &str) -> String {
+    match (host.is_empty(), port.is_empty()) {
+        (true, _) => String::new(),
+        (false, true) => host.to_string(),
+        (false, false) if host.contains(':') => format!("[{host}]:{port}"),
+        (false, false) => format!("{host}:{port}"),
+    }
+}
+
+pub(crate) fn parse_connections(response: &JsonValue) -> Vec<Connection> {
+    response
+        .get("connections")
+        .and_then(|v| v.as_array())
+        .map(|list| list.iter().filter_map(Connection::from_json).collect())
+        .unwrap_or_default()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FilterKey {
+    Host,
+    Process,
+    Rule,
+    Chain,
+    SourceIp,
+    Network,
+}
+
+/// `--filter KEY=VALUE` 条件；除 source-ip（精确或 CIDR）与 network 外均为忽略大小写的子串匹配。
+#[derive(Debug, Clone)]
+pub(crate) struct ConnectionFilter {
+    key: FilterKey,
+    value: String,
+}
+
+impl ConnectionFilter {
+    pub(crate) fn parse(raw: &str) -> Result<Self> {
+        let Some((key, value)) = raw.split_once('=') else {
+            bail!("过滤条件格式应为 KEY=VALUE: {raw}");
+        };
+        let key = match key.trim().to_ascii_lowercase().as_str() {
+            "host" => FilterKey::Host,
+            "process" => FilterKey::Process,
+            "rule" => FilterKey::Rule,
+            "chain" => FilterKey::Chain,
+            "source-ip" | "src" | "source" => FilterKey::SourceIp,
+            "network" => FilterKey::Network,
+            other => {
+                bail!("不支持的过滤字段: {other}（可用 host/process/rule/chain/source-ip/network）")
+            }
+        };
+        let value = value.trim().to_string();
+        if value.is_empty() {
+            bail!("过滤条件的值不能为空: {raw}");
+        }
+        if key == FilterKey::SourceIp && value.contains('/') {
+            parse_cidr(&value).with_context(|| format!("无效的 CIDR: {value}"))?;
+        }
+        Ok(Self { key, value })
+    }
+
+    pub(crate) fn matches(&self, conn: &Connection) -> bool {
+        let needle = self.value.to_lowercase();
+        let contains = |text: &str| text.to_lowercase().contains(&needle);
+        match self.key {
+            FilterKey::Host => contains(&conn.host) || contains(&conn.destination),
+            FilterKey::Process => contains(&conn.process) || contains(&conn.process_path),
+            FilterKey::Rule => contains(&conn.rule) || contains(&conn.rule_payload),
+            FilterKey::Chain => conn.chains.iter().any(|c| contains(c)),
+            FilterKey::Network => conn.network.eq_ignore_ascii_case(&self.value),
+            FilterKey::SourceIp => match parse_cidr(&self.value) {
+                Some((net, prefix)) if self.value.contains('/') => conn
+                    .source_ip
+                    .parse::<IpAddr>()
+                    .is_ok_and(|ip| ip_in_cidr(ip, net, prefix)),
+                _ => conn.source_ip == self.value,
+            },
+        }
+    }
+}
+
+pub(crate) fn parse_filters(raw: &[String]) -> Result<Vec<ConnectionFilter>> {
+    raw.iter().map(|f| ConnectionFilter::parse(f)).collect()
+}
+
+pub(crate) fn matches_all(filters: &[ConnectionFilter], conn: &Connection) -> bool {
+    filters.iter().all(|f| f.matches(conn))
+}
+
+fn parse_cidr(value: &str) -> Option<(IpAddr, u8)> {
+    let (addr, prefix) = value.split_once('/').unwrap_or((value, ""));
+    let addr: IpAddr = addr.parse().ok()?;
+    let max = if addr.is_ipv4() { 32 } else { 128 };
+    let prefix = if prefix.is_empty() {
+        max
+    } else {
+        prefix.parse().ok()?
+    };
+    (prefix <= max).then_some((addr, prefix))
+}
+
+fn ip_in_cidr(ip: IpAddr, net: IpAddr, prefix: u8) -> bool {
+    match (ip, net) {
+        (IpAddr::V4(ip), IpAddr::V4(net)) => {
+            let mask = u32::MAX.checked_shl(32 - u32::from(prefix)).unwrap_or(0);
+            u32::from(ip) & mask == u32::from(net) & mask
+        }
+        (IpAddr::V6(ip), IpAddr::V6(net)) => {
+            let mask = u128::MAX.checked_shl(128 - u32::from(prefix)).unwrap_or(0);
+            u128::from(ip) & mask == u128::from(net) & mask
+        }
+        _ => false,
+    }
+}
+
+/// 按字段聚合的连接统计。
+#[derive(Debug, Clone, Serialize)]
+struct ConnectionGroup {
+    key: String,
+    count: usize,
+    upload: u64,
+    download: u64,
+}
+
+fn group_key(conn: &Connection, key: ConnectionGroupKey) -> String {
+    let value = match key {
+        ConnectionGroupKey::Host => conn.host.clone(),
+        ConnectionGroupKey::Rule => conn.rule_label(),
+        ConnectionGroupKey::Chain => conn.chain_label(),
+        ConnectionGroupKey::Process => conn.process.clone(),
+    };
+    if value.is_empty() {
+        "-".to_string()
+    } else {
+        value
+    }
+}
+
+fn aggregate(
+    connections: &[&Connection],
+    key: ConnectionGroupKey,
+    sort: Option<ConnectionSortKey>,
+) -> Vec<ConnectionGroup> {
+    let mut groups: BTreeMap<String, ConnectionGroup> = BTreeMap::new();
+    for conn in connections {
+        let name = group_key(conn, key);
+        let entry = groups
+            .entry(name.clone())
+            .or_insert_with(|| ConnectionGroup {
+                key: name,
+                count: 0,
+                upload: 0,
+                download: 0,
+            });
+        entry.count += 1;
+        entry.upload += conn.upload;
+        entry.download += conn.download;
+    }
+    let mut groups: Vec<ConnectionGroup> = groups.into_values().collect();
+    // 聚合视图默认按下行流量降序，Duration 不适用时同样按下行
+    match sort {
+        Some(ConnectionSortKey::Host) => {}
+        Some(ConnectionSortKey::Upload) => groups.sort_by_key(|v| Reverse(v.upload)),
+        _ => groups.sort_by_key(|v| Reverse(v.download)),
+    }
+    groups
+}
+
+fn sort_connections(connections: &mut [&Connection], sort: ConnectionSortKey) {
+    match sort {
+        ConnectionSortKey::Download => connections.sort_by_key(|v| Reverse(v.download)),
+        ConnectionSortKey::Upload => connections.sort_by_key(|v| Reverse(v.upload)),
+        ConnectionSortKey::Duration => connections.sort_by_key(|v| Reverse(v.duration_secs)),
+        ConnectionSortKey::Host => connections.sort_by(|a, b| a.host.cmp(&b.host)),
+    }
+}
+
+fn cmd_list(args: ApiConnectionsListArgs, common: ApiCommonArgs) -> Result<()> {
+    let filters = parse_filters(&args.filter)?;
+    let client = build_client(common.timeout_secs)?;
+    let ctx = load_api_context(&common)?;
+    let response = api_get(&client, &ctx, "/connections")?;
+    let upload_total = response
+        .get("uploadTotal")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0);
+    let download_total = response
+        .get("downloadTotal")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0);
+    let connections = parse_connections(&response);
+
+    let mut matched: Vec<&Connection> = connections
+        .iter()
+        .filter(|c| matches_all(&filters, c))
+        .collect();
+    if let Some(sort) = args.sort {
+        sort_connections(&mut matched, sort);
+    }
+    let matched_count = matched.len();
+    let groups = args.group_by.map(|key| {
+        let mut groups = aggregate(&matched, key, args.sort);
+        if let Some(limit) = args.limit {
+            groups.truncate(limit);
+        }
+        groups
+    });
+    if let Some(limit) = args.limit {
+        matched.truncate(limit);
+    }
+
+    if let Some(format) = args.export {
+        let content = match &groups {
+            Some(groups) => export_rows(groups, format, GROUP_CSV_HEADER, group_csv_row)?,
+            None => export_rows(&matched, format, CONNECTION_CSV_HEADER, |c| {
+                connection_csv_row(c)
+            })?,
+        };
+        let rows = groups.as_ref().map_or(matched.len(), Vec::len);
+        let Some(output) = &args.output else {
+            return print_line(content.strip_suffix('\n').unwrap_or(&content));
+        };
+        if let Some(parent) = output.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("创建目录失败: {}", parent.display()))?;
+        }
+        fs::write(output, content)
+            .with_context(|| format!("写入导出文件失败: {}", output.display()))?;
+        if is_json_mode() {
+            return print_json(&serde_json::json!({
+                "ok": true,
+                "action": "api.connections.export",
+                "output": output.display().to_string(),
+                "rows": rows,
+            }));
+        }
+        println!("已导出 {rows} 行到 {}", output.display());
+        return Ok(());
+    }
+
+    if is_json_mode() {
+        return print_json(&serde_json::json!({
+            "ok": true,
+            "action": "api.connections",
+            "total": connections.len(),
+            "matched": matched_count,
+            "upload_total": upload_total,
+            "download_total": download_total,
+            "group_by": args.group_by.map(|k| format!("{k:?}").to_lowercase()),
+            "groups": groups,
+            "connections": if groups.is_some() { Vec::new() } else { matched },
+        }));
+    }
+
+    println!(
+        "连接 {}/{}，总上行 {}，总下行 {}",
+        matched_count,
+        connections.len(),
+        utils::format_bytes(upload_total),
+        utils::format_bytes(download_total)
+    );
+    if let Some(groups) = groups {
+        // 中文表头每字占两列，宽度按显示列数折半
+        println!("{:>4} {:>8} {:>8}  分组", "连接", "上行", "下行");
+        for group in groups {
+            println!(
+                "{:>6} {:>10} {:>10}  {}",
+                group.count,
+                utils::format_bytes(group.upload),
+                utils::format_bytes(group.download),
+                group.key
+            );
+        }
+        return Ok(());
+    }
+    for conn in matched {
+        println!(
+            "{:>7} {:>10} {:>10}  {:<3}  {} ({})  {}  {}{}",
+            conn.duration_secs
+                .map(utils::format_duration)
+                .unwrap_or_else(|| "-".to_string()),
+            utils::format_bytes(conn.upload),
+            utils::format_bytes(conn.download),
+            conn.network,
+            conn.host,
+            conn.destination,
+            conn.rule_label(),
+            conn.chain_label(),
+            if conn.process.is_empty() {
+                String::new()
+            } else {
+                format!("  [{}]", conn.process)
+            }
+        );
+    }
+    Ok(())
+}
+
+const CONNECTION_CSV_HEADER: &str = "id,network,host,destination,source,process,rule,rule_payload,chain,upload,download,start,duration_secs";
+const GROUP_CSV_HEADER: &str = "key,count,upload,download";
+
+fn connection_csv_row(conn: &Connection) -> Vec<String> {
+    vec![
+        conn.id.clone(),
+        conn.network.clone(),
+        conn.host.clone(),
+        conn.destination.clone(),
+        conn.source.clone(),
+        conn.process.clone(),
+        conn.rule.clone(),
+        conn.rule_payload.clone(),
+        conn.chain_label(),
+        conn.upload.to_string(),
+        conn.download.to_string(),
+        conn.start.clone(),
+        conn.duration_secs
+            .map(|d| d.to_string())
+            .unwrap_or_default(),
+    ]
+}
+
+fn group_csv_row(group: &ConnectionGroup) -> Vec<String> {
+    vec![
+        group.key.clone(),
+        group.count.to_string(),
+        group.upload.to_string(),
+        group.download.to_string(),
+    ]
+}
+
+fn export_rows<T: Serialize>(
+    rows: &[T],
+    format: ConnectionExportFormat,
+    header: &str,
+    to_csv: impl Fn(&T) -> Vec<String>,
+) -> Result<String> {
+    let mut out = String::new();
+    match format {
+        ConnectionExportFormat::Csv => {
+            out.push_str(header);
+            out.push('\n');
+            for row in rows {
+                let fields: Vec<String> = to_csv(row).iter().map(|f| csv_escape(f)).collect();
+                out.push_str(&fields.join(","));
+                out.push('\n');
+            }
+        }
+        ConnectionExportFormat::Jsonl => {
+            for row in rows {
+                out.push_str(&serde_json::to_string(row).context("序列化连接失败")?);
+                out.push('\n');
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// 含逗号、引号或换行的字段按 RFC 4180 加引号。
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn cmd_close(args: ApiConnectionsCloseArgs, common: ApiCommonArgs) -> Result<()> {
+    if !args.all && args.id.is_empty() && args.filter.is_empty() {
+        bail!("请指定 --all、--id 或 --filter");
+    }
+    let filters = parse_filters(&args.filter)?;
+    let client = build_client(common.timeout_secs)?;
+    let ctx = load_api_context(&common)?;
+    let connections = parse_connections(&api_get(&client, &ctx, "/connections")?);
+
+    let mut missing: Vec<String> = Vec::new();
+    let targets: Vec<&Connection> = if args.all {
+        connections.iter().collect()
+    } else if !args.id.is_empty() {
+        missing = args
+            .id
+            .iter()
+            .filter(|id| !connections.iter().any(|c| &c.id == *id))
+            .cloned()
+            .collect();
+        connections
+            .iter()
+            .filter(|c| args.id.contains(&c.id) && matches_all(&filters, c))
+            .collect()
+    } else {
+        connections
+            .iter()
+            .filter(|c| matches_all(&filters, c))
+            .collect()
+    };
+
+    let mut failed: Vec<(String, String)> = Vec::new();
+    if !args.dry_run {
+        if args.all {
+            api_delete(&client, &ctx, "/connections").context("关闭全部连接失败")?;
+        } else {
+            for conn in &targets {
+                let path = api_path(&["connections", &conn.id], &[]);
+                if let Err(err) = api_delete(&client, &ctx, &path) {
+                    failed.push((conn.id.clone(), format!("{err:#}")));
+                }
+            }
+        }
+    }
+
+    if is_json_mode() {
+        return print_json(&serde_json::json!({
+            "ok": failed.is_empty(),
+            "action": "api.connections.close",
+            "dry_run": args.dry_run,
+            "all": args.all,
+            "matched": targets.len(),
+            "closed": if args.dry_run { 0 } else { targets.len() - failed.len() },
+            "connections": targets,
+            "missing": missing,
+            "failed": failed
+                .iter()
+                .map(|(id, err)| serde_json::json!({ "id": id, "error": err }))
+                .collect::<Vec<_>>(),
+        }));
+    }
+
+    for conn in &targets {
+        println!(
+            "{}  {} {}  {}  {}  ↑{} ↓{}",
+            conn.id,
+            conn.network,
+            if conn.host.is_empty() {
+                &conn.destination
+            } else {
+                &conn.host
+            },
+            conn.rule_label(),
+            conn.chain_label(),
+            utils::format_bytes(conn.upload),
+            utils::format_bytes(conn.download)
+        );
+    }
+    for id in &missing {
+        println!("未找到连接: {id}");
+    }
+    for (id, err) in &failed {
+        println!("关闭失败: {id}: {err}");
+    }
+    if args.dry_run {
+        println!("将关闭 {} 个连接（dry-run，未实际关闭）", targets.len());
+    } else {
+        println!("已关闭 {} 个连接", targets.len() - failed.len());
+    }
+    if !failed.is_empty() {
+        bail!("{} 个连接关闭失败", failed.len());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aggregate_and_export_should_summarize_connections() {
+        let conns = sample();
+        let refs: Vec<&Connection> = conns.iter().collect();
+        let groups = aggregate(&refs, ConnectionGroupKey::Chain, None);
+        assert_eq!(groups[0].key, "PROXY -> 🇭🇰 香港 01");
+        assert_eq!(groups[0].download, 2048);
+        assert_eq!(groups[1].key, "DIRECT");
+
+        let csv = export_rows(
+            &refs,
+            ConnectionExportFormat::Csv,
+            CONNECTION_CSV_HEADER,
+            |c| connection_csv_row(c),
+        )
+        .expect("导出 CSV 失败");
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].starts_with("c1,tcp,www.google.com,142.250.1.1:443,"));
+        assert_eq!(csv_escape("a,\"b\""), "\"a,\"\"b\"\"\"");
+
+        let jsonl = export_rows(&refs, ConnectionExportFormat::Jsonl, "", |_| Vec::new())
+            .expect("导出 JSONL 失败");
+        assert_eq!(jsonl.lines().count(), 2);
+    }
+
+    fn sample() -> Vec<Connection> {
+        parse_connections(&serde_json::json!({
+            "connections": [
+                {
+                    "id": "c1",
+                    "metadata": {
+                        "network": "tcp", "sourceIP": "192.168.1.20", "sourcePort": "50000",
+                        "destinationIP": "142.250.1.1", "destinationPort": "443",
+                        "host": "www.google.com", "process": "curl", "processPath": "/usr/bin/curl"
+                    },
+                    "upload": 100, "download": 2048,
+                    "chains": ["🇭🇰 香港 01", "PROXY"],
+                    "rule": "DomainSuffix", "rulePayload": "google.com"
+                },
+                {
+                    "id": "c2",
+                    "metadata": {
+                        "network": "udp", "sourceIP": "10.0.0.5", "sourcePort": "53000",
+                        "destinationIP": "1.1.1.1", "destinationPort": "53", "host": ""
+                    },
+                    "chains": ["DIRECT"], "rule": "Match", "rulePayload": ""
+                }
+            ]
+        }))
+    }
+
+    #[test]
+    fn filters_should_match_connection_fields() {
+        let conns = sample();
+        assert_eq!(conns[1].host, "1.1.1.1");
+        assert_eq!(conns[0].chain_label(), "PROXY -> 🇭🇰 香港 01");
+
+        let pick = |raw: &[&str]| -> Vec<String> {
+            let raw: Vec<String> = raw.iter().map(|s| s.to_string()).collect();
+            let filters = parse_filters(&raw).expect("解析过滤条件失败");
+            conns
+                .iter()
+                .filter(|c| matches_all(&filters, c))
+                .map(|c| c.id.clone())
+                .collect()
+        };
+        assert_eq!(pick(&["host=GOOGLE"]), vec!["c1"]);
+        assert_eq!(pick(&["chain=direct"]), vec!["c2"]);
+        assert_eq!(pick(&["rule=google.com", "process=curl"]), vec!["c1"]);
+        assert_eq!(pick(&["source-ip=192.168.0.0/16"]), vec!["c1"]);
+        assert_eq!(pick(&["source-ip=10.0.0.5"]), vec!["c2"]);
+        assert_eq!(pick(&["network=UDP"]), vec!["c2"]);
+        assert!(ConnectionFilter::parse("port=1").is_err());
+        assert!(ConnectionFilter::parse("host").is_err());
+        assert!(ConnectionFilter::parse("source-ip=10.0.0.0/40").is_err());
+    }
+}
@@ -0,0 +1,271 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use anyhow::{Context, Result, bail};
+use reqwest::blocking::Client;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+use super::proxies::{ProxyInfo, parse_proxies};
+use super::{ApiContext, api_get, api_get_with_status, api_path, api_put, build_client};
+use crate::cli::ApiDelayArgs;
+use crate::output::{is_json_mode, print_json};
+
+#[derive(Debug, Clone, Serialize)]
+struct DelayResult {
+    name: String,
+    /// 延迟毫秒，失败为 None
+    delay: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+pub fn run(args: ApiDelayArgs) -> Result<()> {
+    // 客户端超时需覆盖测速超时，避免慢节点被 HTTP 超时误判
+    let timeout_secs = args
+        .api
+        .timeout_secs
+        .max(args.timeout_ms.div_ceil(1000) + 5);
+    let client = build_client(timeout_secs)?;
+    let ctx = super::load_api_context(&args.api)?;
+    let timeout = args.timeout_ms.to_string();
+    let query = [("url", args.url.as_str()), ("timeout", timeout.as_str())];
+
+    if args.proxy.is_none() && args.group.is_none() && !args.all {
+        bail!("请指定 --proxy、--group 或 --all");
+    }
+
+    let mut proxies = BTreeMap::new();
+    let (scope, mut results, previous) = if let Some(name) = &args.proxy {
+        let result = test_proxy(&client, &ctx, name, &query)?;
+        (format!("节点 {name}"), vec![result], None)
+    } else if let Some(group) = &args.group {
+        proxies = parse_proxies(&api_get(&client, &ctx, "/proxies")?);
+        let Some(info) = proxies.get(group).filter(|i| i.is_group()) else {
+            bail!("未找到策略组: {group}");
+        };
+        if args.select_fastest && info.kind != "Selector" {
+            bail!(
+                "策略组 {group} 类型为 {}，仅 Selector 支持切换到最快节点",
+                info.kind
+            );
+        }
+        let results = test_group(&client, &ctx, info, &query)?;
+        (format!("策略组 {group}"), results, info.now.clone())
+    } else {
+        proxies = parse_proxies(&api_get(&client, &ctx, "/proxies")?);
+        let names: Vec<String> = proxies
+            .values()
+            .filter(|i| i.is_outbound_node())
+            .map(|i| i.name.clone())
+            .collect();
+        let results = test_concurrently(&client, &ctx, &names, &query, args.concurrency);
+        ("全部节点".to_string(), results, None)
+    };
+    sort_results(&mut results);
+
+    // DIRECT 等内置出站延迟最低但不是有意义的“最快节点”
+    let fastest = results
+        .iter()
+        .find(|r| r.delay.is_some() && !proxies.get(&r.name).is_some_and(ProxyInfo::is_builtin))
+        .cloned();
+    let mut selected = None;
+    if args.select_fastest
+        && let Some(group) = &args.group
+    {
+        let Some(winner) = &fastest else {
+            bail!("策略组 {group} 中没有测速成功的节点，未切换");
+        };
+        if previous.as_deref() != Some(winner.name.as_str()) {
+            api_put(
+                &client,
+                &ctx,
+                &api_path(&["proxies", group], &[]),
+                serde_json::json!({ "name": winner.name }),
+            )
+            .with_context(|| format!("切换策略组 {group} 到 {} 失败", winner.name))?;
+        }
+        selected = Some(winner.name.clone());
+    }
+
+    if is_json_mode() {
+        return print_json(&serde_json::json!({
+            "ok": true,
+            "action": "api.delay",
+            "scope": scope,
+            "url": args.url,
+            "timeout_ms": args.timeout_ms,
+            "tested": results.len(),
+            "succeeded": results.iter().filter(|r| r.delay.is_some()).count(),
+            "fastest": fastest,
+            "previous": previous,
+            "selected": selected,
+            "results": results,
+        }));
+    }
+
+    println!(
+        "测速范围: {scope}（{}，超时 {}ms）",
+        args.url, args.timeout_ms
+    );
+    for result in &results {
+        match (result.delay, &result.error) {
+            (Some(delay), _) => println!("{:>8}  {}", format!("{delay}ms"), result.name),
+            (None, Some(err)) => println!("{:>8}  {}  ({err})", "✗", result.name),
+            (None, None) => println!("{:>8}  {}", "✗", result.name),
+        }
+    }
+    let ok = results.iter().filter(|r| r.delay.is_some()).count();
+    println!("成功 {ok}/{}", results.len());
+    if let Some(name) = selected {
+        match previous {
+            Some(prev) if prev != name => println!("已切换: {prev} -> {name}"),
+            _ => println!("当前已是最快节点: {name}"),
+        }
+    }
+    Ok(())
+}
+
+fn test_proxy(
+    client: &Client,
+    ctx: &ApiContext,
+    name: &str,
+    query: &[(&str, &str)],
+) -> Result<DelayResult> {
+    let path = api_path(&["proxies", name, "delay"], query);
+    let (status, body) = api_get_with_status(client, ctx, &path)?;
+    if status == 404 {
+        bail!("未找到节点: {name}");
+    }
+    Ok(delay_result(name, status, &body))
+}
+
+fn delay_result(name: &str, status: u16, body: &JsonValue) -> DelayResult {
+    let delay = body
+        .get("delay")
+        .and_then(|v| v.as_u64())
+        .filter(|d| *d > 0);
+    let error = match (status, delay) {
+        (200, Some(_)) => None,
+        (504, _) => Some("超时".to_string()),
+        _ => Some(
+            body.get("message")
+                .and_then(|v| v.as_str())
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("HTTP {status}")),
+        ),
+    };
+    DelayResult {
+        name: name.to_string(),
+        delay: if error.is_none() { delay } else { None },
+        error,
+    }
+}
+
+/// 策略组测速：mihomo 只返回成功成员的延迟，缺失的成员记为失败。
+fn test_group(
+    client: &Client,
+    ctx: &ApiContext,
+    group: &ProxyInfo,
+    query: &[(&str, &str)],
+) -> Result<Vec<DelayResult>> {
+    let path = api_path(&["group", &group.name, "delay"], query);
+    let (status, body) = api_get_with_status(client, ctx, &path)?;
+    if status == 404 {
+        bail!("未找到策略组: {}", group.name);
+    }
+    let failure = match status {
+        200 | 504 => "超时".to_string(),
+        _ => body
+            .get("message")
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("HTTP {status}")),
+    };
+    Ok(group
+        .members
+        .iter()
+        .map(|name| {
+            let delay = body
+                .get(name)
+                .and_then(|v| v.as_u64())
+                .filter(|d| *d > 0 && status == 200);
+            DelayResult {
+                name: name.clone(),
+                delay,
+                error: delay.is_none().then(|| failure.clone()),
+            }
+        })
+        .collect())
+}
+
+/// 固定数量的工作线程按下标领取节点并发测速。
+fn test_concurrently(
+    client: &Client,
+    ctx: &ApiContext,
+    names: &[String],
+    query: &[(&str, &str)],
+    concurrency: usize,
+) -> Vec<DelayResult> {
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::with_capacity(names.len()));
+    let workers = concurrency.clamp(1, names.len().max(1));
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| {
+                loop {
+                    let idx = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(name) = names.get(idx) else {
+                        break;
+                    };
+                    let result =
+                        test_proxy(client, ctx, name, query).unwrap_or_else(|err| DelayResult {
+                            name: name.clone(),
+                            delay: None,
+                            error: Some(format!("{err:#}")),
+                        });
+                    if let Ok(mut list) = results.lock() {
+                        list.push(result);
+                    }
+                }
+            });
+        }
+    });
+    results.into_inner().unwrap_or_default()
+}
+
+/// 成功的按延迟升序，失败的排在最后并按名称排序。
+fn sort_results(results: &mut [DelayResult]) {
+    results.sort_by(|a, b| match (a.delay, b.delay) {
+        (Some(x), Some(y)) => x.cmp(&y).then_with(|| a.name.cmp(&b.name)),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => a.name.cmp(&b.name),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delay_results_should_sort_successes_first() {
+        let mut results = vec![
+            delay_result("b", 504, &serde_json::json!({"message": "Timeout"})),
+            delay_result("c", 200, &serde_json::json!({"delay": 120})),
+            delay_result(
+                "a",
+                503,
+                &serde_json::json!({"message": "An error occurred"}),
+            ),
+            delay_result("d", 200, &serde_json::json!({"delay": 45})),
+        ];
+        sort_results(&mut results);
+        let order: Vec<&str> = results.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(order, vec!["d", "c", "a", "b"]);
+        assert_eq!(results[2].error.as_deref(), Some("An error occurred"));
+        assert_eq!(results[3].error.as_deref(), Some("超时"));
+    }
+}
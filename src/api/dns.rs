@@ -0,0 +1,182 @@
+use anyhow::{Context, Result, bail};
+use reqwest::blocking::Client;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+use super::{ApiContext, api_get_with_status, api_path, api_post, build_client, load_api_context};
+use crate::cli::{ApiCacheCommand, ApiCommonArgs, ApiDnsCommand};
+use crate::output::{is_json_mode, print_json};
+
+#[derive(Debug, Serialize)]
+struct DnsAnswer {
+    name: String,
+    #[serde(rename = "type")]
+    kind: String,
+    ttl: u64,
+    data: String,
+}
+
+pub fn run_dns(action: ApiDnsCommand, common: ApiCommonArgs) -> Result<()> {
+    let ApiDnsCommand::Query(args) = action;
+    let client = build_client(common.timeout_secs)?;
+    let ctx = load_api_context(&common)?;
+    let kind = args.kind.to_ascii_uppercase();
+    let path = api_path(
+        &["dns", "query"],
+        &[("name", args.name.as_str()), ("type", kind.as_str())],
+    );
+    let (status, response) = api_get_with_status(&client, &ctx, &path)?;
+    if status != 200 {
+        let message = response
+            .get("message")
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("HTTP {status}"));
+        bail!("DNS 查询失败: {message}（请确认内核已启用 dns）");
+    }
+
+    let rcode = response.get("Status").and_then(|v| v.as_u64()).unwrap_or(0);
+    let answers = parse_answers(&response);
+    if is_json_mode() {
+        return print_json(&serde_json::json!({
+            "ok": true,
+            "action": "api.dns.query",
+            "name": args.name,
+            "type": kind,
+            "status": rcode_name(rcode),
+            "answers": answers,
+            "response": response,
+        }));
+    }
+
+    println!("查询 {} ({kind}): {}", args.name, rcode_name(rcode));
+    if answers.is_empty() {
+        println!("  （无应答记录）");
+    }
+    let width = answers.iter().map(|a| a.name.len()).max().unwrap_or(0);
+    for answer in &answers {
+        println!(
+            "  {:<width$}  {:>6}  {:<6} {}",
+            answer.name, answer.ttl, answer.kind, answer.data
+        );
+    }
+    Ok(())
+}
+
+pub fn run_cache(action: ApiCacheCommand, common: ApiCommonArgs) -> Result<()> {
+    let ApiCacheCommand::Flush(args) = action;
+    if !args.fakeip && !args.dns {
+        bail!("请指定 --fakeip 和/或 --dns");
+    }
+    let client = build_client(common.timeout_secs)?;
+    let ctx = load_api_context(&common)?;
+    if args.fakeip {
+        flush_fakeip_cache(&client, &ctx)?;
+    }
+    if args.dns {
+        flush_dns_cache(&client, &ctx)?;
+    }
+
+    if is_json_mode() {
+        return print_json(&serde_json::json!({
+            "ok": true,
+            "action": "api.cache.flush",
+            "fakeip": args.fakeip,
+            "dns": args.dns,
+        }));
+    }
+    if args.fakeip {
+        println!("已清空 fake-ip 缓存");
+    }
+    if args.dns {
+        println!("已清空 DNS 缓存");
+    }
+    Ok(())
+}
+
+pub(crate) fn flush_fakeip_cache(client: &Client, ctx: &ApiContext) -> Result<()> {
+    api_post(client, ctx, "/cache/fakeip/flush", None).context("清空 fake-ip 缓存失败")?;
+    Ok(())
+}
+
+pub(crate) fn flush_dns_cache(client: &Client, ctx: &ApiContext) -> Result<()> {
+    api_post(client, ctx, "/cache/dns/flush", None).context("清空 DNS 缓存失败")?;
+    Ok(())
+}
+
+/// `/dns/query` 返回 miekg/dns 风格的报文：Answer 中 type 为数字记录类型。
+fn parse_answers(response: &JsonValue) -> Vec<DnsAnswer> {
+    let Some(list) = response.get("Answer").and_then(|v| v.as_array()) else {
+        return Vec::new();
+    };
+    list.iter()
+        .map(|item| DnsAnswer {
+            name: item
+                .get("name")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string(),
+            kind: item
+                .get("type")
+                .and_then(|v| v.as_u64())
+                .map(record_type_name)
+                .unwrap_or_default(),
+            ttl: item.get("TTL").and_then(|v| v.as_u64()).unwrap_or(0),
+            data: item
+                .get("data")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string(),
+        })
+        .collect()
+}
+
+fn record_type_name(code: u64) -> String {
+    match code {
+        1 => "A".to_string(),
+        2 => "NS".to_string(),
+        5 => "CNAME".to_string(),
+        6 => "SOA".to_string(),
+        12 => "PTR".to_string(),
+        15 => "MX".to_string(),
+        16 => "TXT".to_string(),
+        28 => "AAAA".to_string(),
+        33 => "SRV".to_string(),
+        64 => "SVCB".to_string(),
+        65 => "HTTPS".to_string(),
+        other => format!("TYPE{other}"),
+    }
+}
+
+fn rcode_name(code: u64) -> String {
+    match code {
+        0 => "NOERROR".to_string(),
+        1 => "FORMERR".to_string(),
+        2 => "SERVFAIL".to_string(),
+        3 => "NXDOMAIN".to_string(),
+        4 => "NOTIMP".to_string(),
+        5 => "REFUSED".to_string(),
+        other => format!("RCODE{other}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_answers_should_name_record_types() {
+        let answers = parse_answers(&serde_json::json!({
+            "Status": 0,
+            "Answer": [
+                {"name": "www.example.com.", "type": 5, "TTL": 60, "data": "example.com."},
+                {"name": "example.com.", "type": 1, "TTL": 300, "data": "93.184.216.34"},
+                {"name": "example.com.", "type": 99, "TTL": 1, "data": "x"}
+            ]
+        }));
+        let kinds: Vec<&str> = answers.iter().map(|a| a.kind.as_str()).collect();
+        assert_eq!(kinds, vec!["CNAME", "A", "TYPE99"]);
+        assert_eq!(answers[1].ttl, 300);
+        assert_eq!(rcode_name(3), "NXDOMAIN");
+    }
+}
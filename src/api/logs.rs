@@ -0,0 +1,195 @@
+use std::env;
+use std::io::{self, BufRead, BufReader, IsTerminal};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use super::{api_path, api_stream, build_stream_client, load_api_context};
+use crate::cli::{ApiLogLevel, ApiLogsArgs};
+use crate::output::{is_json_mode, print_json_line, print_line};
+use crate::utils;
+
+#[derive(Debug, Serialize)]
+struct LogEntry {
+    time: u64,
+    level: String,
+    message: String,
+    /// 以下字段从连接日志中解析（`--> host:port match 规则 using 出站`），其他日志为空
+    #[serde(skip_serializing_if = "String::is_empty")]
+    host: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    rule: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    chain: String,
+}
+
+struct LogFilter {
+    host: Option<String>,
+    rule: Option<String>,
+    grep: Vec<String>,
+}
+
+pub fn run(args: ApiLogsArgs) -> Result<()> {
+    let ctx = load_api_context(&args.api)?;
+    let client = build_stream_client()?;
+    let json = is_json_mode();
+    let color =
+        !json && !args.no_color && env::var_os("NO_COLOR").is_none() && io::stdout().is_terminal();
+    let filter = LogFilter {
+        host: args.host.as_deref().map(str::to_lowercase),
+        rule: args.rule.as_deref().map(str::to_lowercase),
+        grep: args.grep.iter().map(|g| g.to_lowercase()).collect(),
+    };
+
+    let path = api_path(&["logs"], &[("level", args.level.as_api_str())]);
+    let response = api_stream(&client, &ctx, &path)?;
+    if !json {
+        eprintln!(
+            "正在读取 {} 的日志（级别 >= {}，Ctrl+C 退出）",
+            ctx.base_url,
+            args.level.as_api_str()
+        );
+    }
+
+    let mut printed = 0u64;
+    for line in BufReader::new(response).lines() {
+        let line = line.context("读取日志流失败")?;
+        let Some(entry) = parse_log_line(&line) else {
+            continue;
+        };
+        // 控制器已按级别过滤，这里再兜底一次以兼容忽略 level 参数的实现
+        if level_of(&entry.level) < args.level || !filter.matches(&entry) {
+            continue;
+        }
+        if json {
+            print_json_line(&entry)?;
+        } else {
+            print_text(&entry, color)?;
+        }
+        printed += 1;
+        if args.count.is_some_and(|count| printed >= count) {
+            break;
+        }
+    }
+    Ok(())
+}
+
+impl LogFilter {
+    fn matches(&self, entry: &LogEntry) -> bool {
+        let message = entry.message.to_lowercase();
+        self.host
+            .as_ref()
+            .is_none_or(|host| entry.host.to_lowercase().contains(host))
+            && self
+                .rule
+                .as_ref()
+                .is_none_or(|rule| entry.rule.to_lowercase().contains(rule))
+            && self.grep.iter().all(|g| message.contains(g))
+    }
+}
+
+/// 解析 `/logs` 推送的一行：`{"type":"info","payload":"..."}`。
+fn parse_log_line(line: &str) -> Option<LogEntry> {
+    let value: serde_json::Value = serde_json::from_str(line.trim()).ok()?;
+    let level = value.get("type").and_then(|v| v.as_str())?.to_string();
+    let message = value
+        .get("payload")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string();
+    let (host, rule, chain) = parse_connection_message(&message);
+    Some(LogEntry {
+        time: utils::now_unix(),
+        level,
+        message,
+        host,
+        rule,
+        chain,
+    })
+}
+
+/// 从 `[TCP] src --> host:port match DomainSuffix(x) using PROXY[node]` 中提取目标、规则与出站。
+fn parse_connection_message(message: &str) -> (String, String, String) {
+    let Some((_, rest)) = message.split_once("--> ") else {
+        return Default::default();
+    };
+    let target = rest.split_whitespace().next().unwrap_or_default();
+    let host = match target.rsplit_once(':') {
+        Some((host, port)) if port.chars().all(|c| c.is_ascii_digit()) => host,
+        _ => target,
+    };
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let rule = rest
+        .split_once(" match ")
+        .map(|(_, tail)| tail.split(" using ").next().unwrap_or(tail))
+        .unwrap_or_default();
+    let chain = rest
+        .split_once(" using ")
+        .map(|(_, tail)| tail)
+        .unwrap_or_default();
+    (
+        host.to_string(),
+        rule.trim().to_string(),
+        chain.trim().to_string(),
+    )
+}
+
+fn level_of(level: &str) -> ApiLogLevel {
+    match level {
+        "debug" => ApiLogLevel::Debug,
+        "warning" => ApiLogLevel::Warning,
+        "error" => ApiLogLevel::Error,
+        _ => ApiLogLevel::Info,
+    }
+}
+
+fn print_text(entry: &LogEntry, color: bool) -> Result<()> {
+    let label = match entry.level.as_str() {
+        "warning" => "WARN",
+        other => &other.to_uppercase(),
+    };
+    let line = if color {
+        let code = match entry.level.as_str() {
+            "debug" => "90",
+            "warning" => "33",
+            "error" => "31",
+            _ => "32",
+        };
+        format!("\x1b[{code}m{label:<5}\x1b[0m {}", entry.message)
+    } else {
+        format!("{label:<5} {}", entry.message)
+    };
+    print_line(&line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_log_line_should_extract_connection_fields() {
+        let entry = parse_log_line(
+            r#"{"type":"info","payload":"[TCP] 127.0.0.1:50001(curl) --> www.google.com:443 match DomainSuffix(google.com) using PROXY[HK 01]"}"#,
+        )
+        .expect("解析连接日志失败");
+        assert_eq!(entry.host, "www.google.com");
+        assert_eq!(entry.rule, "DomainSuffix(google.com)");
+        assert_eq!(entry.chain, "PROXY[HK 01]");
+
+        let v6 = parse_connection_message("[UDP] [::1]:5353 --> [2001:db8::1]:53 using DIRECT");
+        assert_eq!(v6, ("2001:db8::1".into(), String::new(), "DIRECT".into()));
+
+        let plain = parse_log_line(r#"{"type":"warning","payload":"dial failed"}"#)
+            .expect("解析普通日志失败");
+        assert!(plain.host.is_empty());
+
+        let filter = LogFilter {
+            host: Some("google".into()),
+            rule: None,
+            grep: vec!["curl".into()],
+        };
+        assert!(filter.matches(&entry));
+        assert!(!filter.matches(&plain));
+        assert!(parse_log_line("not json").is_none());
+    }
+}
@@ -1,7 +1,17 @@
+mod connections;
+mod delay;
+mod dns;
+mod logs;
+mod providers;
+mod proxies;
+mod rules;
+mod watch;
+
 use std::fs;
 use std::time::Duration;
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
+use reqwest::Url;
 use reqwest::blocking::{Client, RequestBuilder};
 use serde_json::Value as JsonValue;
 use serde_yaml::Value as YamlValue;
@@ -11,9 +21,12 @@ use crate::constants;
 use crate::output::{is_json_mode, print_json};
 use crate::paths::app_paths;
 
+pub(crate) use dns::{flush_dns_cache, flush_fakeip_cache};
+pub(crate) use proxies::{ProxyInfo, parse_proxies};
+
 #[derive(Debug, Clone)]
-struct ApiContext {
-    base_url: String,
+pub(crate) struct ApiContext {
+    pub(crate) base_url: String,
     secret: Option<String>,
 }
 
@@ -21,8 +34,23 @@ pub fn run(command: ApiCommand) -> Result<()> {
     match command {
         ApiCommand::Status(common) => cmd_status(common),
         ApiCommand::Mode { action, common } => cmd_mode(action, common),
-        ApiCommand::Proxies(common) => cmd_proxies(common),
-        ApiCommand::Connections(common) => cmd_connections(common),
+        ApiCommand::Proxies {
+            action,
+            list,
+            common,
+        } => proxies::run(action, list, common),
+        ApiCommand::Delay(args) => delay::run(args),
+        ApiCommand::Connections {
+            action,
+            list,
+            common,
+        } => connections::run(action, list, common),
+        ApiCommand::Watch(args) => watch::run(args),
+        ApiCommand::Logs(args) => logs::run(args),
+        ApiCommand::Rules(args) => rules::run(args),
+        ApiCommand::Providers { action, common } => providers::run(action, common),
+        ApiCommand::Dns { action, common } => dns::run_dns(action, common),
+        ApiCommand::Cache { action, common } => dns::run_cache(action, common),
         ApiCommand::UiUrl(common) => cmd_ui_url(common),
     }
 }
@@ -99,60 +127,6 @@ fn cmd_mode(action: ApiModeCommand, common: ApiCommonArgs) -> Result<()> {
     Ok(())
 }
 
-fn cmd_proxies(common: ApiCommonArgs) -> Result<()> {
-    let client = build_client(common.timeout_secs)?;
-    let ctx = load_api_context(&common)?;
-    let response = api_get(&client, &ctx, "/proxies")?;
-
-    if is_json_mode() {
-        return print_json(&serde_json::json!({
-            "ok": true,
-            "action": "api.proxies",
-            "response": response
-        }));
-    }
-
-    let count = response
-        .get("proxies")
-        .and_then(|v| v.as_object())
-        .map(|m| m.len())
-        .unwrap_or(0);
-    println!("代理对象数量: {}", count);
-    Ok(())
-}
-
-fn cmd_connections(common: ApiCommonArgs) -> Result<()> {
-    let client = build_client(common.timeout_secs)?;
-    let ctx = load_api_context(&common)?;
-    let response = api_get(&client, &ctx, "/connections")?;
-
-    if is_json_mode() {
-        return print_json(&serde_json::json!({
-            "ok": true,
-            "action": "api.connections",
-            "response": response
-        }));
-    }
-
-    let total = response
-        .get("connections")
-        .and_then(|v| v.as_array())
-        .map(|v| v.len())
-        .unwrap_or(0);
-    let down = response
-        .get("downloadTotal")
-        .and_then(|v| v.as_u64())
-        .unwrap_or(0);
-    let up = response
-        .get("uploadTotal")
-        .and_then(|v| v.as_u64())
-        .unwrap_or(0);
-    println!("连接数: {}", total);
-    println!("总下行: {}", down);
-    println!("总上行: {}", up);
-    Ok(())
-}
-
 fn cmd_ui_url(common: ApiCommonArgs) -> Result<()> {
     let ctx = load_api_context(&common)?;
     let paths = app_paths()?;
@@ -193,7 +167,7 @@ struct RuntimeUiFields {
     external_ui_url: Option<String>,
 }
 
-fn build_client(timeout_secs: u64) -> Result<Client> {
+pub(crate) fn build_client(timeout_secs: u64) -> Result<Client> {
     Client::builder()
         .timeout(Duration::from_secs(timeout_secs))
         .connect_timeout(Duration::from_secs(8))
@@ -201,7 +175,15 @@ fn build_client(timeout_secs: u64) -> Result<Client> {
         .context("创建 API 客户端失败")
 }
 
-fn load_api_context(common: &ApiCommonArgs) -> Result<ApiContext> {
+/// 流式接口（/traffic、/logs）持续输出，不能设置整体超时，仅限制建连时间。
+pub(crate) fn build_stream_client() -> Result<Client> {
+    Client::builder()
+        .connect_timeout(Duration::from_secs(8))
+        .build()
+        .context("创建 API 客户端失败")
+}
+
+pub(crate) fn load_api_context(common: &ApiCommonArgs) -> Result<ApiContext> {
     let paths = app_paths()?;
 
     let (config_controller, config_secret) = load_runtime_api_fields(&paths.runtime_config_file)?;
@@ -264,15 +246,30 @@ fn build_dashboard_url(controller: &str) -> String {
 }
 
 fn apply_secret(req: RequestBuilder, ctx: &ApiContext) -> RequestBuilder {
-    if let Some(secret) = &ctx.secret {
-        if !secret.is_empty() {
-            return req.header("Authorization", format!("Bearer {}", secret));
-        }
+    if let Some(secret) = &ctx.secret
+        && !secret.is_empty()
+    {
+        return req.header("Authorization", format!("Bearer {}", secret));
     }
     req
 }
 
-fn api_get(client: &Client, ctx: &ApiContext, path: &str) -> Result<JsonValue> {
+/// 按路径段编码构造 controller 路径；组名/节点名可能含空格、emoji 等字符。
+pub(crate) fn api_path(segments: &[&str], query: &[(&str, &str)]) -> String {
+    let mut url = Url::parse("http://localhost/").expect("固定 URL 合法");
+    if let Ok(mut path) = url.path_segments_mut() {
+        path.pop_if_empty().extend(segments);
+    }
+    if !query.is_empty() {
+        url.query_pairs_mut().extend_pairs(query);
+    }
+    match url.query() {
+        Some(q) => format!("{}?{}", url.path(), q),
+        None => url.path().to_string(),
+    }
+}
+
+pub(crate) fn api_get(client: &Client, ctx: &ApiContext, path: &str) -> Result<JsonValue> {
     let url = format!("{}{}", ctx.base_url, path);
     let req = apply_secret(client.get(&url), ctx);
     let resp = req
@@ -280,11 +277,31 @@ fn api_get(client: &Client, ctx: &ApiContext, path: &str) -> Result<JsonValue> {
         .with_context(|| format!("请求失败: {}", url))?
         .error_for_status()
         .with_context(|| format!("请求返回非成功状态: {}", url))?;
-    resp.json::<JsonValue>()
-        .with_context(|| format!("解析响应失败: {}", url))
+    // 健康检查等接口成功时返回 204 空响应体
+    read_optional_json(resp, &url)
+}
+
+/// GET 请求但不把非 2xx 视为错误，返回状态码与响应体（非 JSON 时为 null）。
+pub(crate) fn api_get_with_status(
+    client: &Client,
+    ctx: &ApiContext,
+    path: &str,
+) -> Result<(u16, JsonValue)> {
+    let url = format!("{}{}", ctx.base_url, path);
+    let resp = apply_secret(client.get(&url), ctx)
+        .send()
+        .with_context(|| format!("请求失败: {}", url))?;
+    let status = resp.status().as_u16();
+    let body = resp
+        .text()
+        .with_context(|| format!("读取响应失败: {}", url))?;
+    Ok((
+        status,
+        serde_json::from_str(&body).unwrap_or(JsonValue::Null),
+    ))
 }
 
-fn api_patch(
+pub(crate) fn api_patch(
     client: &Client,
     ctx: &ApiContext,
     path: &str,
@@ -297,8 +314,87 @@ fn api_patch(
         .with_context(|| format!("请求失败: {}", url))?
         .error_for_status()
         .with_context(|| format!("请求返回非成功状态: {}", url))?;
-    resp.json::<JsonValue>()
-        .with_context(|| format!("解析响应失败: {}", url))
+    // PATCH /configs 成功时返回 204 空响应体
+    read_optional_json(resp, &url)
+}
+
+/// 打开流式 GET 接口，返回可逐行读取的响应（mihomo 以换行分隔的 JSON 持续推送）。
+pub(crate) fn api_stream(
+    client: &Client,
+    ctx: &ApiContext,
+    path: &str,
+) -> Result<reqwest::blocking::Response> {
+    let url = format!("{}{}", ctx.base_url, path);
+    apply_secret(client.get(&url), ctx)
+        .send()
+        .with_context(|| format!("请求失败: {}", url))?
+        .error_for_status()
+        .with_context(|| format!("请求返回非成功状态: {}", url))
+}
+
+/// PUT 请求；切换节点等接口成功时返回 204，空响应体按 null 处理。
+pub(crate) fn api_put(
+    client: &Client,
+    ctx: &ApiContext,
+    path: &str,
+    payload: JsonValue,
+) -> Result<JsonValue> {
+    let url = format!("{}{}", ctx.base_url, path);
+    let resp = apply_secret(client.put(&url).json(&payload), ctx)
+        .send()
+        .with_context(|| format!("请求失败: {}", url))?;
+    let status = resp.status();
+    if !status.is_success() {
+        let body = resp.text().unwrap_or_default();
+        bail!(
+            "请求返回非成功状态 {}: {} {}",
+            status.as_u16(),
+            url,
+            body.trim()
+        );
+    }
+    read_optional_json(resp, &url)
+}
+
+/// DELETE 请求，空响应体按 null 处理。
+pub(crate) fn api_delete(client: &Client, ctx: &ApiContext, path: &str) -> Result<JsonValue> {
+    let url = format!("{}{}", ctx.base_url, path);
+    let resp = apply_secret(client.delete(&url), ctx)
+        .send()
+        .with_context(|| format!("请求失败: {}", url))?
+        .error_for_status()
+        .with_context(|| format!("请求返回非成功状态: {}", url))?;
+    read_optional_json(resp, &url)
+}
+
+/// POST 请求；mihomo 的动作类接口常返回 204，空响应体按 null 处理。
+pub(crate) fn api_post(
+    client: &Client,
+    ctx: &ApiContext,
+    path: &str,
+    payload: Option<JsonValue>,
+) -> Result<JsonValue> {
+    let url = format!("{}{}", ctx.base_url, path);
+    let mut req = client.post(&url);
+    if let Some(payload) = payload {
+        req = req.json(&payload);
+    }
+    let resp = apply_secret(req, ctx)
+        .send()
+        .with_context(|| format!("请求失败: {}", url))?
+        .error_for_status()
+        .with_context(|| format!("请求返回非成功状态: {}", url))?;
+    read_optional_json(resp, &url)
+}
+
+fn read_optional_json(resp: reqwest::blocking::Response, url: &str) -> Result<JsonValue> {
+    let body = resp
+        .text()
+        .with_context(|| format!("读取响应失败: {}", url))?;
+    if body.trim().is_empty() {
+        return Ok(JsonValue::Null);
+    }
+    serde_json::from_str(&body).with_context(|| format!("解析响应失败: {}", url))
 }
 
 #[cfg(test)]
@@ -347,6 +443,18 @@ secret: abc
         assert_eq!(yaml_key_string(&root, "missing"), None);
     }
 
+    #[test]
+    fn api_path_should_encode_segments_and_query() {
+        assert_eq!(
+            api_path(&["proxies", "🇭🇰 香港 01"], &[]),
+            "/proxies/%F0%9F%87%AD%F0%9F%87%B0%20%E9%A6%99%E6%B8%AF%2001"
+        );
+        assert_eq!(
+            api_path(&["group", "a/b", "delay"], &[("timeout", "5000")]),
+            "/group/a%2Fb/delay?timeout=5000"
+        );
+    }
+
     #[test]
     fn build_dashboard_url_should_append_ui_path() {
         assert_eq!(
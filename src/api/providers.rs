@@ -0,0 +1,361 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Result, bail};
+use reqwest::blocking::Client;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+use super::rules::{RuleProvider, parse_rule_providers};
+use super::{ApiContext, api_get, api_path, api_put, build_client, load_api_context};
+use crate::cli::{
+    ApiCommonArgs, ApiProvidersCommand, ApiProvidersHealthcheckArgs, ApiProvidersListArgs,
+    ApiProvidersUpdateArgs, ProviderKind,
+};
+use crate::output::{is_json_mode, print_json};
+use crate::utils;
+
+/// 更新订阅、健康检查可能耗时较长，HTTP 超时不低于该值。
+const SLOW_ACTION_TIMEOUT_SECS: u64 = 60;
+
+/// `/providers/proxies` 中单个 proxy-provider 的规范化视图。
+#[derive(Debug, Clone, Serialize)]
+struct ProxyProvider {
+    name: String,
+    vehicle_type: String,
+    updated_at: String,
+    /// 距上次更新的秒数，updatedAt 无法解析时为 None
+    age_secs: Option<u64>,
+    nodes: usize,
+    alive: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    subscription: Option<SubscriptionInfo>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct SubscriptionInfo {
+    upload: u64,
+    download: u64,
+    total: u64,
+    /// 到期时间（unix 秒），0 或缺失表示不限期
+    expire: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+struct ActionResult {
+    name: String,
+    kind: &'static str,
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    /// 健康检查后的存活节点数与节点总数
+    #[serde(skip_serializing_if = "Option::is_none")]
+    alive: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    nodes: Option<usize>,
+}
+
+pub fn run(action: ApiProvidersCommand, common: ApiCommonArgs) -> Result<()> {
+    match action {
+        ApiProvidersCommand::List(args) => cmd_list(args, common),
+        ApiProvidersCommand::Update(args) => cmd_update(args, common),
+        ApiProvidersCommand::Healthcheck(args) => cmd_healthcheck(args, common),
+    }
+}
+
+fn cmd_list(args: ApiProvidersListArgs, common: ApiCommonArgs) -> Result<()> {
+    let client = build_client(common.timeout_secs)?;
+    let ctx = load_api_context(&common)?;
+    let proxies = if args.kind != Some(ProviderKind::Rule) {
+        fetch_proxy_providers(&client, &ctx)?
+    } else {
+        BTreeMap::new()
+    };
+    let rules = if args.kind != Some(ProviderKind::Proxy) {
+        fetch_rule_providers(&client, &ctx)?
+    } else {
+        BTreeMap::new()
+    };
+
+    if is_json_mode() {
+        return print_json(&serde_json::json!({
+            "ok": true,
+            "action": "api.providers.list",
+            "controller": ctx.base_url,
+            "proxy_providers": proxies.values().collect::<Vec<_>>(),
+            "rule_providers": rules.values().collect::<Vec<_>>(),
+        }));
+    }
+
+    if args.kind != Some(ProviderKind::Rule) {
+        println!("proxy-providers（{} 个）:", proxies.len());
+        for provider in proxies.values() {
+            println!("  {}", proxy_provider_line(provider));
+        }
+    }
+    if args.kind != Some(ProviderKind::Proxy) {
+        println!("rule-providers（{} 个）:", rules.len());
+        for provider in rules.values() {
+            println!("  {}", rule_provider_line(provider));
+        }
+    }
+    Ok(())
+}
+
+fn cmd_update(args: ApiProvidersUpdateArgs, common: ApiCommonArgs) -> Result<()> {
+    let client = build_client(common.timeout_secs.max(SLOW_ACTION_TIMEOUT_SECS))?;
+    let ctx = load_api_context(&common)?;
+    let targets = resolve_targets(&client, &ctx, args.name.as_deref(), args.all, args.kind)?;
+
+    let mut results = Vec::new();
+    for (kind, name) in targets {
+        let segment = match kind {
+            ProviderKind::Proxy => "proxies",
+            ProviderKind::Rule => "rules",
+        };
+        let path = api_path(&["providers", segment, &name], &[]);
+        let outcome = api_put(&client, &ctx, &path, JsonValue::Null);
+        if !is_json_mode() {
+            match &outcome {
+                Ok(_) => println!("已更新 {} {name}", kind_label(kind)),
+                Err(err) => eprintln!("更新 {} {name} 失败: {err:#}", kind_label(kind)),
+            }
+        }
+        results.push(ActionResult {
+            name,
+            kind: kind_label(kind),
+            ok: outcome.is_ok(),
+            error: outcome.err().map(|err| format!("{err:#}")),
+            alive: None,
+            nodes: None,
+        });
+    }
+    finish("api.providers.update", "更新", results)
+}
+
+fn cmd_healthcheck(args: ApiProvidersHealthcheckArgs, common: ApiCommonArgs) -> Result<()> {
+    let client = build_client(common.timeout_secs.max(SLOW_ACTION_TIMEOUT_SECS))?;
+    let ctx = load_api_context(&common)?;
+    let targets = resolve_targets(
+        &client,
+        &ctx,
+        args.name.as_deref(),
+        args.all,
+        Some(ProviderKind::Proxy),
+    )?;
+
+    let mut results = Vec::new();
+    for (kind, name) in targets {
+        let path = api_path(&["providers", "proxies", &name, "healthcheck"], &[]);
+        let outcome = api_get(&client, &ctx, &path).and_then(|_| {
+            let detail = api_get(
+                &client,
+                &ctx,
+                &api_path(&["providers", "proxies", &name], &[]),
+            )?;
+            Ok(parse_proxy_provider(&name, &detail))
+        });
+        let counts = outcome.as_ref().ok().map(|p| (p.alive, p.nodes));
+        if !is_json_mode() {
+            match &outcome {
+                Ok(p) => println!("{name}: 存活 {}/{}", p.alive, p.nodes),
+                Err(err) => eprintln!("健康检查 {name} 失败: {err:#}"),
+            }
+        }
+        results.push(ActionResult {
+            name,
+            kind: kind_label(kind),
+            ok: outcome.is_ok(),
+            error: outcome.err().map(|err| format!("{err:#}")),
+            alive: counts.map(|(alive, _)| alive),
+            nodes: counts.map(|(_, nodes)| nodes),
+        });
+    }
+    finish("api.providers.healthcheck", "健康检查", results)
+}
+
+fn finish(action: &str, verb: &str, results: Vec<ActionResult>) -> Result<()> {
+    let failed = results.iter().filter(|r| !r.ok).count();
+    if is_json_mode() {
+        return print_json(&serde_json::json!({
+            "ok": failed == 0,
+            "action": action,
+            "results": results,
+        }));
+    }
+    if failed > 0 {
+        bail!("{failed} 个 provider {verb}失败");
+    }
+    Ok(())
+}
+
+/// 指定名称时在 proxy/rule provider 中查找（同名时需用 --kind 区分），`--all` 时返回全部。
+fn resolve_targets(
+    client: &Client,
+    ctx: &ApiContext,
+    name: Option<&str>,
+    all: bool,
+    kind: Option<ProviderKind>,
+) -> Result<Vec<(ProviderKind, String)>> {
+    if name.is_none() && !all {
+        bail!("请指定 provider 名称或 --all");
+    }
+    let mut candidates = Vec::new();
+    if kind != Some(ProviderKind::Rule) {
+        for provider in fetch_proxy_providers(client, ctx)?.into_keys() {
+            candidates.push((ProviderKind::Proxy, provider));
+        }
+    }
+    if kind != Some(ProviderKind::Proxy) {
+        for provider in fetch_rule_providers(client, ctx)?.into_keys() {
+            candidates.push((ProviderKind::Rule, provider));
+        }
+    }
+    let Some(name) = name else {
+        return Ok(candidates);
+    };
+    let matched: Vec<_> = candidates.into_iter().filter(|(_, n)| n == name).collect();
+    match matched.len() {
+        0 => bail!("未找到 provider: {name}"),
+        1 => Ok(matched),
+        _ => bail!("proxy-provider 与 rule-provider 中都有 {name}，请用 --kind 指定"),
+    }
+}
+
+fn fetch_proxy_providers(
+    client: &Client,
+    ctx: &ApiContext,
+) -> Result<BTreeMap<String, ProxyProvider>> {
+    let response = api_get(client, ctx, "/providers/proxies")?;
+    Ok(parse_proxy_providers(&response))
+}
+
+fn fetch_rule_providers(
+    client: &Client,
+    ctx: &ApiContext,
+) -> Result<BTreeMap<String, RuleProvider>> {
+    let response = api_get(client, ctx, "/providers/rules")?;
+    Ok(parse_rule_providers(&response))
+}
+
+/// 跳过内核为内联 proxies 生成的 Compatible 伪 provider（如 default）。
+fn parse_proxy_providers(response: &JsonValue) -> BTreeMap<String, ProxyProvider> {
+    let Some(map) = response.get("providers").and_then(|v| v.as_object()) else {
+        return BTreeMap::new();
+    };
+    map.iter()
+        .filter(|(_, item)| item.get("vehicleType").and_then(|v| v.as_str()) != Some("Compatible"))
+        .map(|(name, item)| (name.clone(), parse_proxy_provider(name, item)))
+        .collect()
+}
+
+fn parse_proxy_provider(name: &str, item: &JsonValue) -> ProxyProvider {
+    let text = |key: &str| {
+        item.get(key)
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string()
+    };
+    let nodes = item
+        .get("proxies")
+        .and_then(|v| v.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let updated_at = text("updatedAt");
+    ProxyProvider {
+        name: name.to_string(),
+        vehicle_type: text("vehicleType"),
+        age_secs: utils::parse_rfc3339(&updated_at).map(|at| utils::now_unix().saturating_sub(at)),
+        updated_at,
+        nodes: nodes.len(),
+        alive: nodes
+            .iter()
+            .filter(|n| n.get("alive").and_then(|v| v.as_bool()).unwrap_or(false))
+            .count(),
+        subscription: item.get("subscriptionInfo").and_then(|info| {
+            let field = |key: &str| info.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+            let info = SubscriptionInfo {
+                upload: field("Upload"),
+                download: field("Download"),
+                total: field("Total"),
+                expire: Some(field("Expire")).filter(|v| *v > 0),
+            };
+            (info.total > 0 || info.upload + info.download > 0 || info.expire.is_some())
+                .then_some(info)
+        }),
+    }
+}
+
+fn proxy_provider_line(provider: &ProxyProvider) -> String {
+    let mut parts = vec![
+        provider.name.clone(),
+        provider.vehicle_type.clone(),
+        format!("节点 {}（存活 {}）", provider.nodes, provider.alive),
+    ];
+    if let Some(age) = provider.age_secs {
+        parts.push(format!("{}更新", utils::format_age(age)));
+    }
+    if let Some(info) = &provider.subscription {
+        let used = utils::format_bytes(info.upload + info.download);
+        if info.total > 0 {
+            parts.push(format!("流量 {used}/{}", utils::format_bytes(info.total)));
+        } else {
+            parts.push(format!("已用 {used}"));
+        }
+        if let Some(expire) = info.expire {
+            let now = utils::now_unix();
+            if expire > now {
+                parts.push(format!("剩余 {} 天", (expire - now) / 86400));
+            } else {
+                parts.push("已过期".to_string());
+            }
+        }
+    }
+    parts.join("  ")
+}
+
+fn rule_provider_line(provider: &RuleProvider) -> String {
+    let mut parts = vec![
+        provider.name.clone(),
+        provider.vehicle_type.clone(),
+        provider.behavior.clone(),
+        format!("{} 条", provider.rule_count),
+    ];
+    if let Some(age) = provider.age_secs {
+        parts.push(format!("{}更新", utils::format_age(age)));
+    }
+    parts.join("  ")
+}
+
+fn kind_label(kind: ProviderKind) -> &'static str {
+    match kind {
+        ProviderKind::Proxy => "proxy-provider",
+        ProviderKind::Rule => "rule-provider",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_proxy_providers_should_skip_compatible_and_read_subscription() {
+        let providers = parse_proxy_providers(&serde_json::json!({
+            "providers": {
+                "default": {"vehicleType": "Compatible", "proxies": [{"name": "a", "alive": true}]},
+                "sub": {
+                    "vehicleType": "HTTP",
+                    "updatedAt": "2026-10-18T10:00:00Z",
+                    "proxies": [{"name": "a", "alive": true}, {"name": "b", "alive": false}],
+                    "subscriptionInfo": {"Upload": 1, "Download": 2, "Total": 100, "Expire": 0}
+                },
+                "local": {"vehicleType": "File", "proxies": [], "subscriptionInfo": {}}
+            }
+        }));
+        assert_eq!(providers.keys().collect::<Vec<_>>(), vec!["local", "sub"]);
+        let sub = &providers["sub"];
+        assert_eq!((sub.nodes, sub.alive), (2, 1));
+        let info = sub.subscription.as_ref().expect("缺少订阅信息");
+        assert_eq!((info.download, info.total, info.expire), (2, 100, None));
+        assert!(providers["local"].subscription.is_none());
+    }
+}
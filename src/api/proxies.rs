@@ -0,0 +1,588 @@
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::io::{self, BufRead, IsTerminal, Write};
+
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+use super::{api_get, api_get_with_status, api_path, api_put, build_client, load_api_context};
+use crate::cli::{ApiCommonArgs, ApiProxiesCommand, ApiProxiesListArgs, ApiProxiesSelectArgs};
+use crate::output::{is_json_mode, print_json};
+
+/// 模糊匹配时最多列出的候选数。
+const CANDIDATE_PREVIEW_LIMIT: usize = 10;
+
+/// 内置出站类型：不是真实节点，不参与 `--all` 测速与可用性判断。
+const BUILTIN_KINDS: &[&str] = &[
+    "Direct",
+    "Reject",
+    "RejectDrop",
+    "Pass",
+    "Compatible",
+    "Dns",
+];
+
+pub fn run(
+    action: Option<ApiProxiesCommand>,
+    list: ApiProxiesListArgs,
+    common: ApiCommonArgs,
+) -> Result<()> {
+    match action {
+        None => cmd_list(list, common),
+        Some(ApiProxiesCommand::Select(args)) => cmd_select(args, common),
+    }
+}
+
+/// `/proxies` 中单个代理或策略组的规范化视图。
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct ProxyInfo {
+    pub(crate) name: String,
+    #[serde(rename = "type")]
+    pub(crate) kind: String,
+    /// 策略组当前选中的成员
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) now: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) members: Vec<String>,
+    pub(crate) alive: Option<bool>,
+    /// 最近一次测速延迟（毫秒），失败或未测速为 None
+    pub(crate) delay: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) udp: Option<bool>,
+}
+
+impl ProxyInfo {
+    pub(crate) fn is_group(&self) -> bool {
+        !self.members.is_empty() || self.now.is_some()
+    }
+
+    pub(crate) fn is_builtin(&self) -> bool {
+        BUILTIN_KINDS.contains(&self.kind.as_str())
+    }
+
+    /// 真实出站节点：既不是策略组，也不是 DIRECT/REJECT 等内置出站。
+    pub(crate) fn is_outbound_node(&self) -> bool {
+        !self.is_group() && !self.is_builtin()
+    }
+}
+
+/// 解析 `/proxies` 响应为按名称索引的规范化结构。
+pub(crate) fn parse_proxies(response: &JsonValue) -> BTreeMap<String, ProxyInfo> {
+    let Some(map) = response.get("proxies").and_then(|v| v.as_object()) else {
+        return BTreeMap::new();
+    };
+    map.iter()
+        .map(|(name, item)| {
+            let last_delay = item
+                .get("history")
+                .and_then(|v| v.as_array())
+                .and_then(|h| h.last())
+                .and_then(|h| h.get("delay"))
+                .and_then(|v| v.as_u64())
+                .filter(|d| *d > 0);
+            let info = ProxyInfo {
+                name: name.clone(),
+                kind: item
+                    .get("type")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("-")
+                    .to_string(),
+                now: item
+                    .get("now")
+                    .and_then(|v| v.as_str())
+                    .filter(|v| !v.is_empty())
+                    .map(str::to_string),
+                members: group_members(item),
+                alive: item.get("alive").and_then(|v| v.as_bool()),
+                delay: last_delay,
+                udp: item.get("udp").and_then(|v| v.as_bool()),
+            };
+            (name.clone(), info)
+        })
+        .collect()
+}
+
+/// 策略组按 GLOBAL 中的配置顺序排列，未出现的按名称排序，GLOBAL 放在最后。
+fn ordered_groups(proxies: &BTreeMap<String, ProxyInfo>) -> Vec<&ProxyInfo> {
+    let mut ordered: Vec<&ProxyInfo> = Vec::new();
+    if let Some(global) = proxies.get("GLOBAL") {
+        for name in &global.members {
+            if let Some(info) = proxies.get(name)
+                && info.is_group()
+            {
+                ordered.push(info);
+            }
+        }
+    }
+    for info in proxies.values() {
+        if info.is_group() && info.name != "GLOBAL" && !ordered.iter().any(|g| g.name == info.name)
+        {
+            ordered.push(info);
+        }
+    }
+    if let Some(global) = proxies.get("GLOBAL") {
+        ordered.push(global);
+    }
+    ordered
+}
+
+/// 类型比较时忽略大小写与连字符（url-test 与 URLTest、DOMAIN-SUFFIX 与 DomainSuffix 等价）。
+pub(crate) fn normalize_kind(kind: &str) -> String {
+    kind.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+fn select_roots<'a>(
+    proxies: &'a BTreeMap<String, ProxyInfo>,
+    args: &ApiProxiesListArgs,
+) -> Result<Vec<&'a ProxyInfo>> {
+    let groups = ordered_groups(proxies);
+    if let Some(name) = &args.group {
+        let found = groups
+            .iter()
+            .find(|g| g.name == *name)
+            .or_else(|| groups.iter().find(|g| g.name.eq_ignore_ascii_case(name)));
+        return match found {
+            Some(group) => Ok(vec![*group]),
+            None => bail!(
+                "未找到策略组: {name}，可选: {}",
+                preview(&groups.iter().map(|g| g.name.clone()).collect::<Vec<_>>())
+            ),
+        };
+    }
+    if let Some(kind) = &args.kind {
+        let wanted = normalize_kind(kind);
+        return Ok(groups
+            .into_iter()
+            .filter(|g| normalize_kind(&g.kind) == wanted)
+            .collect());
+    }
+    // 未被其他策略组（GLOBAL 除外）引用的为顶层组
+    let nested: HashSet<&str> = groups
+        .iter()
+        .filter(|g| g.name != "GLOBAL")
+        .flat_map(|g| g.members.iter().map(String::as_str))
+        .collect();
+    Ok(groups
+        .into_iter()
+        .filter(|g| g.name != "GLOBAL" && !nested.contains(g.name.as_str()))
+        .collect())
+}
+
+fn visible_members<'a>(
+    group: &'a ProxyInfo,
+    proxies: &'a BTreeMap<String, ProxyInfo>,
+    alive_only: bool,
+) -> Vec<(&'a str, Option<&'a ProxyInfo>)> {
+    group
+        .members
+        .iter()
+        .map(|name| (name.as_str(), proxies.get(name)))
+        .filter(|(_, info)| !alive_only || info.is_some_and(|i| i.alive != Some(false)))
+        .collect()
+}
+
+fn cmd_list(args: ApiProxiesListArgs, common: ApiCommonArgs) -> Result<()> {
+    let client = build_client(common.timeout_secs)?;
+    let ctx = load_api_context(&common)?;
+    let response = api_get(&client, &ctx, "/proxies")?;
+    let proxies = parse_proxies(&response);
+    let roots = select_roots(&proxies, &args)?;
+
+    if is_json_mode() {
+        // 展开树中出现的全部策略组，成员按 --alive 过滤
+        let mut groups: Vec<serde_json::Value> = Vec::new();
+        let mut nodes: BTreeMap<&str, &ProxyInfo> = BTreeMap::new();
+        let mut seen: HashSet<&str> = HashSet::new();
+        let mut queue: VecDeque<&ProxyInfo> = roots.iter().copied().collect();
+        while let Some(group) = queue.pop_front() {
+            if !seen.insert(group.name.as_str()) {
+                continue;
+            }
+            let members = visible_members(group, &proxies, args.alive);
+            let mut value = serde_json::to_value(group).context("序列化策略组失败")?;
+            value["members"] = serde_json::json!(
+                members
+                    .iter()
+                    .map(|(name, info)| serde_json::json!({
+                        "name": name,
+                        "is_group": info.is_some_and(|i| i.is_group()),
+                        "selected": group.now.as_deref() == Some(*name),
+                    }))
+                    .collect::<Vec<_>>()
+            );
+            groups.push(value);
+            for (_, info) in members {
+                match info {
+                    Some(info) if info.is_group() => queue.push_back(info),
+                    Some(info) => {
+                        nodes.insert(info.name.as_str(), info);
+                    }
+                    None => {}
+                }
+            }
+        }
+        return print_json(&serde_json::json!({
+            "ok": true,
+            "action": "api.proxies",
+            "controller": ctx.base_url,
+            "groups": groups,
+            "nodes": nodes.values().collect::<Vec<_>>(),
+        }));
+    }
+
+    if roots.is_empty() {
+        println!("没有匹配的策略组");
+        return Ok(());
+    }
+    for root in roots {
+        println!("{}", describe(root, false));
+        let mut path = vec![root.name.as_str()];
+        print_members(root, &proxies, args.alive, "", &mut path);
+    }
+    Ok(())
+}
+
+fn print_members<'a>(
+    group: &'a ProxyInfo,
+    proxies: &'a BTreeMap<String, ProxyInfo>,
+    alive_only: bool,
+    prefix: &str,
+    path: &mut Vec<&'a str>,
+) {
+    let members = visible_members(group, proxies, alive_only);
+    let last_index = members.len().saturating_sub(1);
+    for (idx, (name, info)) in members.into_iter().enumerate() {
+        let (branch, indent) = if idx == last_index {
+            ("└─", "   ")
+        } else {
+            ("├─", "│  ")
+        };
+        let selected = group.now.as_deref() == Some(name);
+        let line = match info {
+            Some(info) => describe(info, selected),
+            None => format!("{}{name}", if selected { "* " } else { "" }),
+        };
+        println!("{prefix}{branch} {line}");
+        // 嵌套策略组递归展开，已在当前路径上的组不再展开以避免循环
+        if let Some(info) = info
+            && info.is_group()
+            && !path.contains(&name)
+        {
+            path.push(name);
+            print_members(
+                info,
+                proxies,
+                alive_only,
+                &format!("{prefix}{indent}"),
+                path,
+            );
+            path.pop();
+        }
+    }
+}
+
+fn describe(info: &ProxyInfo, selected: bool) -> String {
+    let mut line = String::new();
+    if selected {
+        line.push_str("* ");
+    }
+    line.push_str(&info.name);
+    line.push_str(&format!(" [{}]", info.kind));
+    if let Some(now) = &info.now {
+        line.push_str(&format!(" -> {now}"));
+    }
+    match (info.delay, info.alive) {
+        (_, Some(false)) => line.push_str("  不可用"),
+        (Some(delay), _) => line.push_str(&format!("  {delay}ms")),
+        _ => {}
+    }
+    line
+}
+
+fn cmd_select(args: ApiProxiesSelectArgs, common: ApiCommonArgs) -> Result<()> {
+    let client = build_client(common.timeout_secs)?;
+    let ctx = load_api_context(&common)?;
+
+    let group_path = api_path(&["proxies", &args.group], &[]);
+    let (status, group) = api_get_with_status(&client, &ctx, &group_path)?;
+    if status == 404 {
+        bail!("未找到策略组: {}", args.group);
+    }
+    if !(200..300).contains(&status) {
+        bail!("读取策略组失败（HTTP {status}）: {}", args.group);
+    }
+    let kind = group.get("type").and_then(|v| v.as_str()).unwrap_or("-");
+    if kind != "Selector" {
+        bail!(
+            "策略组 {} 类型为 {}，仅 Selector 支持手动切换",
+            args.group,
+            kind
+        );
+    }
+    let members = group_members(&group);
+    if members.is_empty() {
+        bail!("策略组 {} 没有可选节点", args.group);
+    }
+    let previous = group
+        .get("now")
+        .and_then(|v| v.as_str())
+        .map(str::to_string);
+
+    let interactive = !is_json_mode() && io::stdin().is_terminal() && io::stderr().is_terminal();
+    let target = match args.proxy.as_deref() {
+        Some(query) => match match_proxy(&members, query) {
+            ProxyMatch::Found(name) => name,
+            ProxyMatch::Ambiguous(candidates) if interactive => {
+                pick_proxy(&args.group, &candidates, previous.as_deref())?
+            }
+            ProxyMatch::Ambiguous(candidates) => bail!(
+                "节点名 {query} 匹配到多个节点，请写得更具体: {}",
+                preview(&candidates)
+            ),
+            ProxyMatch::None => bail!(
+                "策略组 {} 中没有匹配 {query} 的节点，可选: {}",
+                args.group,
+                preview(&members)
+            ),
+        },
+        None if interactive => pick_proxy(&args.group, &members, previous.as_deref())?,
+        None => bail!("缺少 --proxy（非交互终端无法选择节点）"),
+    };
+
+    api_put(
+        &client,
+        &ctx,
+        &group_path,
+        serde_json::json!({ "name": target }),
+    )
+    .with_context(|| format!("切换策略组 {} 到 {} 失败", args.group, target))?;
+
+    if is_json_mode() {
+        return print_json(&serde_json::json!({
+            "ok": true,
+            "action": "api.proxies.select",
+            "group": args.group,
+            "proxy": target,
+            "previous": previous,
+            "changed": previous.as_deref() != Some(target.as_str()),
+        }));
+    }
+
+    match previous {
+        Some(prev) if prev == target => println!("策略组 {} 已是节点: {}", args.group, target),
+        Some(prev) => println!("策略组 {}: {} -> {}", args.group, prev, target),
+        None => println!("策略组 {} 已切换到: {}", args.group, target),
+    }
+    Ok(())
+}
+
+fn group_members(group: &JsonValue) -> Vec<String> {
+    group
+        .get("all")
+        .and_then(|v| v.as_array())
+        .map(|list| {
+            list.iter()
+                .filter_map(|v| v.as_str())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum ProxyMatch {
+    Found(String),
+    Ambiguous(Vec<String>),
+    None,
+}
+
+/// 逐级放宽匹配：精确 → 忽略大小写 → 去除符号/emoji 后相等 → 包含 → 子序列。
+/// 某一级命中唯一节点即返回，命中多个则视为歧义。
+fn match_proxy(members: &[String], query: &str) -> ProxyMatch {
+    let lowered = query.to_lowercase();
+    let normalized = normalize_name(query);
+    let tiers: [&dyn Fn(&str) -> bool; 5] = [
+        &|name| name == query,
+        &|name| name.to_lowercase() == lowered,
+        &|name| !normalized.is_empty() && normalize_name(name) == normalized,
+        &|name| !normalized.is_empty() && normalize_name(name).contains(&normalized),
+        &|name| !normalized.is_empty() && is_subsequence(&normalized, &normalize_name(name)),
+    ];
+    for tier in tiers {
+        let hits: Vec<String> = members.iter().filter(|name| tier(name)).cloned().collect();
+        match hits.len() {
+            0 => continue,
+            1 => return ProxyMatch::Found(hits.into_iter().next().unwrap_or_default()),
+            _ => return ProxyMatch::Ambiguous(hits),
+        }
+    }
+    ProxyMatch::None
+}
+
+/// 仅保留字母数字（含中日韩文字）并转小写，忽略空格、符号与国旗 emoji。
+fn normalize_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut chars = haystack.chars();
+    needle.chars().all(|c| chars.any(|h| h == c))
+}
+
+fn preview(names: &[String]) -> String {
+    let mut text = names
+        .iter()
+        .take(CANDIDATE_PREVIEW_LIMIT)
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(", ");
+    if names.len() > CANDIDATE_PREVIEW_LIMIT {
+        text.push_str(&format!(" …（共 {} 个）", names.len()));
+    }
+    text
+}
+
+/// 终端交互选择：输入序号或节点名（模糊匹配），空行取消。
+fn pick_proxy(group: &str, candidates: &[String], current: Option<&str>) -> Result<String> {
+    let stdin = io::stdin();
+    let mut stderr = io::stderr();
+    writeln!(stderr, "策略组 {group} 可选节点:")?;
+    for (idx, name) in candidates.iter().enumerate() {
+        let mark = if Some(name.as_str()) == current {
+            "*"
+        } else {
+            " "
+        };
+        writeln!(stderr, "{mark} {:>3}) {name}", idx + 1)?;
+    }
+    loop {
+        write!(stderr, "输入序号或节点名（回车取消）: ")?;
+        stderr.flush()?;
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).context("读取输入失败")? == 0 {
+            bail!("已取消选择");
+        }
+        let input = line.trim();
+        if input.is_empty() {
+            bail!("已取消选择");
+        }
+        if let Ok(idx) = input.parse::<usize>()
+            && (1..=candidates.len()).contains(&idx)
+        {
+            return Ok(candidates[idx - 1].clone());
+        }
+        match match_proxy(candidates, input) {
+            ProxyMatch::Found(name) => return Ok(name),
+            ProxyMatch::Ambiguous(hits) => writeln!(stderr, "匹配到多个节点: {}", preview(&hits))?,
+            ProxyMatch::None => writeln!(stderr, "没有匹配的节点，请重新输入")?,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_proxies() -> BTreeMap<String, ProxyInfo> {
+        parse_proxies(&serde_json::json!({
+            "proxies": {
+                "GLOBAL": {"type": "Selector", "now": "PROXY", "all": ["DIRECT", "PROXY", "AUTO"]},
+                "PROXY": {"type": "Selector", "now": "AUTO", "all": ["AUTO", "hk", "jp"], "alive": true},
+                "AUTO": {"type": "URLTest", "now": "jp", "all": ["hk", "jp"], "alive": true},
+                "hk": {"type": "Vmess", "alive": false, "history": [{"delay": 0}]},
+                "jp": {"type": "Trojan", "alive": true, "history": [{"delay": 30}, {"delay": 88}]},
+                "DIRECT": {"type": "Direct", "alive": true, "history": []}
+            }
+        }))
+    }
+
+    #[test]
+    fn parse_proxies_should_normalize_groups_and_delay() {
+        let proxies = sample_proxies();
+        assert!(proxies["AUTO"].is_group());
+        assert!(!proxies["jp"].is_group());
+        assert_eq!(proxies["jp"].delay, Some(88));
+        assert_eq!(proxies["hk"].delay, None);
+
+        let roots = select_roots(
+            &proxies,
+            &ApiProxiesListArgs {
+                group: None,
+                kind: None,
+                alive: false,
+            },
+        )
+        .expect("选择顶层组失败");
+        assert_eq!(
+            roots.iter().map(|g| g.name.as_str()).collect::<Vec<_>>(),
+            vec!["PROXY"]
+        );
+
+        let by_type = select_roots(
+            &proxies,
+            &ApiProxiesListArgs {
+                group: None,
+                kind: Some("url-test".to_string()),
+                alive: false,
+            },
+        )
+        .expect("按类型过滤失败");
+        assert_eq!(by_type[0].name, "AUTO");
+
+        let alive: Vec<&str> = visible_members(&proxies["PROXY"], &proxies, true)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(alive, vec!["AUTO", "jp"]);
+    }
+
+    fn members() -> Vec<String> {
+        [
+            "🇭🇰 香港 01",
+            "🇭🇰 香港 02",
+            "🇯🇵 日本 01",
+            "US-LosAngeles",
+            "DIRECT",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect()
+    }
+
+    #[test]
+    fn match_proxy_should_relax_step_by_step() {
+        let members = members();
+        assert_eq!(
+            match_proxy(&members, "DIRECT"),
+            ProxyMatch::Found("DIRECT".to_string())
+        );
+        assert_eq!(
+            match_proxy(&members, "direct"),
+            ProxyMatch::Found("DIRECT".to_string())
+        );
+        assert_eq!(
+            match_proxy(&members, "香港02"),
+            ProxyMatch::Found("🇭🇰 香港 02".to_string())
+        );
+        assert_eq!(
+            match_proxy(&members, "日本"),
+            ProxyMatch::Found("🇯🇵 日本 01".to_string())
+        );
+        assert_eq!(
+            match_proxy(&members, "usla"),
+            ProxyMatch::Found("US-LosAngeles".to_string())
+        );
+        assert!(matches!(
+            match_proxy(&members, "香港"),
+            ProxyMatch::Ambiguous(hits) if hits.len() == 2
+        ));
+        assert_eq!(match_proxy(&members, "新加坡"), ProxyMatch::None);
+        assert_eq!(match_proxy(&members, "🇸🇬"), ProxyMatch::None);
+    }
+}
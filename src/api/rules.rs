@@ -0,0 +1,277 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+
+use super::proxies::normalize_kind;
+use super::{api_get, build_client, load_api_context};
+use crate::cli::ApiRulesArgs;
+use crate::output::{is_json_mode, print_json};
+use crate::utils;
+
+#[derive(Debug, Clone, Serialize)]
+struct RuntimeRule {
+    index: usize,
+    #[serde(rename = "type")]
+    kind: String,
+    payload: String,
+    target: String,
+    /// RuleSet 规则对应的 rule-provider 信息
+    #[serde(skip_serializing_if = "Option::is_none")]
+    provider: Option<RuleProvider>,
+}
+
+/// `/providers/rules` 中单个 rule-provider 的规范化视图。
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct RuleProvider {
+    pub(crate) name: String,
+    pub(crate) behavior: String,
+    pub(crate) format: String,
+    pub(crate) vehicle_type: String,
+    pub(crate) rule_count: u64,
+    pub(crate) updated_at: String,
+    /// 距上次更新的秒数，updatedAt 无法解析时为 None
+    pub(crate) age_secs: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+struct TargetCount {
+    target: String,
+    count: usize,
+}
+
+pub fn run(args: ApiRulesArgs) -> Result<()> {
+    let client = build_client(args.api.timeout_secs)?;
+    let ctx = load_api_context(&args.api)?;
+    let response = api_get(&client, &ctx, "/rules")?;
+    // 旧版内核可能没有 /providers/rules，取不到时仅不展示规则集信息
+    let providers = api_get(&client, &ctx, "/providers/rules")
+        .map(|v| parse_rule_providers(&v))
+        .unwrap_or_default();
+
+    let rules = parse_rules(&response, &providers);
+    let total = rules.len();
+    let mut matched: Vec<RuntimeRule> = rules
+        .into_iter()
+        .filter(|rule| {
+            args.kind
+                .as_deref()
+                .is_none_or(|kind| normalize_kind(kind) == normalize_kind(&rule.kind))
+                && args
+                    .target
+                    .as_deref()
+                    .is_none_or(|target| rule.target.eq_ignore_ascii_case(target))
+                && args
+                    .match_host
+                    .as_deref()
+                    .is_none_or(|needle| rule_matches(rule, needle))
+        })
+        .collect();
+    let targets = count_targets(&matched);
+    let matched_count = matched.len();
+    if let Some(limit) = args.limit {
+        matched.truncate(limit);
+    }
+
+    if is_json_mode() {
+        return print_json(&serde_json::json!({
+            "ok": true,
+            "action": "api.rules",
+            "controller": ctx.base_url,
+            "total": total,
+            "matched": matched_count,
+            "targets": targets,
+            "rules": if args.summary { Vec::new() } else { matched },
+            "providers": providers.values().collect::<Vec<_>>(),
+        }));
+    }
+
+    if !args.summary {
+        for rule in &matched {
+            print_rule(rule);
+        }
+        if matched.len() < matched_count {
+            println!("……（仅显示前 {} 条）", matched.len());
+        }
+        println!();
+    }
+    if matched_count == total {
+        println!("共 {total} 条规则");
+    } else {
+        println!("共 {total} 条规则，匹配 {matched_count} 条");
+    }
+    if !targets.is_empty() {
+        println!("按出站统计:");
+        for item in &targets {
+            println!("  {:>6}  {}", item.count, item.target);
+        }
+    }
+    if args.summary && !providers.is_empty() {
+        println!("规则集:");
+        for provider in providers.values() {
+            println!("  {}  {}", provider.name, provider_label(provider));
+        }
+    }
+    Ok(())
+}
+
+fn parse_rules(
+    response: &JsonValue,
+    providers: &BTreeMap<String, RuleProvider>,
+) -> Vec<RuntimeRule> {
+    let Some(list) = response.get("rules").and_then(|v| v.as_array()) else {
+        return Vec::new();
+    };
+    list.iter()
+        .enumerate()
+        .map(|(pos, item)| {
+            let text = |key: &str| {
+                item.get(key)
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .to_string()
+            };
+            let kind = text("type");
+            let payload = text("payload");
+            let provider = (normalize_kind(&kind) == "ruleset")
+                .then(|| providers.get(&payload).cloned())
+                .flatten();
+            RuntimeRule {
+                // 新版内核返回 index 字段，旧版按顺序编号
+                index: item
+                    .get("index")
+                    .and_then(|v| v.as_u64())
+                    .map(|v| v as usize)
+                    .unwrap_or(pos),
+                kind,
+                payload,
+                target: text("proxy"),
+                provider,
+            }
+        })
+        .collect()
+}
+
+pub(crate) fn parse_rule_providers(response: &JsonValue) -> BTreeMap<String, RuleProvider> {
+    let Some(map) = response.get("providers").and_then(|v| v.as_object()) else {
+        return BTreeMap::new();
+    };
+    map.iter()
+        .map(|(name, item)| {
+            let text = |key: &str| {
+                item.get(key)
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .to_string()
+            };
+            let updated_at = text("updatedAt");
+            let provider = RuleProvider {
+                name: name.clone(),
+                behavior: text("behavior"),
+                format: text("format"),
+                vehicle_type: text("vehicleType"),
+                rule_count: item.get("ruleCount").and_then(|v| v.as_u64()).unwrap_or(0),
+                age_secs: utils::parse_rfc3339(&updated_at)
+                    .map(|at| utils::now_unix().saturating_sub(at)),
+                updated_at,
+            };
+            (name.clone(), provider)
+        })
+        .collect()
+}
+
+/// `--match` 既支持子串匹配，也按域名规则语义判断：
+/// `www.example.com` 命中 `DOMAIN-SUFFIX,example.com` 与 `DOMAIN-KEYWORD,example`。
+fn rule_matches(rule: &RuntimeRule, needle: &str) -> bool {
+    let needle = needle.trim().trim_end_matches('.').to_lowercase();
+    let payload = rule.payload.to_lowercase();
+    if payload.is_empty() {
+        return false;
+    }
+    if payload.contains(&needle) {
+        return true;
+    }
+    match normalize_kind(&rule.kind).as_str() {
+        "domainsuffix" => needle.ends_with(&format!(".{payload}")),
+        "domainkeyword" => needle.contains(&payload),
+        _ => false,
+    }
+}
+
+fn count_targets(rules: &[RuntimeRule]) -> Vec<TargetCount> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for rule in rules {
+        *counts.entry(rule.target.as_str()).or_default() += 1;
+    }
+    let mut list: Vec<TargetCount> = counts
+        .into_iter()
+        .map(|(target, count)| TargetCount {
+            target: target.to_string(),
+            count,
+        })
+        .collect();
+    list.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.target.cmp(&b.target)));
+    list
+}
+
+fn print_rule(rule: &RuntimeRule) {
+    let payload = if rule.payload.is_empty() {
+        "-"
+    } else {
+        rule.payload.as_str()
+    };
+    let extra = rule
+        .provider
+        .as_ref()
+        .map(|p| format!("  [{}]", provider_label(p)))
+        .unwrap_or_default();
+    println!(
+        "{:>5}  {:<14} {}  -> {}{}",
+        rule.index, rule.kind, payload, rule.target, extra
+    );
+}
+
+fn provider_label(provider: &RuleProvider) -> String {
+    let mut parts = vec![provider.behavior.clone()];
+    parts.push(format!("{} 条", provider.rule_count));
+    if !provider.vehicle_type.is_empty() {
+        parts.push(provider.vehicle_type.clone());
+    }
+    if let Some(age) = provider.age_secs {
+        parts.push(format!("{}更新", utils::format_age(age)));
+    }
+    parts.join(" · ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rules_should_match_by_substring_and_domain_suffix() {
+        let providers = parse_rule_providers(&serde_json::json!({
+            "providers": {"cn": {"behavior": "Domain", "ruleCount": 3, "vehicleType": "HTTP", "updatedAt": "2026-10-18T10:00:00Z"}}
+        }));
+        let rules = parse_rules(
+            &serde_json::json!({"rules": [
+                {"type": "DomainSuffix", "payload": "example.com", "proxy": "PROXY"},
+                {"type": "DomainKeyword", "payload": "google", "proxy": "PROXY"},
+                {"type": "RuleSet", "payload": "cn", "proxy": "DIRECT"},
+                {"type": "Match", "payload": "", "proxy": "PROXY"}
+            ]}),
+            &providers,
+        );
+        assert_eq!(rules[2].provider.as_ref().map(|p| p.rule_count), Some(3));
+        assert_eq!(rules[3].index, 3);
+
+        assert!(rule_matches(&rules[0], "www.Example.com"));
+        assert!(rule_matches(&rules[0], "example"));
+        assert!(!rule_matches(&rules[0], "notexample.com"));
+        assert!(rule_matches(&rules[1], "www.google.com"));
+        assert!(!rule_matches(&rules[3], "anything"));
+
+        let targets = count_targets(&rules);
+        assert_eq!((targets[0].target.as_str(), targets[0].count), ("PROXY", 3));
+    }
+}
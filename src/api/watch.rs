@@ -0,0 +1,340 @@
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, BufRead, BufReader, IsTerminal};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use serde::Serialize;
+
+use super::connections::{Connection, matches_all, parse_connections, parse_filters};
+use super::{ApiContext, api_get, api_stream, build_client, build_stream_client, load_api_context};
+use crate::cli::ApiWatchArgs;
+use crate::output::{is_json_mode, print_json_line, print_line};
+use crate::utils;
+
+/// `/traffic` 推送超过该时长未更新时改用连接总量差值估算速率。
+const TRAFFIC_STALE_AFTER: Duration = Duration::from_secs(3);
+
+/// `/traffic` 最近一次推送：(时间, 上行 B/s, 下行 B/s)。
+type TrafficSample = Arc<Mutex<Option<(Instant, u64, u64)>>>;
+
+#[derive(Debug, Serialize)]
+struct TopConnection {
+    id: String,
+    host: String,
+    chain: String,
+    rule: String,
+    process: String,
+    up_rate: u64,
+    down_rate: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct WatchFrame {
+    time: u64,
+    up_rate: u64,
+    down_rate: u64,
+    /// 速率来源：traffic（流式推送）或 connections（按快照差值估算）
+    rate_source: &'static str,
+    connections: usize,
+    upload_total: u64,
+    download_total: u64,
+    top: Vec<TopConnection>,
+    /// 本次刷新新出现的连接
+    new: Vec<Connection>,
+}
+
+struct Snapshot {
+    at: Instant,
+    upload_total: u64,
+    download_total: u64,
+    per_conn: HashMap<String, (u64, u64)>,
+}
+
+pub fn run(args: ApiWatchArgs) -> Result<()> {
+    let filters = parse_filters(&args.filter)?;
+    let client = build_client(args.api.timeout_secs)?;
+    let ctx = load_api_context(&args.api)?;
+    let interval = Duration::from_millis(args.interval_ms.max(200));
+    let json = is_json_mode();
+    let in_place = !json && io::stdout().is_terminal();
+
+    let traffic: TrafficSample = Arc::new(Mutex::new(None));
+    {
+        let ctx = ctx.clone();
+        let traffic = Arc::clone(&traffic);
+        thread::spawn(move || stream_traffic(&ctx, &traffic));
+    }
+
+    let mut previous: Option<Snapshot> = None;
+    let mut recent_new: VecDeque<Connection> = VecDeque::new();
+    let mut ticks = 0u64;
+    loop {
+        let started = Instant::now();
+        // 内核重启等造成的单次失败只提示，下一次刷新继续重试
+        match api_get(&client, &ctx, "/connections") {
+            Ok(response) => {
+                let upload_total = response
+                    .get("uploadTotal")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(0);
+                let download_total = response
+                    .get("downloadTotal")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(0);
+                let connections: Vec<Connection> = parse_connections(&response)
+                    .into_iter()
+                    .filter(|c| matches_all(&filters, c))
+                    .collect();
+
+                let frame = build_frame(
+                    &connections,
+                    previous.as_ref(),
+                    upload_total,
+                    download_total,
+                    stream_rate(&traffic).filter(|_| filters.is_empty()),
+                    !filters.is_empty(),
+                    args.top,
+                );
+
+                if json {
+                    print_json_line(&frame)?;
+                } else {
+                    for conn in &frame.new {
+                        recent_new.push_front(conn.clone());
+                    }
+                    recent_new.truncate(args.top);
+                    render(&ctx, &frame, &recent_new, in_place)?;
+                }
+
+                previous = Some(Snapshot {
+                    at: started,
+                    upload_total,
+                    download_total,
+                    per_conn: connections
+                        .iter()
+                        .map(|c| (c.id.clone(), (c.upload, c.download)))
+                        .collect(),
+                });
+            }
+            Err(err) => eprintln!("[watch] 读取连接失败，将在下次刷新重试: {err:#}"),
+        }
+        ticks += 1;
+        if args.count.is_some_and(|count| ticks >= count) {
+            return Ok(());
+        }
+        thread::sleep(interval.saturating_sub(started.elapsed()));
+    }
+}
+
+/// 持续读取 `/traffic` 的逐行 JSON；接口不可用时静默退出，由快照差值兜底。
+fn stream_traffic(ctx: &ApiContext, latest: &TrafficSample) {
+    let Ok(client) = build_stream_client() else {
+        return;
+    };
+    let Ok(response) = api_stream(&client, ctx, "/traffic") else {
+        return;
+    };
+    for line in BufReader::new(response).lines() {
+        let Ok(line) = line else {
+            return;
+        };
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(&line) else {
+            continue;
+        };
+        let up = value.get("up").and_then(|v| v.as_u64()).unwrap_or(0);
+        let down = value.get("down").and_then(|v| v.as_u64()).unwrap_or(0);
+        if let Ok(mut slot) = latest.lock() {
+            *slot = Some((Instant::now(), up, down));
+        }
+    }
+}
+
+fn stream_rate(traffic: &TrafficSample) -> Option<(u64, u64)> {
+    let slot = traffic.lock().ok()?;
+    let (at, up, down) = (*slot)?;
+    (at.elapsed() < TRAFFIC_STALE_AFTER).then_some((up, down))
+}
+
+fn build_frame(
+    connections: &[Connection],
+    previous: Option<&Snapshot>,
+    upload_total: u64,
+    download_total: u64,
+    streamed: Option<(u64, u64)>,
+    filtered: bool,
+    top: usize,
+) -> WatchFrame {
+    let elapsed = previous
+        .map(|p| p.at.elapsed().as_secs_f64())
+        .filter(|secs| *secs > 0.0);
+    let rate = |now: u64, before: u64| match elapsed {
+        Some(secs) => (now.saturating_sub(before) as f64 / secs) as u64,
+        None => 0,
+    };
+
+    let mut talkers: Vec<TopConnection> = connections
+        .iter()
+        .map(|conn| {
+            let (up_before, down_before) = previous
+                .and_then(|p| p.per_conn.get(&conn.id).copied())
+                .unwrap_or((0, 0));
+            TopConnection {
+                id: conn.id.clone(),
+                host: conn.host.clone(),
+                chain: conn.chain_label(),
+                rule: conn.rule_label(),
+                process: conn.process.clone(),
+                up_rate: rate(conn.upload, up_before),
+                down_rate: rate(conn.download, down_before),
+            }
+        })
+        .collect();
+
+    let (up_rate, down_rate, rate_source) = match (streamed, previous) {
+        (Some((up, down)), _) => (up, down, "traffic"),
+        // 有过滤条件时只累计匹配连接的速率
+        (None, Some(_)) if filtered => (
+            talkers.iter().map(|t| t.up_rate).sum(),
+            talkers.iter().map(|t| t.down_rate).sum(),
+            "connections",
+        ),
+        (None, Some(p)) => (
+            rate(upload_total, p.upload_total),
+            rate(download_total, p.download_total),
+            "connections",
+        ),
+        (None, None) => (0, 0, "connections"),
+    };
+
+    talkers.sort_by_key(|t| std::cmp::Reverse(t.up_rate + t.down_rate));
+    talkers.retain(|t| t.up_rate + t.down_rate > 0);
+    talkers.truncate(top);
+
+    let new = match previous {
+        Some(p) => connections
+            .iter()
+            .filter(|c| !p.per_conn.contains_key(&c.id))
+            .cloned()
+            .collect(),
+        None => Vec::new(),
+    };
+
+    WatchFrame {
+        time: utils::now_unix(),
+        up_rate,
+        down_rate,
+        rate_source,
+        connections: connections.len(),
+        upload_total,
+        download_total,
+        top: talkers,
+        new,
+    }
+}
+
+fn render(
+    ctx: &ApiContext,
+    frame: &WatchFrame,
+    recent_new: &VecDeque<Connection>,
+    in_place: bool,
+) -> Result<()> {
+    let mut out = String::new();
+    if in_place {
+        // 清屏并回到左上角，实现原地刷新
+        out.push_str("\x1b[H\x1b[2J");
+    }
+    out.push_str(&format!(
+        "控制器 {}  连接 {}  总上行 {}  总下行 {}\n",
+        ctx.base_url,
+        frame.connections,
+        utils::format_bytes(frame.upload_total),
+        utils::format_bytes(frame.download_total)
+    ));
+    out.push_str(&format!(
+        "速率 ↑ {}/s  ↓ {}/s\n\n",
+        utils::format_bytes(frame.up_rate),
+        utils::format_bytes(frame.down_rate)
+    ));
+    out.push_str("高流量连接:\n");
+    if frame.top.is_empty() {
+        out.push_str("  （无活跃流量）\n");
+    }
+    for item in &frame.top {
+        out.push_str(&format!(
+            "  ↓{:>11} ↑{:>11}  {}  {}\n",
+            format!("{}/s", utils::format_bytes(item.down_rate)),
+            format!("{}/s", utils::format_bytes(item.up_rate)),
+            item.host,
+            item.chain
+        ));
+    }
+    out.push_str("\n新建连接:\n");
+    if recent_new.is_empty() {
+        out.push_str("  （暂无）\n");
+    }
+    for conn in recent_new {
+        out.push_str(&format!(
+            "  {:<3}  {}  {}  {}{}\n",
+            conn.network,
+            conn.host,
+            conn.rule_label(),
+            conn.chain_label(),
+            if conn.process.is_empty() {
+                String::new()
+            } else {
+                format!("  [{}]", conn.process)
+            }
+        ));
+    }
+    if !in_place {
+        out.push_str("---\n");
+    }
+    print_line(out.strip_suffix('\n').unwrap_or(&out))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conns(download: u64) -> Vec<Connection> {
+        parse_connections(&serde_json::json!({
+            "connections": [
+                {"id": "a", "metadata": {"host": "a.com"}, "upload": 0, "download": download, "chains": ["DIRECT"]},
+                {"id": "b", "metadata": {"host": "b.com"}, "upload": 10, "download": 10, "chains": ["DIRECT"]}
+            ]
+        }))
+    }
+
+    #[test]
+    fn build_frame_should_rank_talkers_and_detect_new_connections() {
+        let first = build_frame(&conns(100), None, 0, 0, None, false, 5);
+        assert!(first.new.is_empty());
+        assert!(first.top.is_empty());
+
+        let previous = Snapshot {
+            at: Instant::now() - Duration::from_secs(1),
+            upload_total: 0,
+            download_total: 0,
+            per_conn: HashMap::from([("a".to_string(), (0, 100))]),
+        };
+        let frame = build_frame(
+            &conns(100_100),
+            Some(&previous),
+            10,
+            100_110,
+            None,
+            false,
+            5,
+        );
+        assert_eq!(frame.new.len(), 1);
+        assert_eq!(frame.new[0].id, "b");
+        assert_eq!(frame.top[0].id, "a");
+        assert!(frame.top[0].down_rate > 50_000);
+        assert_eq!(frame.rate_source, "connections");
+
+        let streamed = build_frame(&conns(1), Some(&previous), 0, 0, Some((7, 9)), false, 5);
+        assert_eq!((streamed.up_rate, streamed.down_rate), (7, 9));
+    }
+}
@@ -34,20 +34,30 @@ where
     if let Some(home) = env::var_os("CLASH_CLI_HOME") {
         cmd.arg(format!("CLASH_CLI_HOME={}", home.to_string_lossy()));
     }
+    if let Some(key_file) = env::var_os(crate::secret::KEY_FILE_ENV) {
+        cmd.arg(format!(
+            "{}={}",
+            crate::secret::KEY_FILE_ENV,
+            key_file.to_string_lossy()
+        ));
+    }
     cmd.arg(exe);
     if json_mode {
         cmd.arg("--json");
     }
+    if crate::output::show_secrets() {
+        cmd.arg("--show-secrets");
+    }
     append_args(&mut cmd)?;
     cmd.status().context("启动 sudo 失败")
 }
 
 pub fn is_permission_denied_error(err: &anyhow::Error) -> bool {
     for cause in err.chain() {
-        if let Some(io_err) = cause.downcast_ref::<std::io::Error>() {
-            if io_err.kind() == ErrorKind::PermissionDenied {
-                return true;
-            }
+        if let Some(io_err) = cause.downcast_ref::<std::io::Error>()
+            && io_err.kind() == ErrorKind::PermissionDenied
+        {
+            return true;
         }
     }
     let msg = err.to_string();
@@ -0,0 +1,614 @@
+use std::fs;
+use std::net::Ipv4Addr;
+use std::path::Path;
+use std::process::Command;
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+
+use crate::api::{api_get, api_patch, build_client, load_api_context};
+use crate::cli::{
+    AutoSwitchAddArgs, AutoSwitchApplyArgs, AutoSwitchCommand, AutoSwitchRemoveArgs,
+    AutoSwitchRunArgs,
+};
+use crate::output::{is_json_mode, print_json, print_json_line};
+use crate::paths::{AppPaths, app_paths};
+use crate::profile::{
+    ensure_service_runtime_home_matches_current, load_index, render_active_to_runtime,
+    restart_system_service, save_index,
+};
+use crate::utils;
+
+/// 重启服务后等待 controller 就绪的重试次数（每次间隔 1 秒）。
+const MODE_APPLY_RETRIES: u32 = 10;
+
+/// 当前网络指纹。
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub(crate) struct NetworkFingerprint {
+    interface: Option<String>,
+    gateway_ip: Option<String>,
+    gateway_mac: Option<String>,
+    ssid: Option<String>,
+}
+
+/// 网络规则：设置了的条件需全部满足，按列表顺序取第一条命中。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct NetworkRule {
+    name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ssid: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    gateway_ip: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    gateway_mac: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    interface: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    profile: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mode: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RuleSet {
+    #[serde(default)]
+    rules: Vec<NetworkRule>,
+}
+
+impl NetworkRule {
+    fn matches(&self, fp: &NetworkFingerprint) -> bool {
+        let conditions = [
+            (&self.ssid, &fp.ssid, false),
+            (&self.gateway_ip, &fp.gateway_ip, false),
+            (&self.gateway_mac, &fp.gateway_mac, true),
+            (&self.interface, &fp.interface, false),
+        ];
+        let mut any = false;
+        for (expected, actual, ignore_case) in conditions {
+            let Some(expected) = expected else { continue };
+            any = true;
+            let hit = actual.as_deref().is_some_and(|actual| {
+                if ignore_case {
+                    actual.eq_ignore_ascii_case(expected)
+                } else {
+                    actual == expected
+                }
+            });
+            if !hit {
+                return false;
+            }
+        }
+        any
+    }
+
+    fn describe_conditions(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(v) = &self.ssid {
+            parts.push(format!("ssid={v}"));
+        }
+        if let Some(v) = &self.gateway_ip {
+            parts.push(format!("gateway_ip={v}"));
+        }
+        if let Some(v) = &self.gateway_mac {
+            parts.push(format!("gateway_mac={v}"));
+        }
+        if let Some(v) = &self.interface {
+            parts.push(format!("interface={v}"));
+        }
+        parts.join(" ")
+    }
+}
+
+/// 一次评估的结果。
+#[derive(Debug, Default, Serialize)]
+pub(crate) struct Evaluation {
+    fingerprint: NetworkFingerprint,
+    pub(crate) rule: Option<String>,
+    profile_switched: Option<String>,
+    mode_set: Option<String>,
+    dry_run: bool,
+}
+
+pub fn run(command: AutoSwitchCommand) -> Result<()> {
+    match command {
+        AutoSwitchCommand::Detect => cmd_detect(),
+        AutoSwitchCommand::List => cmd_list(),
+        AutoSwitchCommand::Add(args) => cmd_add(args),
+        AutoSwitchCommand::Remove(args) => cmd_remove(args),
+        AutoSwitchCommand::Evaluate(args) => cmd_evaluate(args),
+        AutoSwitchCommand::Run(args) => cmd_run(args),
+    }
+}
+
+fn cmd_detect() -> Result<()> {
+    let fp = detect_fingerprint();
+    if is_json_mode() {
+        return print_json(&serde_json::json!({
+            "ok": true,
+            "action": "auto-switch.detect",
+            "fingerprint": fp,
+        }));
+    }
+    print_fingerprint(&fp);
+    Ok(())
+}
+
+fn cmd_list() -> Result<()> {
+    let paths = app_paths()?;
+    let set = load_rules(&paths.auto_switch_file)?;
+    if is_json_mode() {
+        return print_json(&serde_json::json!({
+            "ok": true,
+            "action": "auto-switch.list",
+            "rules": set.rules,
+        }));
+    }
+    if set.rules.is_empty() {
+        println!("暂无网络规则，可使用 `clash auto-switch add` 添加。");
+        return Ok(());
+    }
+    for rule in &set.rules {
+        println!(
+            "{}: {} => profile={} mode={}",
+            rule.name,
+            rule.describe_conditions(),
+            rule.profile.as_deref().unwrap_or("-"),
+            rule.mode.as_deref().unwrap_or("-")
+        );
+    }
+    Ok(())
+}
+
+fn cmd_add(args: AutoSwitchAddArgs) -> Result<()> {
+    let paths = app_paths()?;
+    let rule = NetworkRule {
+        name: args.name.trim().to_string(),
+        ssid: args.ssid,
+        gateway_ip: args.gateway_ip,
+        gateway_mac: args.gateway_mac,
+        interface: args.interface,
+        profile: args.profile,
+        mode: args.mode.map(|m| m.as_api_str().to_string()),
+    };
+    if rule.name.is_empty() {
+        bail!("规则名称不能为空");
+    }
+    if rule.describe_conditions().is_empty() {
+        bail!("至少需要一个匹配条件（--ssid/--gateway-ip/--gateway-mac/--interface）");
+    }
+    if rule.profile.is_none() && rule.mode.is_none() {
+        bail!("至少需要指定 --profile 或 --mode");
+    }
+    if let Some(profile) = &rule.profile {
+        let index = load_index(&paths.profile_index_file)?;
+        if !index.profiles.iter().any(|p| &p.name == profile) {
+            bail!("profile 不存在: {profile}");
+        }
+    }
+
+    let mut set = load_rules(&paths.auto_switch_file)?;
+    let replaced = match set.rules.iter_mut().find(|r| r.name == rule.name) {
+        Some(existing) => {
+            *existing = rule.clone();
+            true
+        }
+        None => {
+            set.rules.push(rule.clone());
+            false
+        }
+    };
+    save_rules(&paths.auto_switch_file, &set)?;
+
+    if is_json_mode() {
+        return print_json(&serde_json::json!({
+            "ok": true,
+            "action": "auto-switch.add",
+            "rule": rule,
+            "replaced": replaced,
+        }));
+    }
+    println!(
+        "{}网络规则: {}",
+        if replaced { "已更新" } else { "已添加" },
+        rule.name
+    );
+    Ok(())
+}
+
+fn cmd_remove(args: AutoSwitchRemoveArgs) -> Result<()> {
+    let paths = app_paths()?;
+    let mut set = load_rules(&paths.auto_switch_file)?;
+    let before = set.rules.len();
+    set.rules.retain(|r| r.name != args.name);
+    if set.rules.len() == before {
+        bail!("网络规则不存在: {}", args.name);
+    }
+    save_rules(&paths.auto_switch_file, &set)?;
+
+    if is_json_mode() {
+        return print_json(&serde_json::json!({
+            "ok": true,
+            "action": "auto-switch.remove",
+            "removed": args.name,
+        }));
+    }
+    println!("已删除网络规则: {}", args.name);
+    Ok(())
+}
+
+fn cmd_evaluate(args: AutoSwitchApplyArgs) -> Result<()> {
+    let paths = app_paths()?;
+    let result = evaluate(&paths, &args, detect_fingerprint())?;
+
+    if is_json_mode() {
+        return print_json(&serde_json::json!({
+            "ok": true,
+            "action": "auto-switch.evaluate",
+            "result": result,
+        }));
+    }
+    print_fingerprint(&result.fingerprint);
+    print_evaluation(&result);
+    Ok(())
+}
+
+fn cmd_run(args: AutoSwitchRunArgs) -> Result<()> {
+    let paths = app_paths()?;
+    let interval = Duration::from_secs(args.interval_secs.max(1));
+    let mut last: Option<NetworkFingerprint> = None;
+    if !is_json_mode() {
+        println!("auto-switch 已启动，检测间隔 {} 秒", interval.as_secs());
+    }
+
+    loop {
+        let fp = detect_fingerprint();
+        if last.as_ref() != Some(&fp) {
+            match evaluate(&paths, &args.apply, fp.clone()) {
+                Ok(result) => {
+                    if is_json_mode() {
+                        // 常驻模式按行输出事件，便于 journald/管道消费
+                        print_json_line(&serde_json::json!({
+                            "ok": true,
+                            "action": "auto-switch.evaluate",
+                            "at": utils::now_unix(),
+                            "result": result,
+                        }))?;
+                    } else {
+                        println!("网络变化: {}", fingerprint_summary(&fp));
+                        print_evaluation(&result);
+                    }
+                    last = Some(fp);
+                }
+                // 应用失败时不记录指纹，下一轮重试
+                Err(err) => {
+                    if is_json_mode() {
+                        print_json_line(&serde_json::json!({
+                            "ok": false,
+                            "action": "auto-switch.evaluate",
+                            "at": utils::now_unix(),
+                            "error": format!("{err:#}"),
+                        }))?;
+                    } else {
+                        eprintln!("auto-switch 应用失败: {err:#}");
+                    }
+                }
+            }
+        }
+        thread::sleep(interval);
+    }
+}
+
+pub(crate) fn evaluate(
+    paths: &AppPaths,
+    args: &AutoSwitchApplyArgs,
+    fingerprint: NetworkFingerprint,
+) -> Result<Evaluation> {
+    let set = load_rules(&paths.auto_switch_file)?;
+    let mut result = Evaluation {
+        fingerprint,
+        dry_run: args.dry_run,
+        ..Default::default()
+    };
+    let Some(rule) = set.rules.iter().find(|r| r.matches(&result.fingerprint)) else {
+        return Ok(result);
+    };
+    result.rule = Some(rule.name.clone());
+
+    if let Some(profile) = &rule.profile {
+        let mut index = load_index(&paths.profile_index_file)?;
+        if !index.profiles.iter().any(|p| &p.name == profile) {
+            bail!("规则 {} 引用的 profile 不存在: {}", rule.name, profile);
+        }
+        if index.active.as_deref() != Some(profile.as_str()) {
+            if !args.dry_run {
+                if !args.no_restart {
+                    ensure_service_runtime_home_matches_current(
+                        &args.service_name,
+                        &paths.runtime_config_file,
+                    )?;
+                }
+                let previous = index.active.replace(profile.clone());
+                save_index(&paths.profile_index_file, &index)?;
+                if let Err(err) = render_active_to_runtime(paths) {
+                    // 渲染失败时回滚 active，避免索引与运行配置不一致
+                    index.active = previous;
+                    save_index(&paths.profile_index_file, &index)?;
+                    return Err(err);
+                }
+                if !args.no_restart {
+                    restart_system_service(&args.service_name)?;
+                }
+            }
+            result.profile_switched = Some(profile.clone());
+        }
+    }
+
+    if let Some(mode) = &rule.mode {
+        let restarted = result.profile_switched.is_some() && !args.no_restart;
+        if args.dry_run || apply_mode(args, mode, restarted)? {
+            result.mode_set = Some(mode.clone());
+        }
+    }
+    Ok(result)
+}
+
+/// 设置运行模式，已是目标模式时返回 false；服务刚重启时等待 controller 就绪。
+fn apply_mode(args: &AutoSwitchApplyArgs, mode: &str, restarted: bool) -> Result<bool> {
+    let client = build_client(args.api.timeout_secs)?;
+    let ctx = load_api_context(&args.api)?;
+    let attempts = if restarted { MODE_APPLY_RETRIES } else { 1 };
+
+    let mut last_err = None;
+    for attempt in 0..attempts {
+        if attempt > 0 {
+            thread::sleep(Duration::from_secs(1));
+        }
+        let current = match api_get(&client, &ctx, "/configs") {
+            Ok(v) => v,
+            Err(err) => {
+                last_err = Some(err);
+                continue;
+            }
+        };
+        if current.get("mode").and_then(|v| v.as_str()) == Some(mode) {
+            return Ok(false);
+        }
+        api_patch(
+            &client,
+            &ctx,
+            "/configs",
+            serde_json::json!({ "mode": mode }),
+        )?;
+        return Ok(true);
+    }
+    Err(last_err
+        .unwrap_or_else(|| anyhow::anyhow!("controller 未就绪"))
+        .context(format!("设置运行模式 {mode} 失败")))
+}
+
+fn print_fingerprint(fp: &NetworkFingerprint) {
+    println!("网卡: {}", fp.interface.as_deref().unwrap_or("-"));
+    println!("网关 IP: {}", fp.gateway_ip.as_deref().unwrap_or("-"));
+    println!("网关 MAC: {}", fp.gateway_mac.as_deref().unwrap_or("-"));
+    println!("SSID: {}", fp.ssid.as_deref().unwrap_or("-"));
+}
+
+fn fingerprint_summary(fp: &NetworkFingerprint) -> String {
+    format!(
+        "iface={} gw={} mac={} ssid={}",
+        fp.interface.as_deref().unwrap_or("-"),
+        fp.gateway_ip.as_deref().unwrap_or("-"),
+        fp.gateway_mac.as_deref().unwrap_or("-"),
+        fp.ssid.as_deref().unwrap_or("-")
+    )
+}
+
+fn print_evaluation(result: &Evaluation) {
+    let Some(rule) = &result.rule else {
+        println!("未命中任何网络规则，保持当前配置。");
+        return;
+    };
+    let prefix = if result.dry_run { "[dry-run] " } else { "" };
+    println!("{prefix}命中规则: {rule}");
+    match &result.profile_switched {
+        Some(profile) => println!("{prefix}切换 profile -> {profile}"),
+        None => println!("profile 已是目标值，无需切换。"),
+    }
+    if let Some(mode) = &result.mode_set {
+        println!("{prefix}设置模式 -> {mode}");
+    }
+}
+
+/// 采集当前网络指纹；各字段独立探测，缺失时为 None。
+pub(crate) fn detect_fingerprint() -> NetworkFingerprint {
+    let route = fs::read_to_string("/proc/net/route").unwrap_or_default();
+    let (interface, gateway_ip) = match parse_default_route(&route) {
+        Some((iface, gw)) => (Some(iface), Some(gw.to_string())),
+        None => (None, None),
+    };
+    let gateway_mac = gateway_ip.as_deref().and_then(|ip| {
+        let arp = fs::read_to_string("/proc/net/arp").unwrap_or_default();
+        parse_arp_mac(&arp, ip)
+    });
+    let ssid = interface.as_deref().and_then(detect_ssid);
+    NetworkFingerprint {
+        interface,
+        gateway_ip,
+        gateway_mac,
+        ssid,
+    }
+}
+
+/// 解析 /proc/net/route，取 metric 最小的默认路由（网关为小端序十六进制）。
+fn parse_default_route(content: &str) -> Option<(String, Ipv4Addr)> {
+    content
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let cols: Vec<&str> = line.split_whitespace().collect();
+            if cols.len() < 8 || cols[1] != "00000000" || cols[7] != "00000000" {
+                return None;
+            }
+            let gateway = u32::from_str_radix(cols[2], 16).ok()?;
+            if gateway == 0 {
+                return None;
+            }
+            let metric = cols[6].parse::<u32>().unwrap_or(u32::MAX);
+            Some((
+                metric,
+                cols[0].to_string(),
+                Ipv4Addr::from(gateway.swap_bytes()),
+            ))
+        })
+        .min_by_key(|(metric, _, _)| *metric)
+        .map(|(_, iface, gw)| (iface, gw))
+}
+
+fn parse_arp_mac(content: &str, ip: &str) -> Option<String> {
+    content.lines().skip(1).find_map(|line| {
+        let cols: Vec<&str> = line.split_whitespace().collect();
+        if cols.len() >= 4 && cols[0] == ip && cols[3] != "00:00:00:00:00:00" {
+            Some(cols[3].to_ascii_lowercase())
+        } else {
+            None
+        }
+    })
+}
+
+/// 依次尝试 `iw dev <iface> link` 与 nmcli，获取当前连接的 SSID。
+fn detect_ssid(interface: &str) -> Option<String> {
+    if let Some(out) = command_stdout("iw", &["dev", interface, "link"])
+        && let Some(ssid) = out
+            .lines()
+            .find_map(|l| l.trim().strip_prefix("SSID: ").map(|v| v.to_string()))
+    {
+        return Some(ssid);
+    }
+    let out = command_stdout("nmcli", &["-t", "-f", "ACTIVE,SSID,DEVICE", "dev", "wifi"])?;
+    parse_nmcli_ssid(&out, interface)
+}
+
+/// 解析 nmcli 终端格式输出，字段中的 `:` 以 `\:` 转义。
+fn parse_nmcli_ssid(output: &str, interface: &str) -> Option<String> {
+    output.lines().find_map(|line| {
+        let mut fields = Vec::new();
+        let mut current = String::new();
+        let mut chars = line.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => current.extend(chars.next()),
+                ':' => fields.push(std::mem::take(&mut current)),
+                _ => current.push(c),
+            }
+        }
+        fields.push(current);
+        if fields.len() == 3
+            && fields[0] == "yes"
+            && fields[2] == interface
+            && !fields[1].is_empty()
+        {
+            Some(fields[1].clone())
+        } else {
+            None
+        }
+    })
+}
+
+fn command_stdout(binary: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(binary).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// profile 重命名后同步改写引用它的网络规则，返回改写的规则数。
+pub(crate) fn rename_profile_references(paths: &AppPaths, from: &str, to: &str) -> Result<usize> {
+    let mut set = load_rules(&paths.auto_switch_file)?;
+    let mut renamed = 0;
+    for rule in &mut set.rules {
+        if rule.profile.as_deref() == Some(from) {
+            rule.profile = Some(to.to_string());
+            renamed += 1;
+        }
+    }
+    if renamed > 0 {
+        save_rules(&paths.auto_switch_file, &set)?;
+    }
+    Ok(renamed)
+}
+
+fn load_rules(path: &Path) -> Result<RuleSet> {
+    if !path.exists() {
+        return Ok(RuleSet::default());
+    }
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("读取网络规则失败: {}", path.display()))?;
+    serde_json::from_str(&content).with_context(|| format!("解析网络规则失败: {}", path.display()))
+}
+
+fn save_rules(path: &Path, set: &RuleSet) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("创建目录失败: {}", parent.display()))?;
+    }
+    let content = serde_json::to_string_pretty(set).context("序列化网络规则失败")?;
+    fs::write(path, content).with_context(|| format!("写入网络规则失败: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_default_route_should_pick_lowest_metric_gateway() {
+        let route = "\
+Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT
+wlan0\t00000000\t0101A8C0\t0003\t0\t0\t600\t00000000\t0\t0\t0
+eth0\t00000000\t0100000A\t0003\t0\t0\t100\t00000000\t0\t0\t0
+eth0\t0000000A\t00000000\t0001\t0\t0\t100\t00FFFFFF\t0\t0\t0
+";
+        let (iface, gw) = parse_default_route(route).expect("应解析出默认路由");
+        assert_eq!(iface, "eth0");
+        assert_eq!(gw, Ipv4Addr::new(10, 0, 0, 1));
+
+        let arp = "\
+IP address       HW type     Flags       HW address            Mask     Device
+10.0.0.1         0x1         0x2         AA:BB:CC:DD:EE:FF     *        eth0
+";
+        assert_eq!(
+            parse_arp_mac(arp, "10.0.0.1").as_deref(),
+            Some("aa:bb:cc:dd:ee:ff")
+        );
+        assert_eq!(
+            parse_nmcli_ssid("no:Other:wlan0\nyes:Office\\:5G:wlan0\n", "wlan0").as_deref(),
+            Some("Office:5G")
+        );
+    }
+
+    #[test]
+    fn network_rule_should_require_all_conditions() {
+        let fp = NetworkFingerprint {
+            interface: Some("wlan0".to_string()),
+            gateway_ip: Some("192.168.1.1".to_string()),
+            gateway_mac: Some("aa:bb:cc:dd:ee:ff".to_string()),
+            ssid: Some("Office".to_string()),
+        };
+        let rule = NetworkRule {
+            name: "office".to_string(),
+            ssid: Some("Office".to_string()),
+            gateway_mac: Some("AA:BB:CC:DD:EE:FF".to_string()),
+            ..Default::default()
+        };
+        assert!(rule.matches(&fp));
+
+        let other = NetworkRule {
+            interface: Some("eth0".to_string()),
+            ..rule.clone()
+        };
+        assert!(!other.matches(&fp));
+        assert!(!NetworkRule::default().matches(&fp));
+    }
+}
@@ -13,6 +13,12 @@ const DEFAULT_PROFILE_NAME: &str = "main";
 pub struct Cli {
     #[arg(long, global = true, help = "以 JSON 格式输出")]
     pub json: bool,
+    #[arg(
+        long,
+        global = true,
+        help = "输出中显示订阅 URL 等敏感信息（默认脱敏）"
+    )]
+    pub show_secrets: bool,
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -54,6 +60,31 @@ pub enum Commands {
         #[command(subcommand)]
         command: SetupCommand,
     },
+    #[command(about = "管理 Dashboard 面板（安装/切换/离线导入）")]
+    Ui {
+        #[command(subcommand)]
+        command: UiCommand,
+    },
+    #[command(about = "管理 geo 数据库（geoip/geosite/mmdb/asn）")]
+    Geo {
+        #[command(subcommand)]
+        command: GeoCommand,
+    },
+    #[command(about = "规则回归测试（按用例校验目标地址命中的策略）")]
+    Rule {
+        #[command(subcommand)]
+        command: RuleCommand,
+    },
+    #[command(about = "按网络环境（网关/网卡/SSID）自动切换 profile 与模式")]
+    AutoSwitch {
+        #[command(subcommand)]
+        command: AutoSwitchCommand,
+    },
+    #[command(about = "安装网络变化钩子（NetworkManager / networkd-dispatcher）")]
+    Hooks {
+        #[command(subcommand)]
+        command: HooksCommand,
+    },
     #[command(about = "更新 clash CLI 自身到最新版本")]
     Update {
         #[command(subcommand)]
@@ -113,6 +144,25 @@ pub enum ServiceCommand {
     Status(ServiceTargetArgs),
     #[command(about = "查看服务日志（journalctl）")]
     Log(ServiceLogArgs),
+    #[command(about = "管理 systemd system-sleep 钩子（唤醒后自动恢复连接）")]
+    SleepHook {
+        #[command(subcommand)]
+        command: SleepHookCommand,
+    },
+    #[command(
+        about = "唤醒后恢复：关闭旧连接、触发 provider 健康检查，controller 无响应时重启服务"
+    )]
+    Resume(ServiceResumeArgs),
+}
+
+#[derive(Subcommand)]
+pub enum SleepHookCommand {
+    #[command(about = "写入 system-sleep 钩子脚本")]
+    Install(ServiceSleepHookArgs),
+    #[command(about = "删除 system-sleep 钩子脚本")]
+    Uninstall(ServiceTargetArgs),
+    #[command(about = "查看 system-sleep 钩子安装状态")]
+    Status(ServiceTargetArgs),
 }
 
 #[derive(Subcommand)]
@@ -139,10 +189,31 @@ pub enum ProfileCommand {
     Fetch(ProfileFetchArgs),
     #[command(about = "删除 profile")]
     Remove(ProfileRemoveArgs),
+    #[command(about = "重命名 profile（同步重命名文件并保持 active）")]
+    Rename(ProfileRenameArgs),
+    #[command(about = "修改 profile 的订阅 URL")]
+    SetUrl(ProfileSetUrlArgs),
+    #[command(about = "设置 profile 备注与标签")]
+    Set(ProfileSetArgs),
     #[command(about = "将 profile 渲染到运行配置 runtime/config.yaml")]
     Render(ProfileRenderArgs),
     #[command(about = "校验 profile YAML 基础合法性")]
     Validate(ProfileValidateArgs),
+    #[command(about = "预取渲染配置引用的 rule-provider 与 geo 数据库到工作目录")]
+    Prefetch(ProfilePrefetchArgs),
+    #[command(about = "导出 profile 节点为分享链接、sing-box 或精简 Clash 配置")]
+    Export(ProfileExportArgs),
+    #[command(about = "启动局域网订阅服务（令牌 URL，支持多种导出格式）")]
+    Serve(ProfileServeArgs),
+    #[command(about = "使用本地密钥文件加密存储订阅 URL 与 profile 文件")]
+    Encrypt,
+    #[command(about = "解密 profile 存储并恢复明文")]
+    Decrypt(ProfileDecryptArgs),
+    #[command(about = "订阅故障转移：按优先级自动切换到健康的备用 profile")]
+    Failover {
+        #[command(subcommand)]
+        command: FailoverCommand,
+    },
     #[command(about = "管理 mixin.yaml 覆盖配置（show/set/unset/reset）")]
     Mixin {
         #[command(subcommand)]
@@ -161,10 +232,53 @@ pub enum ApiCommand {
         #[command(flatten)]
         common: ApiCommonArgs,
     },
-    #[command(about = "查看代理组与节点摘要")]
-    Proxies(ApiCommonArgs),
-    #[command(about = "查看当前连接摘要")]
-    Connections(ApiCommonArgs),
+    #[command(about = "以树形查看策略组、当前节点与延迟，或切换策略组节点")]
+    Proxies {
+        #[command(subcommand)]
+        action: Option<ApiProxiesCommand>,
+        #[command(flatten)]
+        list: ApiProxiesListArgs,
+        #[command(flatten)]
+        common: ApiCommonArgs,
+    },
+    #[command(about = "测试节点或策略组延迟，可切换到最快节点")]
+    Delay(ApiDelayArgs),
+    #[command(about = "列出/聚合/导出当前连接，或按 id/条件关闭连接")]
+    Connections {
+        #[command(subcommand)]
+        action: Option<ApiConnectionsCommand>,
+        #[command(flatten)]
+        list: ApiConnectionsListArgs,
+        #[command(flatten)]
+        common: ApiCommonArgs,
+    },
+    #[command(about = "实时监控流量速率、高流量连接与新建连接（类似 top）")]
+    Watch(ApiWatchArgs),
+    #[command(about = "实时读取内核日志（不依赖 journalctl，可用于远程控制器）")]
+    Logs(ApiLogsArgs),
+    #[command(about = "查看运行时规则、按出站统计并搜索命中规则")]
+    Rules(ApiRulesArgs),
+    #[command(about = "查看、更新 proxy/rule provider，或对 proxy provider 做健康检查")]
+    Providers {
+        #[command(subcommand)]
+        action: ApiProvidersCommand,
+        #[command(flatten)]
+        common: ApiCommonArgs,
+    },
+    #[command(about = "通过控制器查询 DNS（排查 fake-ip/DNS 问题）")]
+    Dns {
+        #[command(subcommand)]
+        action: ApiDnsCommand,
+        #[command(flatten)]
+        common: ApiCommonArgs,
+    },
+    #[command(about = "清空内核 fake-ip 或 DNS 缓存")]
+    Cache {
+        #[command(subcommand)]
+        action: ApiCacheCommand,
+        #[command(flatten)]
+        common: ApiCommonArgs,
+    },
     #[command(about = "输出 Dashboard 访问地址（含 controller/ui 元信息）")]
     UiUrl(ApiCommonArgs),
 }
@@ -213,6 +327,12 @@ pub struct ProfileUseArgs {
     pub service_name: String,
     #[arg(long, help = "apply 后仅渲染，不自动重启服务")]
     pub no_restart: bool,
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "apply 前执行规则回归用例，任一失败则阻止切换"
+    )]
+    pub rule_cases: Option<PathBuf>,
 }
 
 #[derive(Args, Clone)]
@@ -229,6 +349,57 @@ pub struct ProfileRemoveArgs {
     pub name: String,
 }
 
+#[derive(Args, Clone)]
+pub struct ProfileRenameArgs {
+    #[arg(long, help = "当前 profile 名称")]
+    pub name: String,
+    #[arg(long, help = "新名称")]
+    pub to: String,
+}
+
+#[derive(Args, Clone)]
+pub struct ProfileSetUrlArgs {
+    #[arg(long, help = "profile 名称")]
+    pub name: String,
+    #[arg(long, help = "新的订阅 URL（支持 ${VAR} 占位符）")]
+    pub url: String,
+    #[arg(long, help = "更新后立即拉取")]
+    pub fetch: bool,
+}
+
+#[derive(Args, Clone)]
+pub struct ProfileSetArgs {
+    #[arg(long, help = "profile 名称")]
+    pub name: String,
+    #[arg(long, conflicts_with = "clear_note", help = "备注（空字符串等同清除）")]
+    pub note: Option<String>,
+    #[arg(long, help = "清除备注")]
+    pub clear_note: bool,
+    #[arg(long = "tag", help = "添加标签，可重复")]
+    pub tags: Vec<String>,
+    #[arg(long = "remove-tag", help = "移除标签，可重复")]
+    pub remove_tags: Vec<String>,
+    #[arg(long, help = "清空全部标签（先于 --tag 生效）")]
+    pub clear_tags: bool,
+    #[arg(
+        long,
+        conflicts_with = "clear_priority",
+        help = "故障转移优先级，数字越小越优先"
+    )]
+    pub priority: Option<u32>,
+    #[arg(long, help = "清除优先级（不再参与故障转移）")]
+    pub clear_priority: bool,
+    #[arg(
+        long,
+        value_name = "MIN_RULES",
+        conflicts_with = "no_extract_providers",
+        help = "自动重新渲染运行配置时抽取本地 provider（use/setup/failover/auto-switch 均生效）"
+    )]
+    pub extract_providers: Option<usize>,
+    #[arg(long, help = "取消自动抽取本地 provider")]
+    pub no_extract_providers: bool,
+}
+
 #[derive(Args, Clone)]
 pub struct ProfileRenderArgs {
     #[arg(long, help = "profile 名称，默认使用当前 active")]
@@ -239,6 +410,18 @@ pub struct ProfileRenderArgs {
     pub no_mixin: bool,
     #[arg(long, help = "渲染时跟随订阅中的监听端口与控制器设置")]
     pub follow_subscription_port: bool,
+    #[arg(
+        long,
+        help = "将内联 rules/proxies 抽取为输出目录 providers/ 下的本地 provider"
+    )]
+    pub extract_providers: bool,
+    #[arg(
+        long,
+        default_value_t = 20,
+        requires = "extract_providers",
+        help = "相邻同目标规则达到该数量才抽取为 rule-provider"
+    )]
+    pub extract_min_rules: usize,
 }
 
 #[derive(Args, Clone)]
@@ -247,16 +430,423 @@ pub struct ProfileValidateArgs {
     pub name: Option<String>,
 }
 
+#[derive(Args, Clone)]
+pub struct ProfileDecryptArgs {
+    #[arg(long, help = "解密后保留密钥文件")]
+    pub keep_key: bool,
+}
+
+#[derive(Args, Clone)]
+pub struct ProfileExportArgs {
+    #[arg(long, help = "profile 名称，默认使用当前 active")]
+    pub name: Option<String>,
+    #[arg(long, value_enum, default_value_t = ExportFormat::Clash, help = "导出格式")]
+    pub format: ExportFormat,
+    #[arg(long, help = "输出文件路径，默认输出到 stdout")]
+    pub output: Option<PathBuf>,
+    #[arg(long, help = "导出时忽略 mixin.yaml")]
+    pub no_mixin: bool,
+    #[arg(long, help = "保留 provider 原始 url 与 header（可能包含订阅令牌）")]
+    pub keep_provider_urls: bool,
+}
+
+#[derive(Args, Clone)]
+pub struct ProfileServeArgs {
+    #[arg(long, help = "profile 名称，默认使用请求时的 active profile")]
+    pub name: Option<String>,
+    #[arg(long, default_value = constants::DEFAULT_SERVE_LISTEN, help = "监听地址")]
+    pub listen: String,
+    #[arg(
+        long,
+        env = "CLASH_CLI_SERVE_TOKEN",
+        help = "访问令牌，默认读取或生成 serve.token"
+    )]
+    pub token: Option<String>,
+    #[arg(long, help = "生成订阅时忽略 mixin.yaml")]
+    pub no_mixin: bool,
+    #[arg(long, help = "安装为 systemd 服务而不是前台运行")]
+    pub install_service: bool,
+    #[arg(
+        long,
+        default_value = constants::DEFAULT_SERVE_SERVICE_NAME,
+        help = "systemd 服务名（配合 --install-service）"
+    )]
+    pub service_name: String,
+    #[arg(long, help = "安装为 user 级服务（systemctl --user）")]
+    pub user: bool,
+    #[arg(long, help = "覆盖已存在的 unit 文件")]
+    pub force: bool,
+    #[arg(long, help = "安装后不立即启动服务")]
+    pub no_start: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// 分享链接列表（每行一个）
+    Links,
+    /// base64 编码的分享链接列表（通用订阅格式）
+    Base64,
+    /// sing-box outbounds JSON
+    SingBox,
+    /// 去除本地控制器/密钥等敏感字段的 Clash YAML
+    Clash,
+}
+
+impl ExportFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ExportFormat::Links => "links",
+            ExportFormat::Base64 => "base64",
+            ExportFormat::SingBox => "sing-box",
+            ExportFormat::Clash => "clash",
+        }
+    }
+}
+
+#[derive(Args, Clone)]
+pub struct ProfilePrefetchArgs {
+    #[arg(long, help = "待预取的配置文件，默认 runtime/config.yaml")]
+    pub config: Option<PathBuf>,
+    #[arg(
+        long,
+        help = "mihomo 工作目录，默认读取 service 的 -d 参数，回退 runtime 目录"
+    )]
+    pub workdir: Option<PathBuf>,
+    #[arg(
+        long,
+        default_value = DEFAULT_SERVICE_NAME,
+        help = "用于探测工作目录的 systemd 服务名"
+    )]
+    pub service_name: String,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = MirrorSource::Auto,
+        help = "下载镜像策略"
+    )]
+    pub mirror: MirrorSource,
+    #[arg(
+        long,
+        default_value_t = 24,
+        help = "本地文件在该小时数内视为新鲜，跳过下载"
+    )]
+    pub max_age_hours: u64,
+    #[arg(long, help = "忽略新鲜度强制重新下载")]
+    pub force: bool,
+    #[arg(long, help = "将 http rule-provider 改写为本地 file 类型")]
+    pub rewrite_local: bool,
+    #[arg(long, help = "跳过 geo 数据库")]
+    pub no_geo: bool,
+}
+
 #[derive(Args, Clone)]
 pub struct ApiCommonArgs {
-    #[arg(long, help = "external-controller 地址，例如 127.0.0.1:9090")]
+    #[arg(
+        long,
+        global = true,
+        help = "external-controller 地址，例如 127.0.0.1:9090"
+    )]
     pub controller: Option<String>,
-    #[arg(long, help = "external-controller secret")]
+    #[arg(long, global = true, help = "external-controller secret")]
     pub secret: Option<String>,
-    #[arg(long, default_value_t = 15, help = "API 请求超时秒数")]
+    #[arg(long, global = true, default_value_t = 15, help = "API 请求超时秒数")]
     pub timeout_secs: u64,
 }
 
+#[derive(Args, Clone)]
+pub struct ApiProxiesListArgs {
+    #[arg(long, help = "仅显示指定策略组（含嵌套子组）")]
+    pub group: Option<String>,
+    #[arg(
+        long = "type",
+        help = "仅显示指定类型的策略组，例如 selector/url-test/fallback/load-balance"
+    )]
+    pub kind: Option<String>,
+    #[arg(long, help = "仅显示可用（alive）的成员")]
+    pub alive: bool,
+}
+
+#[derive(Args, Clone)]
+pub struct ApiDelayArgs {
+    #[arg(
+        long,
+        conflicts_with_all = ["group", "all"],
+        help = "测试单个节点"
+    )]
+    pub proxy: Option<String>,
+    #[arg(
+        long,
+        conflicts_with = "all",
+        help = "测试策略组全部成员（/group/{name}/delay）"
+    )]
+    pub group: Option<String>,
+    #[arg(long, help = "逐个测试全部节点（不含策略组与 DIRECT/REJECT）")]
+    pub all: bool,
+    #[arg(
+        long,
+        default_value = constants::DEFAULT_PROBE_URL,
+        help = "延迟测试 URL"
+    )]
+    pub url: String,
+    #[arg(long, default_value_t = 5000, help = "单节点延迟测试超时（毫秒）")]
+    pub timeout_ms: u64,
+    #[arg(long, default_value_t = 8, help = "--all 时的并发数")]
+    pub concurrency: usize,
+    #[arg(
+        long,
+        requires = "group",
+        help = "测试后将 Selector 策略组切换到延迟最低的节点"
+    )]
+    pub select_fastest: bool,
+    #[command(flatten)]
+    pub api: ApiCommonArgs,
+}
+
+#[derive(Args, Clone)]
+pub struct ApiConnectionsListArgs {
+    #[arg(
+        long,
+        value_name = "KEY=VALUE",
+        help = "按条件过滤（可重复，需全部满足）：host/process/rule/chain/source-ip/network"
+    )]
+    pub filter: Vec<String>,
+    #[arg(long, value_enum, help = "排序字段（流量与时长降序，host 升序）")]
+    pub sort: Option<ConnectionSortKey>,
+    #[arg(long, help = "最多显示的条目数")]
+    pub limit: Option<usize>,
+    #[arg(long, value_enum, help = "按字段聚合连接数与流量")]
+    pub group_by: Option<ConnectionGroupKey>,
+    #[arg(
+        long,
+        value_enum,
+        help = "导出为 CSV 或 JSON Lines（默认输出到 stdout）"
+    )]
+    pub export: Option<ConnectionExportFormat>,
+    #[arg(long, requires = "export", help = "导出文件路径")]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ConnectionSortKey {
+    Download,
+    Upload,
+    Duration,
+    Host,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ConnectionGroupKey {
+    Host,
+    Rule,
+    Chain,
+    Process,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ConnectionExportFormat {
+    Csv,
+    Jsonl,
+}
+
+#[derive(Args, Clone)]
+pub struct ApiWatchArgs {
+    #[arg(long, default_value_t = 1000, help = "刷新间隔（毫秒）")]
+    pub interval_ms: u64,
+    #[arg(long, default_value_t = 10, help = "显示速率最高的连接数")]
+    pub top: usize,
+    #[arg(long, help = "刷新指定次数后退出，默认持续运行")]
+    pub count: Option<u64>,
+    #[arg(
+        long,
+        value_name = "KEY=VALUE",
+        help = "仅统计匹配的连接（同 api connections --filter）"
+    )]
+    pub filter: Vec<String>,
+    #[command(flatten)]
+    pub api: ApiCommonArgs,
+}
+
+#[derive(Args, Clone)]
+pub struct ApiLogsArgs {
+    #[arg(long, value_enum, default_value = "info", help = "最低日志级别")]
+    pub level: ApiLogLevel,
+    #[arg(long, help = "仅显示目标主机包含该字符串的连接日志")]
+    pub host: Option<String>,
+    #[arg(
+        long,
+        help = "仅显示命中规则包含该字符串的连接日志（如 DomainSuffix、google.com）"
+    )]
+    pub rule: Option<String>,
+    #[arg(
+        long,
+        value_name = "TEXT",
+        help = "仅显示包含该字符串的日志（忽略大小写，可重复，需全部匹配）"
+    )]
+    pub grep: Vec<String>,
+    #[arg(long, help = "输出指定条数（过滤后）后退出，默认持续运行")]
+    pub count: Option<u64>,
+    #[arg(long, help = "禁用彩色输出（也可设置 NO_COLOR 环境变量）")]
+    pub no_color: bool,
+    #[command(flatten)]
+    pub api: ApiCommonArgs,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum ApiLogLevel {
+    Debug,
+    Info,
+    Warning,
+    Error,
+}
+
+impl ApiLogLevel {
+    pub fn as_api_str(self) -> &'static str {
+        match self {
+            ApiLogLevel::Debug => "debug",
+            ApiLogLevel::Info => "info",
+            ApiLogLevel::Warning => "warning",
+            ApiLogLevel::Error => "error",
+        }
+    }
+}
+
+#[derive(Args, Clone)]
+pub struct ApiRulesArgs {
+    #[arg(
+        long = "match",
+        value_name = "HOST",
+        help = "搜索规则：payload 子串，或按域名后缀/关键字语义命中（如 www.example.com）"
+    )]
+    pub match_host: Option<String>,
+    #[arg(long, help = "仅显示指定出站的规则")]
+    pub target: Option<String>,
+    #[arg(
+        long = "type",
+        help = "仅显示指定类型的规则，例如 domain-suffix/rule-set/geoip"
+    )]
+    pub kind: Option<String>,
+    #[arg(long, help = "最多显示的规则条数")]
+    pub limit: Option<usize>,
+    #[arg(long, help = "仅输出按出站统计与规则集信息，不列出规则")]
+    pub summary: bool,
+    #[command(flatten)]
+    pub api: ApiCommonArgs,
+}
+
+#[derive(Subcommand, Clone)]
+pub enum ApiProvidersCommand {
+    #[command(about = "列出 provider：类型、上次更新、节点/规则数与订阅信息")]
+    List(ApiProvidersListArgs),
+    #[command(about = "更新指定或全部 provider")]
+    Update(ApiProvidersUpdateArgs),
+    #[command(about = "对指定或全部 proxy provider 执行健康检查")]
+    Healthcheck(ApiProvidersHealthcheckArgs),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ProviderKind {
+    /// proxy-providers
+    Proxy,
+    /// rule-providers
+    Rule,
+}
+
+#[derive(Args, Clone)]
+pub struct ApiProvidersListArgs {
+    #[arg(long, value_enum, help = "仅列出指定类别的 provider")]
+    pub kind: Option<ProviderKind>,
+}
+
+#[derive(Args, Clone)]
+pub struct ApiProvidersUpdateArgs {
+    #[arg(help = "provider 名称")]
+    pub name: Option<String>,
+    #[arg(long, conflicts_with = "name", help = "更新全部 provider")]
+    pub all: bool,
+    #[arg(
+        long,
+        value_enum,
+        help = "限定 provider 类别（同名或配合 --all 时使用）"
+    )]
+    pub kind: Option<ProviderKind>,
+}
+
+#[derive(Args, Clone)]
+pub struct ApiProvidersHealthcheckArgs {
+    #[arg(help = "proxy provider 名称")]
+    pub name: Option<String>,
+    #[arg(long, conflicts_with = "name", help = "检查全部 proxy provider")]
+    pub all: bool,
+}
+
+#[derive(Subcommand, Clone)]
+pub enum ApiDnsCommand {
+    #[command(about = "使用内核的 DNS 解析器查询域名")]
+    Query(ApiDnsQueryArgs),
+}
+
+#[derive(Args, Clone)]
+pub struct ApiDnsQueryArgs {
+    #[arg(help = "要查询的域名")]
+    pub name: String,
+    #[arg(
+        long = "type",
+        default_value = "A",
+        help = "记录类型，例如 A/AAAA/CNAME/MX/TXT/HTTPS"
+    )]
+    pub kind: String,
+}
+
+#[derive(Subcommand, Clone)]
+pub enum ApiCacheCommand {
+    #[command(about = "清空 fake-ip 和/或 DNS 缓存")]
+    Flush(ApiCacheFlushArgs),
+}
+
+#[derive(Args, Clone)]
+pub struct ApiCacheFlushArgs {
+    #[arg(long, help = "清空 fake-ip 映射缓存")]
+    pub fakeip: bool,
+    #[arg(long, help = "清空 DNS 解析缓存")]
+    pub dns: bool,
+}
+
+#[derive(Subcommand, Clone)]
+pub enum ApiConnectionsCommand {
+    #[command(about = "关闭全部、指定 id 或匹配条件的连接")]
+    Close(ApiConnectionsCloseArgs),
+}
+
+#[derive(Args, Clone)]
+pub struct ApiConnectionsCloseArgs {
+    #[arg(long, conflicts_with_all = ["id", "filter"], help = "关闭全部连接")]
+    pub all: bool,
+    #[arg(long, help = "按连接 id 关闭（可重复）")]
+    pub id: Vec<String>,
+    #[arg(
+        long,
+        value_name = "KEY=VALUE",
+        help = "按条件关闭（可重复，需全部满足）：host/process/rule/chain/source-ip/network"
+    )]
+    pub filter: Vec<String>,
+    #[arg(long, help = "仅列出将被关闭的连接，不实际关闭")]
+    pub dry_run: bool,
+}
+
+#[derive(Subcommand, Clone)]
+pub enum ApiProxiesCommand {
+    #[command(about = "切换 Selector 策略组的当前节点（PUT /proxies/{group}）")]
+    Select(ApiProxiesSelectArgs),
+}
+
+#[derive(Args, Clone)]
+pub struct ApiProxiesSelectArgs {
+    #[arg(long, help = "策略组名称（须为 Selector 类型）")]
+    pub group: String,
+    #[arg(long, help = "目标节点名，支持模糊匹配；省略时在终端中交互选择")]
+    pub proxy: Option<String>,
+}
+
 #[derive(Args, Clone)]
 pub struct ApiModeSetArgs {
     #[arg(value_enum, help = "目标模式")]
@@ -459,6 +1049,30 @@ pub struct ServiceInstallArgs {
     pub no_enable: bool,
     #[arg(long, help = "安装后不自动 start")]
     pub no_start: bool,
+    #[arg(long, help = "同时安装 system-sleep 钩子（唤醒后自动恢复）")]
+    pub sleep_hook: bool,
+}
+
+#[derive(Args)]
+pub struct ServiceSleepHookArgs {
+    #[command(flatten)]
+    pub target: ServiceTargetArgs,
+    #[arg(long, help = "覆盖已存在的非 clash-cli 脚本")]
+    pub force: bool,
+}
+
+#[derive(Args)]
+pub struct ServiceResumeArgs {
+    #[command(flatten)]
+    pub target: ServiceTargetArgs,
+    #[arg(
+        long,
+        default_value_t = 10,
+        help = "等待 controller 响应的秒数，超时后重启服务"
+    )]
+    pub wait_secs: u64,
+    #[command(flatten)]
+    pub api: ApiCommonArgs,
 }
 
 #[derive(Args)]
@@ -538,6 +1152,16 @@ pub enum MirrorSource {
     Github,
 }
 
+impl MirrorSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MirrorSource::Auto => "auto",
+            MirrorSource::Ghfast => "ghfast",
+            MirrorSource::Github => "github",
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, ValueEnum)]
 pub enum Amd64Variant {
     Auto,
@@ -545,6 +1169,48 @@ pub enum Amd64Variant {
     V3,
 }
 
+// --- Failover 子命令 ---
+
+#[derive(Subcommand, Clone)]
+pub enum FailoverCommand {
+    #[command(about = "检查 active profile 健康状况，必要时切换到下一个健康的备用 profile")]
+    Check(FailoverCheckArgs),
+    #[command(about = "查看备用顺序、连续失败计数与最近的切换事件")]
+    Status,
+}
+
+#[derive(Args, Clone)]
+pub struct FailoverCheckArgs {
+    #[arg(
+        long,
+        default_value_t = 3,
+        help = "active 订阅连续拉取失败达到该次数后触发切换"
+    )]
+    pub max_failures: u32,
+    #[arg(long, help = "不通过 controller 测试节点延迟")]
+    pub no_probe: bool,
+    #[arg(
+        long,
+        default_value = constants::DEFAULT_PROBE_URL,
+        help = "延迟测试 URL"
+    )]
+    pub probe_url: String,
+    #[arg(long, default_value_t = 5000, help = "单节点延迟测试超时（毫秒）")]
+    pub probe_timeout_ms: u64,
+    #[arg(long, help = "仅判断是否需要切换，不修改任何状态")]
+    pub dry_run: bool,
+    #[arg(
+        long,
+        default_value = DEFAULT_SERVICE_NAME,
+        help = "切换后联动重启的 systemd 服务名"
+    )]
+    pub service_name: String,
+    #[arg(long, help = "切换后仅渲染，不自动重启服务")]
+    pub no_restart: bool,
+    #[command(flatten)]
+    pub api: ApiCommonArgs,
+}
+
 // --- Mixin 子命令 ---
 
 #[derive(Subcommand, Clone)]
@@ -571,6 +1237,270 @@ pub struct MixinSetArgs {
     pub value: String,
 }
 
+// --- Rule 命令 ---
+
+#[derive(Subcommand)]
+pub enum RuleCommand {
+    #[command(about = "基于渲染后的配置本地求值，校验用例中的目标地址是否命中期望策略")]
+    Test(RuleTestArgs),
+}
+
+#[derive(Args, Clone)]
+pub struct RuleTestArgs {
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "用例 YAML 文件（destination -> 期望策略）"
+    )]
+    pub cases: PathBuf,
+    #[arg(long, help = "按 profile 渲染后测试（默认测试 runtime/config.yaml）")]
+    pub name: Option<String>,
+    #[arg(long, conflicts_with = "name", help = "直接指定待测配置文件")]
+    pub config: Option<PathBuf>,
+}
+
+// --- Geo 命令 ---
+
+#[derive(Subcommand)]
+pub enum GeoCommand {
+    #[command(about = "查看工作目录中 geo 数据库的存在情况、大小与更新时间")]
+    Status(GeoTargetArgs),
+    #[command(about = "下载/更新 geo 数据库（镜像回退 + 原子替换）")]
+    Update(GeoUpdateArgs),
+}
+
+#[derive(Args, Clone)]
+pub struct GeoTargetArgs {
+    #[arg(
+        long,
+        help = "mihomo 工作目录，默认读取 service 的 -d 参数，回退 runtime 目录"
+    )]
+    pub workdir: Option<PathBuf>,
+    #[arg(
+        long,
+        default_value = DEFAULT_SERVICE_NAME,
+        help = "用于探测工作目录的 systemd 服务名"
+    )]
+    pub service_name: String,
+}
+
+#[derive(Args, Clone)]
+pub struct GeoUpdateArgs {
+    #[command(flatten)]
+    pub target: GeoTargetArgs,
+    #[arg(
+        long = "kind",
+        value_enum,
+        help = "仅更新指定类型，可重复；默认更新已存在或配置引用的文件"
+    )]
+    pub kinds: Vec<GeoKindValue>,
+    #[arg(
+        long = "url",
+        value_name = "KIND=URL",
+        help = "覆盖下载源（默认取配置 geox-url，回退 mihomo 默认源），可重复"
+    )]
+    pub urls: Vec<String>,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = MirrorSource::Auto,
+        help = "下载镜像策略"
+    )]
+    pub mirror: MirrorSource,
+    #[arg(long, help = "更新后调用控制器重载 geo 数据库（POST /configs/geo）")]
+    pub reload: bool,
+    #[command(flatten)]
+    pub api: ApiCommonArgs,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum GeoKindValue {
+    Geoip,
+    Geosite,
+    Mmdb,
+    Asn,
+}
+
+// --- UI 命令 ---
+
+#[derive(Subcommand)]
+pub enum UiCommand {
+    #[command(about = "列出内置面板、已安装面板与当前选择")]
+    List(UiTargetArgs),
+    #[command(about = "下载或从本地 zip 安装面板到工作目录的 ui 目录")]
+    Install(UiInstallArgs),
+    #[command(about = "切换渲染配置使用的面板（更新 external-ui-name/url）")]
+    Use(UiUseArgs),
+}
+
+#[derive(Args, Clone)]
+pub struct UiTargetArgs {
+    #[arg(
+        long,
+        help = "mihomo 工作目录，默认读取 service 的 -d 参数，回退 runtime 目录"
+    )]
+    pub workdir: Option<PathBuf>,
+    #[arg(
+        long,
+        default_value = DEFAULT_SERVICE_NAME,
+        help = "用于探测工作目录的 systemd 服务名"
+    )]
+    pub service_name: String,
+}
+
+#[derive(Args, Clone)]
+pub struct UiInstallArgs {
+    #[arg(
+        long,
+        default_value = constants::DEFAULT_EXTERNAL_UI_NAME,
+        help = "面板名称（内置: metacubexd/yacd-meta/zashboard，自定义名称需配合 --archive 或 --url）"
+    )]
+    pub name: String,
+    #[arg(long, conflicts_with = "url", help = "本地 zip 包路径（离线安装）")]
+    pub archive: Option<PathBuf>,
+    #[arg(long, help = "自定义 zip 下载地址")]
+    pub url: Option<String>,
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = MirrorSource::Auto,
+        help = "下载镜像策略"
+    )]
+    pub mirror: MirrorSource,
+    #[arg(long = "use", help = "安装后切换为当前面板并重新渲染")]
+    pub use_ui: bool,
+    #[command(flatten)]
+    pub target: UiTargetArgs,
+}
+
+#[derive(Args, Clone)]
+pub struct UiUseArgs {
+    #[arg(long, help = "面板名称")]
+    pub name: String,
+    #[arg(long, help = "仅记录选择，不重新渲染 runtime 配置")]
+    pub no_render: bool,
+    #[command(flatten)]
+    pub target: UiTargetArgs,
+}
+
+// --- AutoSwitch 命令 ---
+
+#[derive(Subcommand)]
+pub enum AutoSwitchCommand {
+    #[command(about = "输出当前网络指纹（网关 IP/MAC、网卡、SSID）")]
+    Detect,
+    #[command(about = "列出网络规则")]
+    List,
+    #[command(about = "添加或覆盖网络规则（条件需全部满足）")]
+    Add(AutoSwitchAddArgs),
+    #[command(about = "删除网络规则")]
+    Remove(AutoSwitchRemoveArgs),
+    #[command(about = "检测一次当前网络并应用匹配规则")]
+    Evaluate(AutoSwitchApplyArgs),
+    #[command(about = "常驻运行，网络变化时自动应用匹配规则")]
+    Run(AutoSwitchRunArgs),
+}
+
+#[derive(Args, Clone)]
+pub struct AutoSwitchAddArgs {
+    #[arg(long, help = "规则名称")]
+    pub name: String,
+    #[arg(long, help = "匹配 Wi-Fi SSID")]
+    pub ssid: Option<String>,
+    #[arg(long, help = "匹配默认网关 IP")]
+    pub gateway_ip: Option<String>,
+    #[arg(long, help = "匹配默认网关 MAC")]
+    pub gateway_mac: Option<String>,
+    #[arg(long, help = "匹配默认路由网卡名")]
+    pub interface: Option<String>,
+    #[arg(long, help = "命中后切换到的 profile")]
+    pub profile: Option<String>,
+    #[arg(long, value_enum, help = "命中后设置的运行模式")]
+    pub mode: Option<ApiModeValue>,
+}
+
+#[derive(Args, Clone)]
+pub struct AutoSwitchRemoveArgs {
+    #[arg(long, help = "规则名称")]
+    pub name: String,
+}
+
+#[derive(Args, Clone)]
+pub struct AutoSwitchApplyArgs {
+    #[arg(long, help = "仅输出匹配结果，不做切换")]
+    pub dry_run: bool,
+    #[arg(
+        long,
+        default_value = DEFAULT_SERVICE_NAME,
+        help = "切换 profile 后联动重启的 systemd 服务名"
+    )]
+    pub service_name: String,
+    #[arg(long, help = "切换 profile 后仅渲染，不自动重启服务")]
+    pub no_restart: bool,
+    #[command(flatten)]
+    pub api: ApiCommonArgs,
+}
+
+#[derive(Args, Clone)]
+pub struct AutoSwitchRunArgs {
+    #[arg(long, default_value_t = 30, help = "网络检测间隔秒数")]
+    pub interval_secs: u64,
+    #[command(flatten)]
+    pub apply: AutoSwitchApplyArgs,
+}
+
+// --- Hooks 命令 ---
+
+#[derive(Subcommand)]
+pub enum HooksCommand {
+    #[command(about = "写入网络变化钩子脚本")]
+    Install(HooksInstallArgs),
+    #[command(about = "删除由 clash-cli 写入的钩子脚本")]
+    Uninstall(HooksTargetArgs),
+    #[command(about = "查看钩子安装状态")]
+    Status,
+    #[command(about = "执行网络变化动作：清理 fake-ip/DNS 缓存并关闭旧连接（由钩子脚本调用）")]
+    Run(HooksRunArgs),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum HookTarget {
+    /// 按已安装的 dispatcher 自动选择
+    Auto,
+    NetworkManager,
+    Networkd,
+}
+
+#[derive(Args, Clone)]
+pub struct HooksTargetArgs {
+    #[arg(long, value_enum, default_value_t = HookTarget::Auto, help = "钩子类型")]
+    pub target: HookTarget,
+}
+
+#[derive(Args, Clone)]
+pub struct HooksInstallArgs {
+    #[command(flatten)]
+    pub target: HooksTargetArgs,
+    #[arg(long, help = "网络变化时同时执行 auto-switch 规则评估")]
+    pub auto_switch: bool,
+    #[arg(long, help = "覆盖已存在的非 clash-cli 脚本")]
+    pub force: bool,
+}
+
+#[derive(Args, Clone)]
+pub struct HooksRunArgs {
+    #[arg(long, help = "触发原因（写入输出，便于日志排查）")]
+    pub reason: Option<String>,
+    #[arg(long, help = "不清理 fake-ip/DNS 缓存")]
+    pub no_flush_dns: bool,
+    #[arg(long, help = "不关闭现有连接")]
+    pub no_close_connections: bool,
+    #[arg(long, help = "先执行 auto-switch 规则评估")]
+    pub auto_switch: bool,
+    #[command(flatten)]
+    pub api: ApiCommonArgs,
+}
+
 // --- Update 命令 ---
 
 #[derive(Subcommand)]
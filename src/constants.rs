@@ -1,4 +1,4 @@
-/// 集中管理的默认常量，避免在各模块中重复定义
+//! 集中管理的默认常量，避免在各模块中重复定义
 
 // --- 端口 ---
 pub const DEFAULT_MIXED_PORT: u16 = 7890;
@@ -16,8 +16,39 @@ pub const DEFAULT_SYSTEM_SERVICE_UNIT: &str = "clash-mihomo.service";
 // --- 代理 ---
 pub const DEFAULT_NO_PROXY: &str = "localhost,127.0.0.1,::1";
 
+// --- 局域网订阅服务 ---
+pub const DEFAULT_SERVE_LISTEN: &str = "0.0.0.0:7899";
+pub const DEFAULT_SERVE_SERVICE_NAME: &str = "clash-cli-serve";
+
+// --- 故障转移 ---
+pub const DEFAULT_PROBE_URL: &str = "https://www.gstatic.com/generate_204";
+/// 保留的故障转移事件条数
+pub const FAILOVER_EVENT_LIMIT: usize = 50;
+
+// --- 网络变化钩子 ---
+pub const NM_DISPATCHER_DIR: &str = "/etc/NetworkManager/dispatcher.d";
+pub const NETWORKD_DISPATCHER_DIR: &str = "/etc/networkd-dispatcher";
+pub const NETWORK_HOOK_FILE_NAME: &str = "90-clash-cli";
+pub const SYSTEM_SLEEP_DIR: &str = "/usr/lib/systemd/system-sleep";
+
 // --- Dashboard / UI ---
 pub const DEFAULT_EXTERNAL_UI: &str = "ui";
 pub const DEFAULT_EXTERNAL_UI_NAME: &str = "metacubexd";
 pub const DEFAULT_EXTERNAL_UI_URL: &str =
     "https://ghfast.top/https://github.com/MetaCubeX/metacubexd/archive/refs/heads/gh-pages.zip";
+pub const UI_METACUBEXD_URL: &str =
+    "https://github.com/MetaCubeX/metacubexd/archive/refs/heads/gh-pages.zip";
+pub const UI_YACD_META_URL: &str =
+    "https://github.com/MetaCubeX/Yacd-meta/archive/refs/heads/gh-pages.zip";
+pub const UI_ZASHBOARD_URL: &str =
+    "https://github.com/Zephyruso/zashboard/releases/latest/download/dist.zip";
+
+// --- Geo 数据库 ---
+pub const DEFAULT_GEOIP_URL: &str =
+    "https://github.com/MetaCubeX/meta-rules-dat/releases/download/latest/geoip.dat";
+pub const DEFAULT_GEOSITE_URL: &str =
+    "https://github.com/MetaCubeX/meta-rules-dat/releases/download/latest/geosite.dat";
+pub const DEFAULT_MMDB_URL: &str =
+    "https://github.com/MetaCubeX/meta-rules-dat/releases/download/latest/country.mmdb";
+pub const DEFAULT_ASN_URL: &str =
+    "https://github.com/xishang0128/geoip/releases/download/latest/GeoLite2-ASN.mmdb";
@@ -0,0 +1,599 @@
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_yaml::{Mapping, Value};
+
+/// 本地 provider 文件目录（相对 mihomo 工作目录）。
+const PROVIDER_DIR: &str = "providers";
+/// 生成的 provider 名称与文件名前缀，重新渲染时据此清理旧文件。
+const INLINE_PREFIX: &str = "clash-cli-inline";
+/// 新 provider 文件先写入该子目录，运行配置写入成功后再替换正式文件。
+const STAGING_DIR: &str = ".clash-cli-staging";
+
+/// 仅作用于 use provider、对内联 proxies 无效的策略组字段。
+const GROUP_FILTER_KEYS: &[&str] = &["filter", "exclude-filter", "exclude-type"];
+
+/// classical rule-provider 可承载的规则类型。
+const CLASSICAL_RULE_TYPES: &[&str] = &[
+    "DOMAIN",
+    "DOMAIN-SUFFIX",
+    "DOMAIN-KEYWORD",
+    "DOMAIN-REGEX",
+    "DOMAIN-WILDCARD",
+    "GEOSITE",
+    "GEOIP",
+    "IP-CIDR",
+    "IP-CIDR6",
+    "IP-SUFFIX",
+    "IP-ASN",
+    "SRC-IP-CIDR",
+    "SRC-PORT",
+    "DST-PORT",
+    "PROCESS-NAME",
+    "PROCESS-PATH",
+];
+
+#[derive(Debug, Default, Serialize)]
+pub(crate) struct ExtractSummary {
+    pub(crate) rule_providers: Vec<String>,
+    pub(crate) rules_extracted: usize,
+    pub(crate) proxy_provider: Option<String>,
+    pub(crate) proxies_extracted: usize,
+    /// 未抽取内联节点的原因
+    pub(crate) proxies_skipped: Option<String>,
+}
+
+/// 内联规则的拆分结果：可放入 classical provider 的部分与目标策略。
+struct InlineRule {
+    payload: String,
+    target: String,
+}
+
+/// 已写入暂存目录、等待替换正式文件的抽取结果。
+pub(crate) struct StagedProviders {
+    dir: PathBuf,
+    staging: PathBuf,
+    summary: ExtractSummary,
+}
+
+/// 将内联 rules 按连续相同目标策略抽取为本地 rule-provider，内联 proxies 抽取为本地
+/// proxy-provider，并改写配置中的引用。文件先写入暂存目录，调用
+/// [`StagedProviders::commit`] 后才替换 `workdir/providers/` 下的旧文件。
+/// 仅合并相邻且目标相同的规则，保持原有匹配顺序不变。
+pub(crate) fn extract_inline_providers(
+    root: &mut Value,
+    workdir: &Path,
+    min_rules: usize,
+) -> Result<StagedProviders> {
+    let dir = workdir.join(PROVIDER_DIR);
+    let staging = dir.join(STAGING_DIR);
+    // 清理上次未提交的暂存文件
+    if staging.exists() {
+        fs::remove_dir_all(&staging)
+            .with_context(|| format!("清理暂存目录失败: {}", staging.display()))?;
+    }
+    fs::create_dir_all(&staging).with_context(|| format!("创建目录失败: {}", staging.display()))?;
+
+    let mut summary = ExtractSummary::default();
+    extract_rules(root, &staging, min_rules.max(1), &mut summary)?;
+    extract_proxies(root, &staging, &mut summary)?;
+    Ok(StagedProviders {
+        dir,
+        staging,
+        summary,
+    })
+}
+
+impl StagedProviders {
+    /// 运行配置写入成功后调用：新文件逐个覆盖旧文件，再删除不再引用的旧文件。
+    pub(crate) fn commit(self) -> Result<ExtractSummary> {
+        let mut written = HashSet::new();
+        let entries = fs::read_dir(&self.staging)
+            .with_context(|| format!("读取目录失败: {}", self.staging.display()))?;
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            let target = self.dir.join(&name);
+            fs::rename(entry.path(), &target)
+                .with_context(|| format!("替换 provider 文件失败: {}", target.display()))?;
+            written.insert(name);
+        }
+        fs::remove_dir_all(&self.staging)
+            .with_context(|| format!("清理暂存目录失败: {}", self.staging.display()))?;
+        remove_stale_files(&self.dir, &written)?;
+        Ok(self.summary)
+    }
+}
+
+fn remove_stale_files(dir: &Path, keep: &HashSet<OsString>) -> Result<()> {
+    let entries = fs::read_dir(dir).with_context(|| format!("读取目录失败: {}", dir.display()))?;
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        if name.to_string_lossy().starts_with(INLINE_PREFIX) && !keep.contains(&name) {
+            let path = entry.path();
+            fs::remove_file(&path)
+                .with_context(|| format!("删除旧 provider 文件失败: {}", path.display()))?;
+        }
+    }
+    Ok(())
+}
+
+fn extract_rules(
+    root: &mut Value,
+    dir: &Path,
+    min_rules: usize,
+    summary: &mut ExtractSummary,
+) -> Result<()> {
+    let Some(rules) = root.get("rules").and_then(Value::as_sequence) else {
+        return Ok(());
+    };
+
+    let mut rewritten: Vec<Value> = Vec::with_capacity(rules.len());
+    let mut providers = Mapping::new();
+    let mut run: Vec<(Value, InlineRule)> = Vec::new();
+    for rule in rules {
+        let parsed = rule.as_str().and_then(parse_inline_rule);
+        let continues = matches!(
+            (&parsed, run.last()),
+            (Some(next), Some((_, last))) if next.target == last.target
+        );
+        if !continues {
+            flush_run(
+                &mut run,
+                &mut rewritten,
+                &mut providers,
+                dir,
+                min_rules,
+                summary,
+            )?;
+        }
+        match parsed {
+            Some(inline) => run.push((rule.clone(), inline)),
+            None => rewritten.push(rule.clone()),
+        }
+    }
+    flush_run(
+        &mut run,
+        &mut rewritten,
+        &mut providers,
+        dir,
+        min_rules,
+        summary,
+    )?;
+
+    if providers.is_empty() {
+        return Ok(());
+    }
+    let map = ensure_mapping(root);
+    map.insert(Value::from("rules"), Value::Sequence(rewritten));
+    merge_providers(map, "rule-providers", providers);
+    Ok(())
+}
+
+fn flush_run(
+    run: &mut Vec<(Value, InlineRule)>,
+    rewritten: &mut Vec<Value>,
+    providers: &mut Mapping,
+    dir: &Path,
+    min_rules: usize,
+    summary: &mut ExtractSummary,
+) -> Result<()> {
+    if run.is_empty() {
+        return Ok(());
+    }
+    let items = std::mem::take(run);
+    if items.len() < min_rules {
+        rewritten.extend(items.into_iter().map(|(raw, _)| raw));
+        return Ok(());
+    }
+
+    let name = format!("{INLINE_PREFIX}-{:03}", summary.rule_providers.len() + 1);
+    let file_name = format!("{name}.yaml");
+    let target = items[0].1.target.clone();
+    let payload: Vec<Value> = items
+        .iter()
+        .map(|(_, inline)| Value::String(inline.payload.clone()))
+        .collect();
+    write_provider_file(&dir.join(&file_name), "payload", Value::Sequence(payload))?;
+
+    let mut provider = Mapping::new();
+    provider.insert(Value::from("type"), Value::from("file"));
+    provider.insert(Value::from("behavior"), Value::from("classical"));
+    provider.insert(Value::from("format"), Value::from("yaml"));
+    provider.insert(
+        Value::from("path"),
+        Value::String(format!("./{PROVIDER_DIR}/{file_name}")),
+    );
+    providers.insert(Value::String(name.clone()), Value::Mapping(provider));
+    rewritten.push(Value::String(format!("RULE-SET,{name},{target}")));
+
+    summary.rules_extracted += items.len();
+    summary.rule_providers.push(name);
+    Ok(())
+}
+
+/// 解析 `TYPE,VALUE,TARGET[,params]`；不可放入 classical provider 的规则返回 None。
+fn parse_inline_rule(rule: &str) -> Option<InlineRule> {
+    let parts: Vec<&str> = rule.split(',').map(str::trim).collect();
+    if parts.len() < 3 || parts[1].is_empty() || parts[2].is_empty() {
+        return None;
+    }
+    let kind = parts[0].to_ascii_uppercase();
+    if !CLASSICAL_RULE_TYPES.contains(&kind.as_str()) {
+        return None;
+    }
+    let mut payload = format!("{kind},{}", parts[1]);
+    for param in &parts[3..] {
+        payload.push(',');
+        payload.push_str(param);
+    }
+    Some(InlineRule {
+        payload,
+        target: parts[2].to_string(),
+    })
+}
+
+fn extract_proxies(root: &mut Value, dir: &Path, summary: &mut ExtractSummary) -> Result<()> {
+    let Some(proxies) = root.get("proxies").and_then(Value::as_sequence) else {
+        return Ok(());
+    };
+    if proxies.is_empty() {
+        return Ok(());
+    }
+    let names: HashSet<String> = proxies
+        .iter()
+        .filter_map(|p| p.get("name").and_then(Value::as_str))
+        .map(str::to_string)
+        .collect();
+    if names.len() != proxies.len() {
+        summary.proxies_skipped = Some("存在缺少名称或重名的节点".to_string());
+        return Ok(());
+    }
+    if let Some(reason) = proxy_reference_blocker(root, &names) {
+        summary.proxies_skipped = Some(reason);
+        return Ok(());
+    }
+
+    let proxies = proxies.clone();
+    let file_name = format!("{INLINE_PREFIX}-proxies.yaml");
+    write_provider_file(&dir.join(&file_name), "proxies", Value::Sequence(proxies))?;
+
+    let provider_name = INLINE_PREFIX.to_string();
+    rewrite_group_references(root, &names, &provider_name);
+
+    let mut provider = Mapping::new();
+    provider.insert(Value::from("type"), Value::from("file"));
+    provider.insert(
+        Value::from("path"),
+        Value::String(format!("./{PROVIDER_DIR}/{file_name}")),
+    );
+    let mut providers = Mapping::new();
+    providers.insert(
+        Value::String(provider_name.clone()),
+        Value::Mapping(provider),
+    );
+
+    let map = ensure_mapping(root);
+    map.remove("proxies");
+    merge_providers(map, "proxy-providers", providers);
+
+    summary.proxies_extracted = names.len();
+    summary.proxy_provider = Some(provider_name);
+    Ok(())
+}
+
+/// 节点被规则、dialer-proxy 或无法改写的策略组直接引用时无法移入 provider。
+fn proxy_reference_blocker(root: &Value, names: &HashSet<String>) -> Option<String> {
+    let rules = root.get("rules").and_then(Value::as_sequence);
+    for rule in rules.into_iter().flatten().filter_map(Value::as_str) {
+        if rule_targets(rule).any(|target| names.contains(target)) {
+            return Some(format!("规则直接引用了节点: {rule}"));
+        }
+    }
+
+    let proxies = root.get("proxies").and_then(Value::as_sequence);
+    for proxy in proxies.into_iter().flatten() {
+        if proxy.get("dialer-proxy").is_some() {
+            return Some("节点使用了 dialer-proxy".to_string());
+        }
+    }
+
+    // 节点在 provider 文件中的顺序，即原 proxies 列表顺序
+    let order: Vec<&str> = proxies
+        .into_iter()
+        .flatten()
+        .filter_map(|p| p.get("name").and_then(Value::as_str))
+        .collect();
+    let groups = root.get("proxy-groups").and_then(Value::as_sequence);
+    for group in groups.into_iter().flatten() {
+        let name = group.get("name").and_then(Value::as_str).unwrap_or("-");
+        if group.get("include-all").is_some() || group.get("include-all-proxies").is_some() {
+            return Some(format!("策略组 {name} 使用了 include-all"));
+        }
+        if group.get("dialer-proxy").is_some() {
+            return Some(format!("策略组 {name} 使用了 dialer-proxy"));
+        }
+        let members: Vec<&str> = group_proxy_names(group).collect();
+        let referenced = members.iter().filter(|p| names.contains(**p)).count();
+        if referenced == 0 {
+            continue;
+        }
+        // filter 类字段只作用于 use 的 provider，移入后会额外筛掉原本内联的节点
+        if GROUP_FILTER_KEYS.iter().any(|k| group.get(*k).is_some()) {
+            return Some(format!(
+                "策略组 {name} 使用了 filter/exclude-filter/exclude-type"
+            ));
+        }
+        if referenced < names.len() && group.get("use").is_some() {
+            return Some(format!("策略组 {name} 同时使用 provider 与部分节点"));
+        }
+        // mihomo 先列 proxies 再列 use，节点须位于列表末尾且与 provider 文件同序才能保持组内顺序
+        let tail = &members[members.len() - referenced..];
+        let positions: Vec<Option<usize>> = tail
+            .iter()
+            .map(|p| order.iter().position(|o| o == p))
+            .collect();
+        let ordered = positions.iter().all(Option::is_some) && positions.is_sorted();
+        if !ordered {
+            return Some(format!(
+                "策略组 {name} 中的节点不在列表末尾或与节点列表顺序不同，抽取会改变组内顺序"
+            ));
+        }
+    }
+    None
+}
+
+/// 规则中可能为策略名的字段：普通规则取第三段，MATCH 取第二段。
+fn rule_targets(rule: &str) -> impl Iterator<Item = &str> {
+    let parts: Vec<&str> = rule.split(',').map(str::trim).collect();
+    let target = if parts
+        .first()
+        .is_some_and(|t| t.eq_ignore_ascii_case("MATCH"))
+    {
+        parts.get(1).copied()
+    } else {
+        parts.get(2).copied()
+    };
+    target.into_iter()
+}
+
+fn group_proxy_names(group: &Value) -> impl Iterator<Item = &str> {
+    group
+        .get("proxies")
+        .and_then(Value::as_sequence)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+}
+
+/// 策略组中的内联节点改为 `use` provider；只引用部分节点时附加精确匹配的 filter。
+fn rewrite_group_references(root: &mut Value, names: &HashSet<String>, provider: &str) {
+    let Some(groups) = root
+        .get_mut("proxy-groups")
+        .and_then(Value::as_sequence_mut)
+    else {
+        return;
+    };
+    for group in groups.iter_mut() {
+        let referenced: Vec<String> = group_proxy_names(group)
+            .filter(|p| names.contains(*p))
+            .map(str::to_string)
+            .collect();
+        if referenced.is_empty() {
+            continue;
+        }
+        let Some(map) = group.as_mapping_mut() else {
+            continue;
+        };
+
+        let remaining: Vec<Value> = map
+            .get("proxies")
+            .and_then(Value::as_sequence)
+            .into_iter()
+            .flatten()
+            .filter(|p| p.as_str().is_none_or(|n| !names.contains(n)))
+            .cloned()
+            .collect();
+        if remaining.is_empty() {
+            map.remove("proxies");
+        } else {
+            map.insert(Value::from("proxies"), Value::Sequence(remaining));
+        }
+
+        let uses = map
+            .entry(Value::from("use"))
+            .or_insert_with(|| Value::Sequence(Vec::new()));
+        // 放在已有 provider 之前，与原先“内联节点在 use 之前”的顺序一致
+        if let Value::Sequence(list) = uses
+            && !list.iter().any(|v| v.as_str() == Some(provider))
+        {
+            list.insert(0, Value::from(provider));
+        }
+        if referenced.len() < names.len() {
+            let pattern = referenced
+                .iter()
+                .map(|n| escape_regex(n))
+                .collect::<Vec<_>>()
+                .join("|");
+            map.insert(
+                Value::from("filter"),
+                Value::String(format!("^(?:{pattern})$")),
+            );
+        }
+    }
+}
+
+fn escape_regex(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if "\\.^$|?*+()[]{}".contains(ch) {
+            out.push('\\');
+        }
+        out.push(ch);
+    }
+    out
+}
+
+fn write_provider_file(path: &Path, key: &str, value: Value) -> Result<()> {
+    let mut doc = Mapping::new();
+    doc.insert(Value::from(key), value);
+    let text = serde_yaml::to_string(&Value::Mapping(doc)).context("序列化 provider 失败")?;
+    fs::write(path, text).with_context(|| format!("写入 provider 文件失败: {}", path.display()))
+}
+
+fn ensure_mapping(root: &mut Value) -> &mut Mapping {
+    if !root.is_mapping() {
+        *root = Value::Mapping(Mapping::new());
+    }
+    root.as_mapping_mut().expect("root 已确保为 mapping")
+}
+
+fn merge_providers(map: &mut Mapping, key: &str, providers: Mapping) {
+    let entry = map
+        .entry(Value::from(key))
+        .or_insert_with(|| Value::Mapping(Mapping::new()));
+    if !entry.is_mapping() {
+        *entry = Value::Mapping(Mapping::new());
+    }
+    if let Value::Mapping(existing) = entry {
+        existing.extend(providers);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(tag: &str) -> std::path::PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("clash-cli-extract-{tag}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("创建临时目录失败");
+        dir
+    }
+
+    #[test]
+    fn extract_should_group_consecutive_rules_and_move_proxies() {
+        let dir = temp_dir("rules");
+        let mut root: Value = serde_yaml::from_str(
+            r#"
+proxies:
+  - {name: a, type: ss, server: 1.1.1.1, port: 1, cipher: aes-128-gcm, password: p}
+  - {name: b, type: ss, server: 2.2.2.2, port: 1, cipher: aes-128-gcm, password: p}
+proxy-groups:
+  - {name: PROXY, type: select, proxies: [DIRECT, a, b]}
+  - {name: HK, type: select, proxies: [b]}
+rules:
+  - DOMAIN-SUFFIX,a.com,PROXY
+  - DOMAIN,b.com,PROXY
+  - IP-CIDR,1.0.0.0/8,PROXY,no-resolve
+  - DOMAIN,c.com,DIRECT
+  - RULE-SET,ads,REJECT
+  - MATCH,PROXY
+"#,
+        )
+        .expect("解析失败");
+
+        let providers = dir.join("providers");
+        fs::create_dir_all(&providers).expect("创建目录失败");
+        let stale = providers.join("clash-cli-inline-009.yaml");
+        fs::write(&stale, "payload: []\n").expect("写入旧文件失败");
+
+        let staged = extract_inline_providers(&mut root, &dir, 2).expect("抽取失败");
+        // 提交前旧文件保持不变，新文件只在暂存目录
+        assert!(stale.exists());
+        assert!(!providers.join("clash-cli-inline-001.yaml").exists());
+        let summary = staged.commit().expect("提交失败");
+        assert!(!stale.exists());
+        assert!(!providers.join(STAGING_DIR).exists());
+        assert_eq!(summary.rule_providers, vec!["clash-cli-inline-001"]);
+        assert_eq!(summary.rules_extracted, 3);
+        assert_eq!(summary.proxies_extracted, 2);
+
+        let rules: Vec<&str> = root["rules"]
+            .as_sequence()
+            .expect("rules")
+            .iter()
+            .filter_map(Value::as_str)
+            .collect();
+        assert_eq!(
+            rules,
+            vec![
+                "RULE-SET,clash-cli-inline-001,PROXY",
+                "DOMAIN,c.com,DIRECT",
+                "RULE-SET,ads,REJECT",
+                "MATCH,PROXY",
+            ]
+        );
+        let payload = fs::read_to_string(dir.join("providers/clash-cli-inline-001.yaml"))
+            .expect("读取 provider 失败");
+        assert!(payload.contains("IP-CIDR,1.0.0.0/8,no-resolve"));
+
+        assert!(root.get("proxies").is_none());
+        let groups = root["proxy-groups"].as_sequence().expect("groups");
+        assert_eq!(groups[0]["proxies"][0].as_str(), Some("DIRECT"));
+        assert_eq!(groups[0]["use"][0].as_str(), Some("clash-cli-inline"));
+        assert!(groups[0].get("filter").is_none());
+        assert!(groups[1].get("proxies").is_none());
+        assert_eq!(groups[1]["filter"].as_str(), Some("^(?:b)$"));
+        assert!(dir.join("providers/clash-cli-inline-proxies.yaml").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn extract_should_keep_proxies_when_rules_target_nodes_directly() {
+        let dir = temp_dir("skip");
+        let mut root: Value = serde_yaml::from_str(
+            r#"
+proxies:
+  - {name: a, type: ss, server: 1.1.1.1, port: 1, cipher: aes-128-gcm, password: p}
+rules:
+  - DOMAIN,a.com,a
+"#,
+        )
+        .expect("解析失败");
+
+        let summary = extract_inline_providers(&mut root, &dir, 10)
+            .and_then(StagedProviders::commit)
+            .expect("抽取失败");
+        assert!(summary.proxies_skipped.is_some());
+        assert!(summary.rule_providers.is_empty());
+        assert_eq!(root["proxies"].as_sequence().map(|v| v.len()), Some(1));
+        assert_eq!(root["rules"][0].as_str(), Some("DOMAIN,a.com,a"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn extract_should_keep_proxies_when_rewrite_changes_group_order_or_filter() {
+        let dir = temp_dir("order");
+        let base = r#"
+proxies:
+  - {name: HK-a, type: ss, server: 1.1.1.1, port: 1, cipher: aes-128-gcm, password: p}
+  - {name: JP-b, type: ss, server: 2.2.2.2, port: 1, cipher: aes-128-gcm, password: p}
+proxy-groups:
+"#;
+        let cases = [
+            "  - {name: FB, type: fallback, proxies: [HK-a, JP-b, DIRECT]}\n",
+            "  - {name: SEL, type: select, proxies: [JP-b, HK-a]}\n",
+            "  - {name: HK, type: select, filter: HK, proxies: [HK-a, JP-b]}\n",
+            "  - {name: EX, type: url-test, exclude-type: vmess, proxies: [HK-a]}\n",
+        ];
+        for group in cases {
+            let mut root: Value =
+                serde_yaml::from_str(&format!("{base}{group}")).expect("解析失败");
+            let summary = extract_inline_providers(&mut root, &dir, 10)
+                .and_then(StagedProviders::commit)
+                .expect("抽取失败");
+            assert!(summary.proxies_skipped.is_some(), "应跳过: {group}");
+            assert_eq!(root["proxies"].as_sequence().map(|v| v.len()), Some(2));
+            assert!(root.get("proxy-providers").is_none());
+        }
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
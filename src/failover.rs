@@ -0,0 +1,523 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+
+use crate::api::{
+    ProxyInfo, api_get, api_get_with_status, api_path, build_client, load_api_context,
+    parse_proxies,
+};
+use crate::cli::{FailoverCheckArgs, FailoverCommand};
+use crate::constants;
+use crate::output::{is_json_mode, print_json};
+use crate::paths::{AppPaths, app_paths};
+use crate::profile::{
+    ProfileEntry, ProfileIndex, ensure_service_runtime_home_matches_current, fetch_profile_entry,
+    load_index, load_profile_yaml, render_active_to_runtime, restart_system_service, save_index,
+};
+use crate::utils;
+
+/// 故障转移状态：各 profile 连续拉取失败次数与最近的切换事件。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct FailoverState {
+    #[serde(default)]
+    failures: BTreeMap<String, u32>,
+    #[serde(default)]
+    events: Vec<FailoverEvent>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FailoverEvent {
+    at: u64,
+    from: String,
+    /// 切换目标；没有健康备用时为 None
+    to: Option<String>,
+    reason: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    skipped: Vec<String>,
+}
+
+/// 通过 controller 对全部节点测速的结果。
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ProbeOutcome {
+    Skipped,
+    Healthy {
+        alive: usize,
+    },
+    AllTimeout,
+    /// controller 不可达或返回无法判断的结果，不作为切换依据
+    Inconclusive(String),
+}
+
+impl ProbeOutcome {
+    fn to_json(&self) -> serde_json::Value {
+        match self {
+            ProbeOutcome::Skipped => serde_json::json!({"status": "skipped"}),
+            ProbeOutcome::Healthy { alive } => {
+                serde_json::json!({"status": "healthy", "alive": alive})
+            }
+            ProbeOutcome::AllTimeout => serde_json::json!({"status": "all_timeout"}),
+            ProbeOutcome::Inconclusive(detail) => {
+                serde_json::json!({"status": "inconclusive", "detail": detail})
+            }
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            ProbeOutcome::Skipped => "已跳过".to_string(),
+            ProbeOutcome::Healthy { alive } => format!("{alive} 个节点可用"),
+            ProbeOutcome::AllTimeout => "全部节点超时".to_string(),
+            ProbeOutcome::Inconclusive(detail) => format!("无法判断（{detail}）"),
+        }
+    }
+}
+
+pub fn run(command: FailoverCommand) -> Result<()> {
+    match command {
+        FailoverCommand::Check(args) => cmd_check(args),
+        FailoverCommand::Status => cmd_status(),
+    }
+}
+
+fn cmd_check(args: FailoverCheckArgs) -> Result<()> {
+    let paths = app_paths()?;
+    let mut index = load_index(&paths.profile_index_file)?;
+    let mut state = load_state(&paths.failover_state_file)?;
+    let active = index
+        .active
+        .clone()
+        .context("当前没有 active profile，无法执行故障转移检查")?;
+
+    let (fetch_ok, fetch_error) = if args.dry_run {
+        (None, None)
+    } else {
+        let entry = index
+            .profiles
+            .iter_mut()
+            .find(|p| p.name == active)
+            .with_context(|| format!("active profile 不存在: {active}"))?;
+        match fetch_profile_entry(entry, &paths, true) {
+            Ok(()) => {
+                state.failures.remove(&active);
+                (Some(true), None)
+            }
+            Err(err) => {
+                *state.failures.entry(active.clone()).or_insert(0) += 1;
+                (Some(false), Some(format!("{err:#}")))
+            }
+        }
+    };
+    let failures = state.failures.get(&active).copied().unwrap_or(0);
+
+    let probe = if args.no_probe {
+        ProbeOutcome::Skipped
+    } else {
+        probe_active(&args)
+    };
+    let reason = failover_reason(failures, args.max_failures, &probe);
+
+    let mut switched_to = None;
+    let mut skipped = Vec::new();
+    if let Some(reason) = &reason
+        && !args.dry_run
+    {
+        if !args.no_restart {
+            ensure_service_runtime_home_matches_current(
+                &args.service_name,
+                &paths.runtime_config_file,
+            )?;
+        }
+        let candidates: Vec<String> = failover_candidates(&index, &active)
+            .into_iter()
+            .map(|p| p.name.clone())
+            .collect();
+        for name in candidates {
+            match check_candidate(&paths, &mut index, &name) {
+                Ok(()) => {
+                    state.failures.remove(&name);
+                    switched_to = Some(name);
+                    break;
+                }
+                Err(err) => {
+                    *state.failures.entry(name.clone()).or_insert(0) += 1;
+                    skipped.push(format!("{name}: {err:#}"));
+                }
+            }
+        }
+
+        if let Some(next) = &switched_to {
+            index.active = Some(next.clone());
+        }
+        save_index(&paths.profile_index_file, &index)?;
+        record_event(
+            &mut state,
+            FailoverEvent {
+                at: utils::now_unix(),
+                from: active.clone(),
+                to: switched_to.clone(),
+                reason: reason.clone(),
+                skipped: skipped.clone(),
+            },
+        );
+        save_state(&paths.failover_state_file, &state)?;
+
+        if switched_to.is_some() {
+            render_active_to_runtime(&paths)?;
+            if !args.no_restart {
+                restart_system_service(&args.service_name)?;
+            }
+        }
+    } else if !args.dry_run {
+        save_index(&paths.profile_index_file, &index)?;
+        save_state(&paths.failover_state_file, &state)?;
+    }
+
+    if reason.is_some() && !args.dry_run && switched_to.is_none() {
+        bail!(
+            "profile {} 需要故障转移，但没有健康的备用 profile（已尝试 {} 个，可用 `profile set --priority` 设置备用顺序）",
+            active,
+            skipped.len()
+        );
+    }
+
+    if is_json_mode() {
+        return print_json(&serde_json::json!({
+            "ok": true,
+            "action": "profile.failover.check",
+            "active": index.active,
+            "checked": active,
+            "fetch_ok": fetch_ok,
+            "fetch_error": fetch_error,
+            "failures": failures,
+            "max_failures": args.max_failures,
+            "probe": probe.to_json(),
+            "triggered": reason.is_some(),
+            "reason": reason,
+            "switched_to": switched_to,
+            "skipped": skipped,
+            "dry_run": args.dry_run,
+            "restarted": switched_to.is_some() && !args.no_restart,
+        }));
+    }
+
+    println!("检查 profile: {active}");
+    match (fetch_ok, &fetch_error) {
+        (Some(true), _) => println!("订阅拉取: 成功"),
+        (Some(false), Some(err)) => println!(
+            "订阅拉取: 失败（连续 {}/{} 次）: {}",
+            failures, args.max_failures, err
+        ),
+        _ => println!("订阅拉取: 已跳过（dry-run），连续失败 {failures} 次"),
+    }
+    println!("节点测速: {}", probe.describe());
+    for item in &skipped {
+        println!("跳过备用: {item}");
+    }
+    match (&reason, &switched_to) {
+        (None, _) => println!("状态健康，无需切换。"),
+        (Some(reason), None) => println!("需要切换（{reason}），dry-run 未做修改。"),
+        (Some(reason), Some(next)) => {
+            println!("已切换 active profile: {active} -> {next}（{reason}）");
+            if args.no_restart {
+                println!("已渲染到运行配置，已跳过服务重启（--no-restart）。");
+            } else {
+                println!(
+                    "已渲染并重启服务: {}",
+                    utils::normalize_unit_name(&args.service_name)
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn cmd_status() -> Result<()> {
+    let paths = app_paths()?;
+    let index = load_index(&paths.profile_index_file)?;
+    let state = load_state(&paths.failover_state_file)?;
+    let active = index.active.clone().unwrap_or_default();
+    let order: Vec<serde_json::Value> = failover_candidates(&index, &active)
+        .into_iter()
+        .map(|p| {
+            serde_json::json!({
+                "name": p.name,
+                "priority": p.priority,
+                "failures": state.failures.get(&p.name).copied().unwrap_or(0),
+            })
+        })
+        .collect();
+
+    if is_json_mode() {
+        return print_json(&serde_json::json!({
+            "ok": true,
+            "action": "profile.failover.status",
+            "active": index.active,
+            "active_failures": state.failures.get(&active).copied().unwrap_or(0),
+            "backups": order,
+            "events": state.events,
+        }));
+    }
+
+    println!(
+        "active: {} (连续失败 {} 次)",
+        index.active.as_deref().unwrap_or("-"),
+        state.failures.get(&active).copied().unwrap_or(0)
+    );
+    if order.is_empty() {
+        println!("备用顺序: 无（使用 `clash profile set --name <名称> --priority <N>` 设置）");
+    } else {
+        println!("备用顺序:");
+        for item in &order {
+            println!(
+                "  {} priority={} failures={}",
+                item["name"].as_str().unwrap_or("-"),
+                item["priority"],
+                item["failures"]
+            );
+        }
+    }
+    if !state.events.is_empty() {
+        println!("最近事件:");
+        for event in state.events.iter().rev().take(10) {
+            println!(
+                "  [{}] {} -> {} ({})",
+                event.at,
+                event.from,
+                event.to.as_deref().unwrap_or("无可用备用"),
+                event.reason
+            );
+        }
+    }
+    Ok(())
+}
+
+/// 满足任一条件即需切换：连续拉取失败达到阈值，或 controller 报告全部节点超时。
+fn failover_reason(failures: u32, max_failures: u32, probe: &ProbeOutcome) -> Option<String> {
+    if max_failures > 0 && failures >= max_failures {
+        return Some(format!("订阅连续拉取失败 {failures} 次"));
+    }
+    if *probe == ProbeOutcome::AllTimeout {
+        return Some("全部节点延迟测试超时".to_string());
+    }
+    None
+}
+
+/// 按 priority 升序（相同则按索引顺序）列出除 active 外设置了优先级的 profile。
+fn failover_candidates<'a>(index: &'a ProfileIndex, active: &str) -> Vec<&'a ProfileEntry> {
+    let mut list: Vec<&ProfileEntry> = index
+        .profiles
+        .iter()
+        .filter(|p| p.priority.is_some() && p.name != active)
+        .collect();
+    list.sort_by_key(|p| p.priority);
+    list
+}
+
+/// 备用 profile 需能成功拉取，且包含节点或 proxy-providers。
+fn check_candidate(paths: &AppPaths, index: &mut ProfileIndex, name: &str) -> Result<()> {
+    let entry = index
+        .profiles
+        .iter_mut()
+        .find(|p| p.name == name)
+        .with_context(|| format!("profile 不存在: {name}"))?;
+    fetch_profile_entry(entry, paths, true)?;
+    let root = load_profile_yaml(paths, &paths.profile_dir.join(&entry.file))?;
+    if !has_nodes(&root) {
+        bail!("订阅中没有节点");
+    }
+    Ok(())
+}
+
+fn has_nodes(root: &Value) -> bool {
+    let proxies = root
+        .get("proxies")
+        .and_then(|v| v.as_sequence())
+        .is_some_and(|v| !v.is_empty());
+    let providers = root
+        .get("proxy-providers")
+        .and_then(|v| v.as_mapping())
+        .is_some_and(|v| !v.is_empty());
+    proxies || providers
+}
+
+/// 对 GLOBAL 组测速；mihomo 在全部节点超时时返回 504 或空结果。
+/// GLOBAL 还包含 DIRECT 与各策略组，它们几乎总有延迟，只统计真实出站节点。
+fn probe_active(args: &FailoverCheckArgs) -> ProbeOutcome {
+    let ctx = match load_api_context(&args.api) {
+        Ok(v) => v,
+        Err(err) => return ProbeOutcome::Inconclusive(format!("{err:#}")),
+    };
+    let timeout_secs = args
+        .api
+        .timeout_secs
+        .max(args.probe_timeout_ms.div_ceil(1000) + 5);
+    let client = match build_client(timeout_secs) {
+        Ok(v) => v,
+        Err(err) => return ProbeOutcome::Inconclusive(format!("{err:#}")),
+    };
+    let timeout = args.probe_timeout_ms.to_string();
+    let path = api_path(
+        &["group", "GLOBAL", "delay"],
+        &[("url", &args.probe_url), ("timeout", &timeout)],
+    );
+
+    let proxies = match api_get(&client, &ctx, "/proxies") {
+        Ok(v) => parse_proxies(&v),
+        Err(err) => return ProbeOutcome::Inconclusive(format!("controller 不可达: {err}")),
+    };
+    match api_get_with_status(&client, &ctx, &path) {
+        Ok((status, body)) => probe_outcome_from_response(status, &body, &proxies),
+        Err(err) => ProbeOutcome::Inconclusive(format!("controller 不可达: {err}")),
+    }
+}
+
+fn probe_outcome_from_response(
+    status: u16,
+    body: &serde_json::Value,
+    proxies: &BTreeMap<String, ProxyInfo>,
+) -> ProbeOutcome {
+    match status {
+        200 => {
+            let alive = body
+                .as_object()
+                .map(|m| {
+                    m.iter()
+                        .filter(|(name, _)| {
+                            proxies.get(*name).is_some_and(ProxyInfo::is_outbound_node)
+                        })
+                        .filter(|(_, v)| v.as_u64().is_some_and(|d| d > 0))
+                        .count()
+                })
+                .unwrap_or(0);
+            if alive == 0 {
+                ProbeOutcome::AllTimeout
+            } else {
+                ProbeOutcome::Healthy { alive }
+            }
+        }
+        503 | 504 => ProbeOutcome::AllTimeout,
+        other => ProbeOutcome::Inconclusive(format!("HTTP {other}")),
+    }
+}
+
+fn record_event(state: &mut FailoverState, event: FailoverEvent) {
+    state.events.push(event);
+    let overflow = state
+        .events
+        .len()
+        .saturating_sub(constants::FAILOVER_EVENT_LIMIT);
+    state.events.drain(..overflow);
+}
+
+/// profile 重命名后把连续失败计数迁移到新名称下。
+pub(crate) fn rename_profile_state(paths: &AppPaths, from: &str, to: &str) -> Result<()> {
+    let mut state = load_state(&paths.failover_state_file)?;
+    let Some(count) = state.failures.remove(from) else {
+        return Ok(());
+    };
+    state.failures.insert(to.to_string(), count);
+    save_state(&paths.failover_state_file, &state)
+}
+
+fn load_state(path: &Path) -> Result<FailoverState> {
+    if !path.exists() {
+        return Ok(FailoverState::default());
+    }
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("读取故障转移状态失败: {}", path.display()))?;
+    serde_json::from_str(&content)
+        .with_context(|| format!("解析故障转移状态失败: {}", path.display()))
+}
+
+fn save_state(path: &Path, state: &FailoverState) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("创建目录失败: {}", parent.display()))?;
+    }
+    let content = serde_json::to_string_pretty(state).context("序列化故障转移状态失败")?;
+    fs::write(path, content).with_context(|| format!("写入故障转移状态失败: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, priority: Option<u32>) -> ProfileEntry {
+        ProfileEntry {
+            name: name.to_string(),
+            url: format!("https://example.com/{name}"),
+            file: format!("{name}.yaml"),
+            created_at: 0,
+            updated_at: None,
+            subscription_userinfo: None,
+            note: None,
+            tags: Vec::new(),
+            priority,
+            extract_min_rules: None,
+        }
+    }
+
+    #[test]
+    fn failover_candidates_should_order_by_priority_and_skip_active() {
+        let index = ProfileIndex {
+            active: Some("main".to_string()),
+            profiles: vec![
+                entry("main", Some(0)),
+                entry("c", Some(20)),
+                entry("none", None),
+                entry("b", Some(10)),
+                entry("d", Some(20)),
+            ],
+        };
+        let names: Vec<&str> = failover_candidates(&index, "main")
+            .into_iter()
+            .map(|p| p.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["b", "c", "d"]);
+    }
+
+    #[test]
+    fn failover_reason_should_trigger_on_threshold_or_all_timeout() {
+        let healthy = ProbeOutcome::Healthy { alive: 3 };
+        assert!(failover_reason(2, 3, &healthy).is_none());
+        assert!(failover_reason(3, 3, &healthy).is_some());
+        assert!(failover_reason(0, 3, &ProbeOutcome::AllTimeout).is_some());
+        assert!(failover_reason(0, 3, &ProbeOutcome::Inconclusive("x".into())).is_none());
+
+        let proxies = parse_proxies(&serde_json::json!({"proxies": {
+            "a": {"type": "Shadowsocks"},
+            "b": {"type": "Trojan"},
+            "DIRECT": {"type": "Direct"},
+            "PROXY": {"type": "Selector", "now": "a", "all": ["a", "b"]}
+        }}));
+        assert_eq!(
+            probe_outcome_from_response(200, &serde_json::json!({"a": 0, "b": 120}), &proxies),
+            ProbeOutcome::Healthy { alive: 1 }
+        );
+        assert_eq!(
+            probe_outcome_from_response(200, &serde_json::json!({}), &proxies),
+            ProbeOutcome::AllTimeout
+        );
+        // DIRECT 与策略组有延迟不代表节点可用
+        assert_eq!(
+            probe_outcome_from_response(200, &serde_json::json!({"DIRECT": 1}), &proxies),
+            ProbeOutcome::AllTimeout
+        );
+        assert_eq!(
+            probe_outcome_from_response(
+                200,
+                &serde_json::json!({"DIRECT": 1, "PROXY": 80, "a": 0}),
+                &proxies
+            ),
+            ProbeOutcome::AllTimeout
+        );
+        assert_eq!(
+            probe_outcome_from_response(504, &serde_json::Value::Null, &proxies),
+            ProbeOutcome::AllTimeout
+        );
+    }
+}
@@ -0,0 +1,486 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use serde_yaml::Value;
+
+use crate::api;
+use crate::cli::{GeoCommand, GeoKindValue, GeoTargetArgs, GeoUpdateArgs};
+use crate::constants;
+use crate::http::{
+    build_http_client, download_candidates, download_with_fallback, ensure_non_empty,
+};
+use crate::output::{is_json_mode, print_json};
+use crate::paths::{AppPaths, app_paths};
+use crate::profile::{load_yaml, resolve_core_workdir};
+use crate::utils;
+
+/// mihomo 在工作目录中使用的 geo 数据库文件。
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum GeoKind {
+    GeoIp,
+    GeoSite,
+    Mmdb,
+    Asn,
+}
+
+impl GeoKind {
+    pub(crate) const ALL: [GeoKind; 4] = [
+        GeoKind::GeoIp,
+        GeoKind::GeoSite,
+        GeoKind::Mmdb,
+        GeoKind::Asn,
+    ];
+
+    pub(crate) fn file_name(self) -> &'static str {
+        match self {
+            GeoKind::GeoIp => "geoip.dat",
+            GeoKind::GeoSite => "geosite.dat",
+            GeoKind::Mmdb => "country.mmdb",
+            GeoKind::Asn => "GeoLite2-ASN.mmdb",
+        }
+    }
+
+    /// 对应 `geox-url` 下的键名。
+    pub(crate) fn geox_key(self) -> &'static str {
+        match self {
+            GeoKind::GeoIp => "geoip",
+            GeoKind::GeoSite => "geosite",
+            GeoKind::Mmdb => "mmdb",
+            GeoKind::Asn => "asn",
+        }
+    }
+
+    pub(crate) fn default_url(self) -> &'static str {
+        match self {
+            GeoKind::GeoIp => constants::DEFAULT_GEOIP_URL,
+            GeoKind::GeoSite => constants::DEFAULT_GEOSITE_URL,
+            GeoKind::Mmdb => constants::DEFAULT_MMDB_URL,
+            GeoKind::Asn => constants::DEFAULT_ASN_URL,
+        }
+    }
+}
+
+impl From<GeoKindValue> for GeoKind {
+    fn from(value: GeoKindValue) -> Self {
+        match value {
+            GeoKindValue::Geoip => GeoKind::GeoIp,
+            GeoKindValue::Geosite => GeoKind::GeoSite,
+            GeoKindValue::Mmdb => GeoKind::Mmdb,
+            GeoKindValue::Asn => GeoKind::Asn,
+        }
+    }
+}
+
+pub fn run(command: GeoCommand) -> Result<()> {
+    match command {
+        GeoCommand::Status(args) => cmd_status(args),
+        GeoCommand::Update(args) => cmd_update(args),
+    }
+}
+
+fn cmd_status(args: GeoTargetArgs) -> Result<()> {
+    let paths = app_paths()?;
+    let workdir = resolve_workdir(&paths, &args);
+    let root = load_runtime_root(&paths)?;
+    let referenced = referenced_geo_kinds(&root);
+
+    let files = GeoKind::ALL
+        .iter()
+        .map(|kind| {
+            let path = workdir.join(kind.file_name());
+            let size = fs::metadata(&path).ok().map(|m| m.len());
+            serde_json::json!({
+                "kind": kind.geox_key(),
+                "file": kind.file_name(),
+                "path": path.display().to_string(),
+                "exists": size.is_some(),
+                "size": size,
+                "age_secs": utils::file_age_secs(&path),
+                "referenced": referenced.contains(kind),
+                "url": geo_url(&root, *kind),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if is_json_mode() {
+        return print_json(&serde_json::json!({
+            "ok": true,
+            "action": "geo.status",
+            "workdir": workdir.display().to_string(),
+            "files": files,
+        }));
+    }
+
+    println!("工作目录: {}", workdir.display());
+    for kind in GeoKind::ALL {
+        let path = workdir.join(kind.file_name());
+        let state = match fs::metadata(&path) {
+            Ok(meta) => format!(
+                "{}, 更新于 {}",
+                utils::format_bytes(meta.len()),
+                utils::file_age_secs(&path)
+                    .map(utils::format_age)
+                    .unwrap_or_else(|| "未知".to_string())
+            ),
+            Err(_) => "不存在".to_string(),
+        };
+        println!(
+            "{:<18} {}{}",
+            kind.file_name(),
+            state,
+            if referenced.contains(&kind) {
+                "（配置引用）"
+            } else {
+                ""
+            }
+        );
+    }
+    Ok(())
+}
+
+fn cmd_update(args: GeoUpdateArgs) -> Result<()> {
+    let paths = app_paths()?;
+    let workdir = resolve_workdir(&paths, &args.target);
+    let root = load_runtime_root(&paths)?;
+    let overrides = parse_url_overrides(&args.urls)?;
+    let kinds = select_update_kinds(&args, &root, &workdir);
+
+    let client = build_http_client()?;
+    let mut results = Vec::new();
+    let mut failed = 0usize;
+    for kind in &kinds {
+        let url = overrides
+            .iter()
+            .find(|(k, _)| k == kind)
+            .map(|(_, url)| url.clone())
+            .unwrap_or_else(|| geo_url(&root, *kind));
+        let path = workdir.join(kind.file_name());
+        let candidates = download_candidates(&url, args.mirror);
+        let outcome = download_with_fallback(&client, &candidates, &path, |temp| {
+            validate_geo_file(*kind, temp)
+        })
+        .map(|source| (source, fs::metadata(&path).map(|m| m.len()).unwrap_or(0)));
+        match outcome {
+            Ok((source, size)) => {
+                if !is_json_mode() {
+                    println!(
+                        "已更新 {} ({}) <- {}",
+                        kind.file_name(),
+                        utils::format_bytes(size),
+                        source
+                    );
+                }
+                results.push(serde_json::json!({
+                    "kind": kind.geox_key(),
+                    "path": path.display().to_string(),
+                    "ok": true,
+                    "source": source,
+                    "size": size,
+                }));
+            }
+            Err(err) => {
+                failed += 1;
+                if !is_json_mode() {
+                    eprintln!("更新 {} 失败: {}", kind.file_name(), err);
+                }
+                results.push(serde_json::json!({
+                    "kind": kind.geox_key(),
+                    "path": path.display().to_string(),
+                    "ok": false,
+                    "error": err.to_string(),
+                }));
+            }
+        }
+    }
+
+    let mut reload_error = None;
+    let reloaded = if args.reload && failed < kinds.len() {
+        match reload_geo(&args) {
+            Ok(()) => true,
+            Err(err) => {
+                reload_error = Some(err.to_string());
+                false
+            }
+        }
+    } else {
+        false
+    };
+
+    if is_json_mode() {
+        return print_json(&serde_json::json!({
+            "ok": failed == 0 && reload_error.is_none(),
+            "action": "geo.update",
+            "workdir": workdir.display().to_string(),
+            "results": results,
+            "reload_requested": args.reload,
+            "reloaded": reloaded,
+            "reload_error": reload_error,
+        }));
+    }
+
+    if reloaded {
+        println!("已通知内核重载 geo 数据库。");
+    }
+    if let Some(err) = reload_error {
+        bail!("geo 数据库已更新，但控制器重载失败: {err}");
+    }
+    if failed > 0 {
+        bail!("{} 个 geo 数据库更新失败", failed);
+    }
+    Ok(())
+}
+
+/// 替换前校验下载结果：非空，mmdb 还需包含 MaxMind 元数据标记（截断的文件会缺失该尾部标记）。
+fn validate_geo_file(kind: GeoKind, path: &Path) -> Result<()> {
+    ensure_non_empty(path)?;
+    if matches!(kind, GeoKind::Mmdb | GeoKind::Asn) {
+        let data = fs::read(path).with_context(|| format!("读取文件失败: {}", path.display()))?;
+        if !has_mmdb_metadata(&data) {
+            bail!("不是有效的 mmdb 文件（缺少元数据标记，可能下载不完整）");
+        }
+    }
+    Ok(())
+}
+
+/// MaxMind DB 的元数据段以该标记开头，位于文件末尾 128KiB 内。
+fn has_mmdb_metadata(data: &[u8]) -> bool {
+    const MARKER: &[u8] = b"\xAB\xCD\xEFMaxMind.com";
+    let tail = &data[data.len().saturating_sub(128 * 1024)..];
+    tail.windows(MARKER.len()).any(|w| w == MARKER)
+}
+
+fn resolve_workdir(paths: &AppPaths, args: &GeoTargetArgs) -> PathBuf {
+    args.workdir
+        .clone()
+        .unwrap_or_else(|| resolve_core_workdir(paths, &args.service_name))
+}
+
+fn load_runtime_root(paths: &AppPaths) -> Result<Value> {
+    if paths.runtime_config_file.exists() {
+        load_yaml(&paths.runtime_config_file)
+    } else {
+        Ok(Value::Mapping(serde_yaml::Mapping::new()))
+    }
+}
+
+/// 未显式指定 `--kind` 时：更新已存在或被配置引用的文件，都没有则全部下载。
+fn select_update_kinds(args: &GeoUpdateArgs, root: &Value, workdir: &Path) -> Vec<GeoKind> {
+    if !args.kinds.is_empty() {
+        return GeoKind::ALL
+            .into_iter()
+            .filter(|k| args.kinds.iter().any(|v| GeoKind::from(*v) == *k))
+            .collect();
+    }
+    let referenced = referenced_geo_kinds(root);
+    let selected = GeoKind::ALL
+        .into_iter()
+        .filter(|k| referenced.contains(k) || workdir.join(k.file_name()).exists())
+        .collect::<Vec<_>>();
+    if selected.is_empty() {
+        GeoKind::ALL.to_vec()
+    } else {
+        selected
+    }
+}
+
+fn parse_url_overrides(values: &[String]) -> Result<Vec<(GeoKind, String)>> {
+    values
+        .iter()
+        .map(|raw| {
+            let (key, url) = raw
+                .split_once('=')
+                .with_context(|| format!("无效的 --url 参数（应为 KIND=URL）: {raw}"))?;
+            let kind = GeoKind::ALL
+                .into_iter()
+                .find(|k| k.geox_key().eq_ignore_ascii_case(key.trim()))
+                .with_context(|| {
+                    format!("未知的 geo 类型: {key}（可选 geoip/geosite/mmdb/asn）")
+                })?;
+            Ok((kind, url.trim().to_string()))
+        })
+        .collect()
+}
+
+fn reload_geo(args: &GeoUpdateArgs) -> Result<()> {
+    let client = api::build_client(args.api.timeout_secs)?;
+    let ctx = api::load_api_context(&args.api)?;
+    api::api_post(&client, &ctx, "/configs/geo", None).map(|_| ())
+}
+
+/// 下载地址优先取配置中的 `geox-url`，否则回退 mihomo 默认源。
+pub(crate) fn geo_url(root: &Value, kind: GeoKind) -> String {
+    root.get("geox-url")
+        .and_then(|v| v.get(kind.geox_key()))
+        .and_then(|v| v.as_str())
+        .filter(|v| !v.trim().is_empty())
+        .map(|v| v.to_string())
+        .unwrap_or_else(|| kind.default_url().to_string())
+}
+
+/// 推断配置实际会用到的 geo 文件：显式 geox-url、rules 中的 GEOIP/GEOSITE/IP-ASN、
+/// 以及 DNS nameserver-policy 中的 geosite 条目。
+pub(crate) fn referenced_geo_kinds(root: &Value) -> Vec<GeoKind> {
+    let geodata_mode = root
+        .get("geodata-mode")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let mut used = GeoKind::ALL
+        .iter()
+        .map(|kind| {
+            root.get("geox-url")
+                .and_then(|v| v.get(kind.geox_key()))
+                .is_some()
+        })
+        .collect::<Vec<_>>();
+    let mut mark = |kind: GeoKind| {
+        if let Some(pos) = GeoKind::ALL.iter().position(|k| *k == kind) {
+            used[pos] = true;
+        }
+    };
+
+    let rules = root
+        .get("rules")
+        .and_then(|v| v.as_sequence())
+        .map(|seq| seq.iter().filter_map(|v| v.as_str()).collect::<Vec<_>>())
+        .unwrap_or_default();
+    for rule in rules {
+        let upper = rule.to_ascii_uppercase();
+        if upper.contains("GEOSITE,") {
+            mark(GeoKind::GeoSite);
+        }
+        if upper.contains("GEOIP,") {
+            mark(if geodata_mode {
+                GeoKind::GeoIp
+            } else {
+                GeoKind::Mmdb
+            });
+        }
+        if upper.contains("IP-ASN,") {
+            mark(GeoKind::Asn);
+        }
+    }
+
+    if let Some(policy) = root
+        .get("dns")
+        .and_then(|v| v.get("nameserver-policy"))
+        .and_then(|v| v.as_mapping())
+        && policy.keys().filter_map(|k| k.as_str()).any(|k| {
+            k.to_ascii_lowercase()
+                .split(',')
+                .any(|p| p.trim().starts_with("geosite:"))
+        })
+    {
+        mark(GeoKind::GeoSite);
+    }
+
+    GeoKind::ALL
+        .iter()
+        .zip(used)
+        .filter(|(_, used)| *used)
+        .map(|(kind, _)| *kind)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_yaml(input: &str) -> Value {
+        serde_yaml::from_str(input).expect("解析测试 YAML 失败")
+    }
+
+    #[test]
+    fn referenced_geo_kinds_should_follow_rules_and_geodata_mode() {
+        let root = parse_yaml(
+            r#"
+rules:
+  - GEOSITE,cn,DIRECT
+  - GEOIP,CN,DIRECT
+  - MATCH,PROXY
+"#,
+        );
+        assert_eq!(
+            referenced_geo_kinds(&root),
+            vec![GeoKind::GeoSite, GeoKind::Mmdb]
+        );
+
+        let root = parse_yaml(
+            r#"
+geodata-mode: true
+geox-url:
+  asn: https://example.com/asn.mmdb
+rules:
+  - AND,((GEOIP,CN),(NETWORK,UDP)),DIRECT
+"#,
+        );
+        assert_eq!(
+            referenced_geo_kinds(&root),
+            vec![GeoKind::GeoIp, GeoKind::Asn]
+        );
+    }
+
+    #[test]
+    fn parse_url_overrides_should_map_kind_keys() {
+        let parsed = parse_url_overrides(&["GeoIP=https://a/geoip.dat".to_string()])
+            .expect("解析 --url 失败");
+        assert_eq!(
+            parsed,
+            vec![(GeoKind::GeoIp, "https://a/geoip.dat".to_string())]
+        );
+        assert!(parse_url_overrides(&["bad".to_string()]).is_err());
+        assert!(parse_url_overrides(&["city=https://x".to_string()]).is_err());
+    }
+
+    #[test]
+    fn geo_url_should_prefer_geox_url() {
+        let root = parse_yaml("geox-url:\n  geosite: https://mirror.example/geosite.dat\n");
+        assert_eq!(
+            geo_url(&root, GeoKind::GeoSite),
+            "https://mirror.example/geosite.dat"
+        );
+        assert_eq!(geo_url(&root, GeoKind::Mmdb), constants::DEFAULT_MMDB_URL);
+    }
+
+    #[test]
+    fn failed_validation_should_keep_existing_database() {
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+
+        let dir = std::env::temp_dir().join(format!("clash-geo-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("创建测试目录失败");
+        let path = dir.join("country.mmdb");
+        fs::write(&path, b"old database").expect("写入旧数据库失败");
+
+        // 一次性 HTTP 服务：返回 200 但内容为空
+        let listener = TcpListener::bind("127.0.0.1:0").expect("监听端口失败");
+        let url = format!(
+            "http://{}/country.mmdb",
+            listener.local_addr().expect("读取监听地址失败")
+        );
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("接受连接失败");
+            let mut buf = [0u8; 1024];
+            let _ = stream.read(&mut buf);
+            let _ = stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n");
+        });
+
+        let client = reqwest::blocking::Client::builder()
+            .no_proxy()
+            .build()
+            .expect("创建 HTTP 客户端失败");
+        let result = download_with_fallback(&client, &[url], &path, |temp| {
+            validate_geo_file(GeoKind::Mmdb, temp)
+        });
+        server.join().expect("测试服务线程异常");
+        assert!(result.is_err());
+        assert_eq!(fs::read(&path).expect("读取数据库失败"), b"old database");
+        assert_eq!(fs::read_dir(&dir).expect("读取目录失败").count(), 1);
+
+        fs::write(&path, b"truncated").expect("写入测试文件失败");
+        assert!(validate_geo_file(GeoKind::Mmdb, &path).is_err());
+        fs::write(&path, b"data\xAB\xCD\xEFMaxMind.commeta").expect("写入测试文件失败");
+        assert!(validate_geo_file(GeoKind::Mmdb, &path).is_ok());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
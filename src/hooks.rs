@@ -0,0 +1,424 @@
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+
+use crate::api::{api_delete, build_client, flush_dns_cache, flush_fakeip_cache, load_api_context};
+use crate::auto_sudo;
+use crate::auto_switch;
+use crate::cli::{
+    AutoSwitchApplyArgs, HookTarget, HooksCommand, HooksInstallArgs, HooksRunArgs, HooksTargetArgs,
+};
+use crate::constants;
+use crate::output::{is_json_mode, print_json};
+use crate::paths::{AppPaths, app_paths};
+use crate::utils::shell_quote;
+
+/// 钩子脚本首行标记，用于识别并只删除由 clash-cli 写入的脚本。
+const HOOK_MARKER: &str = "# clash-cli network hook";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HookKind {
+    NetworkManager,
+    Networkd,
+}
+
+impl HookKind {
+    const ALL: [HookKind; 2] = [HookKind::NetworkManager, HookKind::Networkd];
+
+    fn as_str(self) -> &'static str {
+        match self {
+            HookKind::NetworkManager => "network-manager",
+            HookKind::Networkd => "networkd",
+        }
+    }
+
+    /// dispatcher 的根目录，存在即视为已安装对应组件。
+    fn base_dir(self) -> &'static Path {
+        match self {
+            HookKind::NetworkManager => Path::new(constants::NM_DISPATCHER_DIR),
+            HookKind::Networkd => Path::new(constants::NETWORKD_DISPATCHER_DIR),
+        }
+    }
+
+    fn script_path(self) -> PathBuf {
+        match self {
+            HookKind::NetworkManager => self.base_dir().join(constants::NETWORK_HOOK_FILE_NAME),
+            // networkd-dispatcher 按状态分目录，接口进入 routable 时触发
+            HookKind::Networkd => self
+                .base_dir()
+                .join("routable.d")
+                .join(constants::NETWORK_HOOK_FILE_NAME),
+        }
+    }
+}
+
+pub fn run(command: HooksCommand) -> Result<()> {
+    let result = match &command {
+        HooksCommand::Install(args) => cmd_install(args),
+        HooksCommand::Uninstall(args) => cmd_uninstall(args),
+        HooksCommand::Status => cmd_status(),
+        HooksCommand::Run(args) => cmd_run(args),
+    };
+
+    match result {
+        Err(err)
+            if matches!(
+                command,
+                HooksCommand::Install(_) | HooksCommand::Uninstall(_)
+            ) && auto_sudo::is_permission_denied_error(&err)
+                && auto_sudo::should_auto_delegate(is_json_mode()) =>
+        {
+            println!("检测到权限不足，正在请求 sudo 授权继续执行 hooks 命令...");
+            run_with_sudo(&command)
+        }
+        other => other,
+    }
+}
+
+fn cmd_install(args: &HooksInstallArgs) -> Result<()> {
+    let paths = app_paths()?;
+    let exe = std::env::current_exe().context("获取当前可执行文件路径失败")?;
+    let kinds = resolve_kinds(args.target.target, true)?;
+
+    let mut installed = Vec::new();
+    for kind in kinds {
+        let path = kind.script_path();
+        if path.exists() && !is_managed_script(&path) && !args.force {
+            bail!(
+                "{} 已存在且不是 clash-cli 写入的脚本，可加 --force 覆盖",
+                path.display()
+            );
+        }
+        let content = build_hook_script(kind, &exe, &paths, args.auto_switch);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("创建目录失败: {}", parent.display()))?;
+        }
+        fs::write(&path, content)
+            .with_context(|| format!("写入钩子脚本失败: {}", path.display()))?;
+        // dispatcher 要求脚本属主 root 且不可被组/其他用户写
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))
+            .with_context(|| format!("设置钩子脚本权限失败: {}", path.display()))?;
+        installed.push((kind, path));
+    }
+
+    if is_json_mode() {
+        let items: Vec<serde_json::Value> = installed
+            .iter()
+            .map(|(kind, path)| {
+                serde_json::json!({
+                    "target": kind.as_str(),
+                    "path": path.display().to_string(),
+                })
+            })
+            .collect();
+        return print_json(&serde_json::json!({
+            "ok": true,
+            "action": "hooks.install",
+            "installed": items,
+            "auto_switch": args.auto_switch,
+            "home": paths.config_dir.display().to_string(),
+        }));
+    }
+
+    for (kind, path) in &installed {
+        println!("已安装 {} 钩子: {}", kind.as_str(), path.display());
+    }
+    println!(
+        "网络变化时将清理 fake-ip/DNS 缓存并关闭旧连接{}。",
+        if args.auto_switch {
+            "，并执行 auto-switch 规则评估"
+        } else {
+            ""
+        }
+    );
+    Ok(())
+}
+
+fn cmd_uninstall(args: &HooksTargetArgs) -> Result<()> {
+    let kinds = resolve_kinds(args.target, false)?;
+    let mut removed = Vec::new();
+    for kind in kinds {
+        let path = kind.script_path();
+        if !path.exists() {
+            continue;
+        }
+        if !is_managed_script(&path) {
+            bail!("{} 不是 clash-cli 写入的脚本，已跳过删除", path.display());
+        }
+        fs::remove_file(&path).with_context(|| format!("删除钩子脚本失败: {}", path.display()))?;
+        removed.push(path.display().to_string());
+    }
+
+    if is_json_mode() {
+        return print_json(&serde_json::json!({
+            "ok": true,
+            "action": "hooks.uninstall",
+            "removed": removed,
+        }));
+    }
+    if removed.is_empty() {
+        println!("未发现已安装的钩子脚本。");
+    }
+    for path in &removed {
+        println!("已删除钩子脚本: {path}");
+    }
+    Ok(())
+}
+
+fn cmd_status() -> Result<()> {
+    let items: Vec<serde_json::Value> = HookKind::ALL
+        .iter()
+        .map(|kind| {
+            let path = kind.script_path();
+            serde_json::json!({
+                "target": kind.as_str(),
+                "dispatcher_present": kind.base_dir().is_dir(),
+                "installed": path.exists() && is_managed_script(&path),
+                "path": path.display().to_string(),
+            })
+        })
+        .collect();
+
+    if is_json_mode() {
+        return print_json(&serde_json::json!({
+            "ok": true,
+            "action": "hooks.status",
+            "hooks": items,
+        }));
+    }
+
+    for item in &items {
+        let state = if item["installed"].as_bool().unwrap_or(false) {
+            "已安装"
+        } else if item["dispatcher_present"].as_bool().unwrap_or(false) {
+            "未安装"
+        } else {
+            "dispatcher 不存在"
+        };
+        println!(
+            "{}: {} ({})",
+            item["target"].as_str().unwrap_or("-"),
+            state,
+            item["path"].as_str().unwrap_or("-")
+        );
+    }
+    Ok(())
+}
+
+/// 网络变化后的动作；单步失败只记录，不中断后续步骤。
+fn cmd_run(args: &HooksRunArgs) -> Result<()> {
+    let mut steps: Vec<serde_json::Value> = Vec::new();
+    let mut push_step = |name: &str, result: Result<Option<String>>| {
+        let step = match result {
+            Ok(detail) => serde_json::json!({"step": name, "ok": true, "detail": detail}),
+            Err(err) => {
+                serde_json::json!({"step": name, "ok": false, "error": format!("{err:#}")})
+            }
+        };
+        steps.push(step);
+    };
+
+    if args.auto_switch {
+        let result = app_paths().and_then(|paths| {
+            let apply = AutoSwitchApplyArgs {
+                dry_run: false,
+                service_name: constants::DEFAULT_SERVICE_NAME.to_string(),
+                no_restart: false,
+                api: args.api.clone(),
+            };
+            let evaluation =
+                auto_switch::evaluate(&paths, &apply, auto_switch::detect_fingerprint())?;
+            Ok(evaluation.rule.map(|rule| format!("命中规则 {rule}")))
+        });
+        push_step("auto_switch", result);
+    }
+
+    // controller 不可用时记为失败步骤并跳过后续 API 步骤，已完成的步骤照常输出
+    let api = build_client(args.api.timeout_secs)
+        .and_then(|client| load_api_context(&args.api).map(|ctx| (client, ctx)));
+    let needs_api = !args.no_flush_dns || !args.no_close_connections;
+    match api {
+        Ok((client, ctx)) => {
+            if !args.no_flush_dns {
+                push_step(
+                    "flush_fakeip",
+                    flush_fakeip_cache(&client, &ctx).map(|_| None),
+                );
+                push_step("flush_dns", flush_dns_cache(&client, &ctx).map(|_| None));
+            }
+            if !args.no_close_connections {
+                push_step(
+                    "close_connections",
+                    api_delete(&client, &ctx, "/connections").map(|_| None),
+                );
+            }
+        }
+        Err(err) if needs_api => push_step("controller", Err(err)),
+        Err(_) => {}
+    }
+
+    let ok = steps.iter().all(|s| s["ok"].as_bool().unwrap_or(false));
+    if is_json_mode() {
+        return print_json(&serde_json::json!({
+            "ok": ok,
+            "action": "hooks.run",
+            "reason": args.reason,
+            "steps": steps,
+        }));
+    }
+
+    println!(
+        "网络变化处理{}",
+        args.reason
+            .as_deref()
+            .map(|r| format!("（{r}）"))
+            .unwrap_or_default()
+    );
+    for step in &steps {
+        let name = step["step"].as_str().unwrap_or("-");
+        match step["error"].as_str() {
+            Some(err) => println!("  {name}: 失败: {err}"),
+            None => match step["detail"].as_str() {
+                Some(detail) => println!("  {name}: 完成（{detail}）"),
+                None => println!("  {name}: 完成"),
+            },
+        }
+    }
+    Ok(())
+}
+
+/// auto 模式下选择已存在的 dispatcher；安装时一个都没有则报错。
+fn resolve_kinds(target: HookTarget, for_install: bool) -> Result<Vec<HookKind>> {
+    let kinds = match target {
+        HookTarget::NetworkManager => vec![HookKind::NetworkManager],
+        HookTarget::Networkd => vec![HookKind::Networkd],
+        HookTarget::Auto if for_install => {
+            let found: Vec<HookKind> = HookKind::ALL
+                .into_iter()
+                .filter(|k| k.base_dir().is_dir())
+                .collect();
+            if found.is_empty() {
+                bail!(
+                    "未检测到 NetworkManager 或 networkd-dispatcher（{} / {}），可用 --target 指定",
+                    constants::NM_DISPATCHER_DIR,
+                    constants::NETWORKD_DISPATCHER_DIR
+                );
+            }
+            found
+        }
+        HookTarget::Auto => HookKind::ALL.to_vec(),
+    };
+    Ok(kinds)
+}
+
+fn is_managed_script(path: &Path) -> bool {
+    fs::read_to_string(path)
+        .map(|content| content.lines().nth(1) == Some(HOOK_MARKER))
+        .unwrap_or(false)
+}
+
+fn build_hook_script(kind: HookKind, exe: &Path, paths: &AppPaths, auto_switch: bool) -> String {
+    // 两种 dispatcher 传参方式不同：NM 用位置参数，networkd-dispatcher 用环境变量
+    let (vars, filter) = match kind {
+        HookKind::NetworkManager => (
+            "IFACE=\"$1\"\nACTION=\"$2\"\n",
+            "case \"$ACTION\" in\n  up|dhcp4-change|dhcp6-change|connectivity-change|vpn-up|vpn-down) ;;\n  *) exit 0 ;;\nesac\n",
+        ),
+        HookKind::Networkd => ("ACTION=\"${STATE:-routable}\"\n", ""),
+    };
+    let mut command = format!(
+        "env CLASH_CLI_HOME={} {} hooks run --reason \"{}:$ACTION:$IFACE\"",
+        shell_quote(&paths.config_dir.display().to_string()),
+        shell_quote(&exe.display().to_string()),
+        kind.as_str()
+    );
+    if auto_switch {
+        command.push_str(" --auto-switch");
+    }
+    format!(
+        "#!/bin/sh\n\
+         {HOOK_MARKER}\n\
+         # 由 `clash hooks install` 生成，请勿手动修改；删除请执行 `clash hooks uninstall`\n\
+         {vars}\
+         {filter}\
+         # 跳过回环与 mihomo 自身的 TUN 网卡，避免内核启动时自触发\n\
+         case \"$IFACE\" in\n  lo|Meta*|utun*|tun*) exit 0 ;;\nesac\n\
+         if command -v logger >/dev/null 2>&1; then\n  ({command} 2>&1 | logger -t clash-cli-hook) >/dev/null 2>&1 &\n\
+         else\n  {command} >/dev/null 2>&1 &\nfi\n\
+         exit 0\n"
+    )
+}
+
+fn run_with_sudo(command: &HooksCommand) -> Result<()> {
+    let mut cli_args = vec!["hooks".to_string()];
+    let target = match command {
+        HooksCommand::Install(v) => {
+            cli_args.push("install".to_string());
+            if v.auto_switch {
+                cli_args.push("--auto-switch".to_string());
+            }
+            if v.force {
+                cli_args.push("--force".to_string());
+            }
+            v.target.target
+        }
+        HooksCommand::Uninstall(v) => {
+            cli_args.push("uninstall".to_string());
+            v.target
+        }
+        _ => bail!("该 hooks 命令无需 sudo"),
+    };
+    cli_args.push("--target".to_string());
+    cli_args.push(
+        match target {
+            HookTarget::Auto => "auto",
+            HookTarget::NetworkManager => "network-manager",
+            HookTarget::Networkd => "networkd",
+        }
+        .to_string(),
+    );
+
+    let status = auto_sudo::run_with_sudo(is_json_mode(), |cmd| {
+        cmd.args(&cli_args);
+        Ok(())
+    })?;
+    if status.success() {
+        return Ok(());
+    }
+    bail!("sudo 授权未通过或命令执行失败，请手动使用 sudo 重试");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_hook_script_should_quote_paths_and_carry_marker() {
+        let paths = AppPaths {
+            config_dir: PathBuf::from("/etc/clash cli"),
+            ..crate::paths::app_paths().expect("获取路径失败")
+        };
+        let script = build_hook_script(
+            HookKind::NetworkManager,
+            Path::new("/usr/local/bin/clash"),
+            &paths,
+            true,
+        );
+        assert!(script.starts_with("#!/bin/sh\n"));
+        assert_eq!(script.lines().nth(1), Some(HOOK_MARKER));
+        assert!(
+            script.contains("CLASH_CLI_HOME='/etc/clash cli' '/usr/local/bin/clash' hooks run")
+        );
+        assert!(script.contains("--auto-switch"));
+        assert!(script.contains("IFACE=\"$1\""));
+
+        let networkd =
+            build_hook_script(HookKind::Networkd, Path::new("/bin/clash"), &paths, false);
+        assert!(!networkd.contains("--auto-switch"));
+        assert!(networkd.contains("${STATE:-routable}"));
+        assert_eq!(shell_quote("a'b"), "'a'\\''b'");
+    }
+}
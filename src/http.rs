@@ -1,9 +1,9 @@
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::{self, Write};
 use std::path::Path;
 use std::time::Duration;
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, bail};
 use reqwest::blocking::Client;
 
 use crate::cli::MirrorSource;
@@ -51,3 +51,53 @@ pub fn download_to_file(client: &Client, url: &str, output_path: &Path) -> Resul
         .with_context(|| format!("刷新文件失败: {}", output_path.display()))?;
     Ok(())
 }
+
+/// 按顺序尝试候选地址下载，先写入同目录临时文件，校验通过后再原子替换目标，返回实际使用的地址。
+/// 校验失败视同该地址下载失败，继续尝试下一个候选，目标文件保持不变。
+pub fn download_with_fallback(
+    client: &Client,
+    candidates: &[String],
+    output_path: &Path,
+    validate: impl Fn(&Path) -> Result<()>,
+) -> Result<String> {
+    if let Some(parent) = output_path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("创建目录失败: {}", parent.display()))?;
+    }
+    let mut temp_name = output_path
+        .file_name()
+        .map(|v| v.to_os_string())
+        .unwrap_or_default();
+    temp_name.push(format!(".{}.download", std::process::id()));
+    let temp_path = output_path.with_file_name(temp_name);
+
+    let mut errors = Vec::new();
+    for url in candidates {
+        match download_to_file(client, url, &temp_path).and_then(|_| validate(&temp_path)) {
+            Ok(()) => {
+                fs::rename(&temp_path, output_path).with_context(|| {
+                    format!(
+                        "替换文件失败: {} -> {}",
+                        temp_path.display(),
+                        output_path.display()
+                    )
+                })?;
+                return Ok(url.clone());
+            }
+            Err(err) => errors.push(format!("{url} => {err}")),
+        }
+    }
+    let _ = fs::remove_file(&temp_path);
+    bail!("下载失败，已尝试所有源:\n{}", errors.join("\n"))
+}
+
+/// 最基本的下载校验：结果不能是空文件。
+pub fn ensure_non_empty(path: &Path) -> Result<()> {
+    let size = fs::metadata(path)
+        .with_context(|| format!("读取文件信息失败: {}", path.display()))?
+        .len();
+    if size == 0 {
+        bail!("下载结果为空文件");
+    }
+    Ok(())
+}
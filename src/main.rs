@@ -1,17 +1,29 @@
 mod api;
 mod auto_sudo;
+mod auto_switch;
 mod cli;
 mod constants;
 mod core;
+mod export;
+mod extract;
+mod failover;
+mod geo;
+mod hooks;
 mod http;
 mod mixin;
 mod output;
 mod paths;
+mod prefetch;
 mod profile;
 mod proxy;
+mod render;
+mod rule;
+mod secret;
+mod serve;
 mod service;
 mod setup;
 mod tun;
+mod ui;
 mod update;
 mod utils;
 
@@ -37,6 +49,7 @@ fn main() {
 fn run() -> Result<()> {
     let cli = Cli::parse();
     output::set_json_mode(cli.json);
+    output::set_show_secrets(cli.show_secrets);
 
     match cli.command {
         Commands::Proxy { command } => proxy::run(command)?,
@@ -46,6 +59,11 @@ fn run() -> Result<()> {
         Commands::Profile { command } => profile::run(command)?,
         Commands::Api { command } => api::run(command)?,
         Commands::Setup { command } => setup::run(command)?,
+        Commands::Ui { command } => ui::run(command)?,
+        Commands::Geo { command } => geo::run(command)?,
+        Commands::Rule { command } => rule::run(command)?,
+        Commands::AutoSwitch { command } => auto_switch::run(command)?,
+        Commands::Hooks { command } => hooks::run(command)?,
         Commands::Update { command } => update::run(command)?,
     }
 
@@ -1,9 +1,11 @@
+use std::io::{self, Write};
 use std::sync::atomic::{AtomicBool, Ordering};
 
 use anyhow::{Context, Result};
 use serde::Serialize;
 
 static JSON_MODE: AtomicBool = AtomicBool::new(false);
+static SHOW_SECRETS: AtomicBool = AtomicBool::new(false);
 
 pub fn set_json_mode(enabled: bool) {
     JSON_MODE.store(enabled, Ordering::Relaxed);
@@ -13,8 +15,43 @@ pub fn is_json_mode() -> bool {
     JSON_MODE.load(Ordering::Relaxed)
 }
 
+pub fn set_show_secrets(enabled: bool) {
+    SHOW_SECRETS.store(enabled, Ordering::Relaxed);
+}
+
+pub fn show_secrets() -> bool {
+    SHOW_SECRETS.load(Ordering::Relaxed)
+}
+
+/// 输出订阅 URL 时统一经过这里，默认脱敏，`--show-secrets` 时原样输出。
+pub fn display_url(url: &str) -> String {
+    if show_secrets() {
+        url.to_string()
+    } else {
+        crate::secret::redact_url(url)
+    }
+}
+
 pub fn print_json<T: Serialize>(value: &T) -> Result<()> {
     let text = serde_json::to_string_pretty(value).context("序列化 JSON 失败")?;
     println!("{}", text);
     Ok(())
 }
+
+/// 流式命令的 JSON Lines 输出：每条紧凑 JSON 一行并立即刷新。
+pub fn print_json_line<T: Serialize>(value: &T) -> Result<()> {
+    let text = serde_json::to_string(value).context("序列化 JSON 失败")?;
+    print_line(&text)
+}
+
+/// 流式命令逐行输出并立即刷新；下游管道关闭（如 `| head`）时视为正常结束直接退出。
+pub fn print_line(line: &str) -> Result<()> {
+    let mut stdout = io::stdout().lock();
+    if let Err(err) = writeln!(stdout, "{line}").and_then(|_| stdout.flush()) {
+        if err.kind() == io::ErrorKind::BrokenPipe {
+            std::process::exit(0);
+        }
+        return Err(err).context("写入 stdout 失败");
+    }
+    Ok(())
+}
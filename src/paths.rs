@@ -22,6 +22,12 @@ pub struct AppPaths {
     pub profile_dir: PathBuf,
     pub profile_index_file: PathBuf,
     pub profile_mixin_file: PathBuf,
+    pub profile_key_file: PathBuf,
+    pub secrets_env_file: PathBuf,
+    pub ui_state_file: PathBuf,
+    pub serve_token_file: PathBuf,
+    pub failover_state_file: PathBuf,
+    pub auto_switch_file: PathBuf,
     pub core_dir: PathBuf,
     pub core_versions_dir: PathBuf,
     pub core_current_link: PathBuf,
@@ -53,6 +59,12 @@ pub fn app_paths() -> Result<AppPaths> {
         env_file: config_dir.join("proxy.env"),
         profile_index_file: profile_dir.join("index.json"),
         profile_mixin_file: profile_dir.join("mixin.yaml"),
+        profile_key_file: config_dir.join("store.key"),
+        secrets_env_file: config_dir.join("secrets.env"),
+        ui_state_file: config_dir.join("ui.json"),
+        serve_token_file: config_dir.join("serve.token"),
+        failover_state_file: config_dir.join("failover.json"),
+        auto_switch_file: config_dir.join("auto-switch.json"),
         profile_dir,
         runtime_dir: config_dir.join("runtime"),
         runtime_config_file: config_dir.join("runtime").join("config.yaml"),
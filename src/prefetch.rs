@@ -0,0 +1,400 @@
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use serde::Serialize;
+use serde_yaml::Value;
+
+use crate::cli::ProfilePrefetchArgs;
+use crate::geo::{self, GeoKind};
+use crate::http::{
+    build_http_client, download_candidates, download_with_fallback, ensure_non_empty,
+};
+use crate::output::{is_json_mode, print_json};
+use crate::paths::app_paths;
+use crate::profile::{load_yaml, resolve_core_workdir};
+use crate::rule::resolve_provider_path;
+use crate::utils;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum PrefetchStatus {
+    Downloaded,
+    Fresh,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct PrefetchItem {
+    kind: &'static str,
+    name: String,
+    url: String,
+    /// 相对工作目录的路径（provider 改写时写回配置）
+    path: String,
+    status: PrefetchStatus,
+    source: Option<String>,
+    size: Option<u64>,
+    age_secs: Option<u64>,
+    error: Option<String>,
+}
+
+struct PrefetchTarget {
+    kind: &'static str,
+    name: String,
+    url: String,
+    path: String,
+    /// 配置未给出可用 path，使用本工具推断的默认路径
+    defaulted: bool,
+}
+
+pub fn run(args: ProfilePrefetchArgs) -> Result<()> {
+    let paths = app_paths()?;
+    let config_path = args
+        .config
+        .clone()
+        .unwrap_or_else(|| paths.runtime_config_file.clone());
+    if !config_path.exists() {
+        bail!(
+            "未找到渲染配置: {}，请先执行 `clash profile render`",
+            config_path.display()
+        );
+    }
+    let mut root = load_yaml(&config_path)?;
+    let workdir = args
+        .workdir
+        .clone()
+        .unwrap_or_else(|| resolve_core_workdir(&paths, &args.service_name));
+
+    // 未设置 path 的 provider 由 mihomo 按内部规则决定落盘位置，不改写配置时预取文件不会被使用
+    let (mut targets, defaulted): (Vec<_>, Vec<_>) = collect_provider_targets(&root)
+        .into_iter()
+        .partition(|t| args.rewrite_local || !t.defaulted);
+    let skipped: Vec<String> = defaulted.into_iter().map(|t| t.name).collect();
+    if !args.no_geo {
+        targets.extend(collect_geo_targets(&root));
+    }
+
+    let client = build_http_client()?;
+    let max_age = args.max_age_hours.saturating_mul(3600);
+    let mut items = Vec::new();
+    for target in targets {
+        let full_path = resolve_provider_path(&target.path, &workdir);
+        let age = utils::file_age_secs(&full_path);
+        let mut item = PrefetchItem {
+            kind: target.kind,
+            name: target.name,
+            url: target.url,
+            path: target.path,
+            status: PrefetchStatus::Fresh,
+            source: None,
+            size: None,
+            age_secs: age,
+            error: None,
+        };
+
+        if args.force || age.is_none_or(|v| v >= max_age) {
+            let candidates = download_candidates(&item.url, args.mirror);
+            match download_with_fallback(&client, &candidates, &full_path, ensure_non_empty) {
+                Ok(source) => {
+                    item.status = PrefetchStatus::Downloaded;
+                    item.source = Some(source);
+                    item.age_secs = Some(0);
+                }
+                Err(err) => {
+                    item.status = PrefetchStatus::Failed;
+                    item.error = Some(err.to_string());
+                }
+            }
+        }
+        item.size = fs::metadata(&full_path).ok().map(|m| m.len());
+        items.push(item);
+    }
+
+    let mut rewritten = Vec::new();
+    if args.rewrite_local {
+        rewritten = rewrite_providers_to_local(&mut root, &items);
+        if !rewritten.is_empty() {
+            let text = serde_yaml::to_string(&root).context("序列化配置失败")?;
+            fs::write(&config_path, text)
+                .with_context(|| format!("写入配置失败: {}", config_path.display()))?;
+        }
+    }
+
+    let count = |status: PrefetchStatus| items.iter().filter(|i| i.status == status).count();
+    let (downloaded, fresh, failed) = (
+        count(PrefetchStatus::Downloaded),
+        count(PrefetchStatus::Fresh),
+        count(PrefetchStatus::Failed),
+    );
+
+    if is_json_mode() {
+        return print_json(&serde_json::json!({
+            "ok": failed == 0,
+            "action": "profile.prefetch",
+            "config": config_path.display().to_string(),
+            "workdir": workdir.display().to_string(),
+            "summary": {
+                "downloaded": downloaded,
+                "fresh": fresh,
+                "failed": failed,
+            },
+            "items": items,
+            "rewritten": rewritten,
+            "skipped": skipped,
+        }));
+    }
+
+    println!("配置文件: {}", config_path.display());
+    println!("工作目录: {}", workdir.display());
+    if !skipped.is_empty() {
+        eprintln!(
+            "警告: 以下 rule-provider 未设置可用的 path，预取文件不会被内核使用，已跳过: {}（可加 --rewrite-local 改写为本地文件）",
+            skipped.join(", ")
+        );
+    }
+    if items.is_empty() {
+        println!("配置中未引用 http rule-provider 或 geo 数据库，无需预取。");
+        return Ok(());
+    }
+    for item in &items {
+        print_item(item);
+    }
+    if !rewritten.is_empty() {
+        println!("已改写为本地 file 类型: {}", rewritten.join(", "));
+        println!("提示: 重新执行 `clash profile render` 会恢复订阅原始设置，需再次预取。");
+    }
+    println!();
+    println!(
+        "预取汇总: downloaded={} fresh={} failed={}",
+        downloaded, fresh, failed
+    );
+    if failed > 0 {
+        bail!("部分文件预取失败，请检查网络或镜像设置（--mirror）");
+    }
+    Ok(())
+}
+
+fn collect_provider_targets(root: &Value) -> Vec<PrefetchTarget> {
+    let Some(providers) = root.get("rule-providers").and_then(|v| v.as_mapping()) else {
+        return Vec::new();
+    };
+    providers
+        .iter()
+        .filter_map(|(name, provider)| {
+            let name = name.as_str()?;
+            let provider_type = provider.get("type").and_then(|v| v.as_str())?;
+            // 已改写为 file 但保留了 url 的 provider 也允许刷新
+            if provider_type != "http" && provider_type != "file" {
+                return None;
+            }
+            let url = provider.get("url").and_then(|v| v.as_str())?;
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                return None;
+            }
+            let configured = provider
+                .get("path")
+                .and_then(|v| v.as_str())
+                .filter(|p| is_safe_relative_path(p));
+            Some(PrefetchTarget {
+                kind: "rule-provider",
+                name: name.to_string(),
+                url: url.to_string(),
+                path: configured
+                    .map(str::to_string)
+                    .unwrap_or_else(|| default_provider_path(name, provider)),
+                defaulted: configured.is_none(),
+            })
+        })
+        .collect()
+}
+
+fn collect_geo_targets(root: &Value) -> Vec<PrefetchTarget> {
+    geo::referenced_geo_kinds(root)
+        .into_iter()
+        .map(|kind: GeoKind| PrefetchTarget {
+            kind: "geo",
+            name: kind.geox_key().to_string(),
+            url: geo::geo_url(root, kind),
+            path: kind.file_name().to_string(),
+            defaulted: false,
+        })
+        .collect()
+}
+
+/// 只接受工作目录内的相对路径，避免订阅借 provider.path 覆盖任意文件。
+fn is_safe_relative_path(path: &str) -> bool {
+    let path = Path::new(path);
+    !path.as_os_str().is_empty()
+        && path
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+}
+
+fn default_provider_path(name: &str, provider: &Value) -> String {
+    let ext = match provider.get("format").and_then(|v| v.as_str()) {
+        Some("text") => "txt",
+        Some("mrs") => "mrs",
+        _ => "yaml",
+    };
+    let safe_name = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    PathBuf::from("ruleset")
+        .join(format!("{safe_name}.{ext}"))
+        .display()
+        .to_string()
+}
+
+fn rewrite_providers_to_local(root: &mut Value, items: &[PrefetchItem]) -> Vec<String> {
+    let Some(providers) = root
+        .get_mut("rule-providers")
+        .and_then(|v| v.as_mapping_mut())
+    else {
+        return Vec::new();
+    };
+    let mut rewritten = Vec::new();
+    for item in items {
+        if item.kind != "rule-provider" || item.size.is_none() {
+            continue;
+        }
+        let Some(provider) = providers
+            .get_mut(item.name.as_str())
+            .and_then(|v| v.as_mapping_mut())
+        else {
+            continue;
+        };
+        // 保留 url，便于后续再次执行 prefetch 刷新本地文件
+        provider.insert(
+            Value::String("type".to_string()),
+            Value::String("file".to_string()),
+        );
+        provider.insert(
+            Value::String("path".to_string()),
+            Value::String(item.path.clone()),
+        );
+        rewritten.push(item.name.clone());
+    }
+    rewritten
+}
+
+fn print_item(item: &PrefetchItem) {
+    let status = match item.status {
+        PrefetchStatus::Downloaded => "下载",
+        PrefetchStatus::Fresh => "新鲜",
+        PrefetchStatus::Failed => "失败",
+    };
+    let mut detail = Vec::new();
+    if let Some(size) = item.size {
+        detail.push(utils::format_bytes(size));
+    }
+    if let Some(age) = item.age_secs {
+        detail.push(format!("更新于 {}", utils::format_age(age)));
+    }
+    println!(
+        "[{}] {} {} -> {} ({})",
+        status,
+        item.kind,
+        item.name,
+        item.path,
+        if detail.is_empty() {
+            "本地不存在".to_string()
+        } else {
+            detail.join(", ")
+        }
+    );
+    if let Some(source) = &item.source {
+        println!("        来源: {}", source);
+    }
+    if let Some(err) = &item.error {
+        println!("        错误: {}", err.replace('\n', "\n        "));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_yaml(input: &str) -> Value {
+        serde_yaml::from_str(input).expect("解析测试 YAML 失败")
+    }
+
+    #[test]
+    fn collect_provider_targets_should_pick_http_providers_with_safe_paths() {
+        let root = parse_yaml(
+            r#"
+rule-providers:
+  reject:
+    type: http
+    behavior: domain
+    url: https://example.com/reject.yaml
+    path: ./ruleset/reject.yaml
+  escape:
+    type: http
+    behavior: domain
+    format: text
+    url: https://example.com/escape.txt
+    path: ../../etc/passwd
+  local:
+    type: file
+    behavior: domain
+    path: ./local.yaml
+  inline:
+    type: inline
+    behavior: domain
+    payload: []
+"#,
+        );
+        let targets = collect_provider_targets(&root);
+        assert_eq!(targets.len(), 2);
+        assert_eq!(targets[0].name, "reject");
+        assert_eq!(targets[0].path, "./ruleset/reject.yaml");
+        assert_eq!(targets[1].name, "escape");
+        assert_eq!(targets[1].path, "ruleset/escape.txt");
+        assert!(!targets[0].defaulted);
+        assert!(targets[1].defaulted);
+    }
+
+    #[test]
+    fn rewrite_providers_to_local_should_switch_type_and_keep_url() {
+        let mut root = parse_yaml(
+            r#"
+rule-providers:
+  reject:
+    type: http
+    behavior: domain
+    url: https://example.com/reject.yaml
+    interval: 86400
+"#,
+        );
+        let items = vec![PrefetchItem {
+            kind: "rule-provider",
+            name: "reject".to_string(),
+            url: "https://example.com/reject.yaml".to_string(),
+            path: "ruleset/reject.yaml".to_string(),
+            status: PrefetchStatus::Downloaded,
+            source: None,
+            size: Some(10),
+            age_secs: Some(0),
+            error: None,
+        }];
+        let rewritten = rewrite_providers_to_local(&mut root, &items);
+        assert_eq!(rewritten, vec!["reject".to_string()]);
+        let provider = root
+            .get("rule-providers")
+            .and_then(|v| v.get("reject"))
+            .expect("provider 不存在");
+        assert_eq!(provider.get("type").and_then(|v| v.as_str()), Some("file"));
+        assert_eq!(
+            provider.get("path").and_then(|v| v.as_str()),
+            Some("ruleset/reject.yaml")
+        );
+        assert!(provider.get("url").is_some());
+    }
+}
@@ -2,19 +2,23 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
-use anyhow::{Context, Result, bail};
+use anyhow::{Context, Result, anyhow, bail};
 use reqwest::blocking::Client;
 use serde::{Deserialize, Serialize};
 use serde_yaml::Value;
 
 use crate::auto_sudo;
 use crate::cli::{
-    ProfileAddArgs, ProfileCommand, ProfileFetchArgs, ProfileRemoveArgs, ProfileRenderArgs,
+    FailoverCommand, ProfileAddArgs, ProfileCommand, ProfileDecryptArgs, ProfileFetchArgs,
+    ProfileRemoveArgs, ProfileRenameArgs, ProfileRenderArgs, ProfileSetArgs, ProfileSetUrlArgs,
     ProfileUseArgs, ProfileValidateArgs,
 };
 use crate::constants;
-use crate::output::{is_json_mode, print_json};
+use crate::extract::ExtractSummary;
+use crate::output::{display_url, is_json_mode, print_json};
 use crate::paths::{AppPaths, app_paths};
+use crate::render::RenderedProfile;
+use crate::secret::{self, StoreKey};
 use crate::utils;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -24,6 +28,19 @@ pub(crate) struct ProfileEntry {
     pub(crate) file: String,
     pub(crate) created_at: u64,
     pub(crate) updated_at: Option<u64>,
+    /// 上游订阅返回的 subscription-userinfo（流量/到期信息）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) subscription_userinfo: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) note: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) tags: Vec<String>,
+    /// 故障转移优先级，数字越小越优先；未设置的 profile 不作为备用
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) priority: Option<u32>,
+    /// 渲染运行配置时抽取本地 provider 的最小规则数；未设置时不抽取
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) extract_min_rules: Option<usize>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -32,6 +49,16 @@ pub(crate) struct ProfileIndex {
     pub(crate) profiles: Vec<ProfileEntry>,
 }
 
+impl ProfileEntry {
+    /// 用于输出的副本，订阅 URL 按 `--show-secrets` 决定是否脱敏。
+    pub(crate) fn for_display(&self) -> ProfileEntry {
+        ProfileEntry {
+            url: display_url(&self.url),
+            ..self.clone()
+        }
+    }
+}
+
 pub fn run(command: ProfileCommand) -> Result<()> {
     // Mixin 子命令有自己的 auto_sudo 逻辑，直接转发
     if let ProfileCommand::Mixin {
@@ -48,8 +75,17 @@ pub fn run(command: ProfileCommand) -> Result<()> {
         ProfileCommand::Use(args) => cmd_use(args),
         ProfileCommand::Fetch(args) => cmd_fetch(args),
         ProfileCommand::Remove(args) => cmd_remove(args),
+        ProfileCommand::Rename(args) => cmd_rename(args),
+        ProfileCommand::SetUrl(args) => cmd_set_url(args),
+        ProfileCommand::Set(args) => cmd_set(args),
         ProfileCommand::Render(args) => cmd_render(args),
         ProfileCommand::Validate(args) => cmd_validate(args),
+        ProfileCommand::Prefetch(args) => crate::prefetch::run(args),
+        ProfileCommand::Export(args) => crate::export::run(args),
+        ProfileCommand::Serve(args) => crate::serve::run(args),
+        ProfileCommand::Encrypt => cmd_encrypt(),
+        ProfileCommand::Decrypt(args) => cmd_decrypt(args),
+        ProfileCommand::Failover { command } => crate::failover::run(command),
         ProfileCommand::Mixin { .. } => unreachable!(),
     };
 
@@ -82,10 +118,15 @@ fn cmd_add(args: ProfileAddArgs) -> Result<()> {
         file: format!("{}.yaml", args.name),
         created_at: utils::now_unix(),
         updated_at: None,
+        subscription_userinfo: None,
+        note: None,
+        tags: Vec::new(),
+        priority: None,
+        extract_min_rules: None,
     };
 
     if !args.no_fetch {
-        fetch_profile_entry(&mut entry, &paths.profile_dir, true)?;
+        fetch_profile_entry(&mut entry, &paths, true)?;
     }
     if args.use_profile {
         index.active = Some(entry.name.clone());
@@ -97,7 +138,7 @@ fn cmd_add(args: ProfileAddArgs) -> Result<()> {
         return print_json(&serde_json::json!({
             "ok": true,
             "action": "profile.add",
-            "profile": entry,
+            "profile": entry.for_display(),
             "active": index.active,
         }));
     }
@@ -117,7 +158,10 @@ fn cmd_list() -> Result<()> {
     let index = load_index(&paths.profile_index_file)?;
 
     if is_json_mode() {
-        return print_json(&index);
+        return print_json(&ProfileIndex {
+            active: index.active.clone(),
+            profiles: index.profiles.iter().map(|p| p.for_display()).collect(),
+        });
     }
 
     if index.profiles.is_empty() {
@@ -133,15 +177,26 @@ fn cmd_list() -> Result<()> {
         } else {
             " "
         };
+        let mut extra = String::new();
+        if let Some(priority) = profile.priority {
+            extra.push_str(&format!(" priority={priority}"));
+        }
+        if !profile.tags.is_empty() {
+            extra.push_str(&format!(" [{}]", profile.tags.join(", ")));
+        }
+        if let Some(note) = &profile.note {
+            extra.push_str(&format!(" # {note}"));
+        }
         println!(
-            "{} {} -> {} ({})",
+            "{} {} -> {} ({}){}",
             mark,
             profile.name,
-            profile.url,
+            display_url(&profile.url),
             profile
                 .updated_at
                 .map(|v| format!("updated_at={v}"))
-                .unwrap_or_else(|| "未拉取".to_string())
+                .unwrap_or_else(|| "未拉取".to_string()),
+            extra
         );
     }
     Ok(())
@@ -163,7 +218,7 @@ fn cmd_use(args: ProfileUseArgs) -> Result<()> {
     if !index.profiles.iter().any(|p| p.name == args.name) {
         bail!("profile 不存在: {}", args.name);
     }
-    index.active = Some(args.name.clone());
+    let previous_active = index.active.replace(args.name.clone());
     save_index(&paths.profile_index_file, &index)?;
 
     if args.fetch {
@@ -172,12 +227,24 @@ fn cmd_use(args: ProfileUseArgs) -> Result<()> {
             force: true,
         })?;
     }
+    if apply
+        && let Some(cases) = &args.rule_cases
+        && let Err(err) = crate::rule::ensure_profile_passes_cases(&paths, &args.name, cases)
+    {
+        // 用例未通过时回滚 active，避免索引与运行配置不一致
+        let mut index = load_index(&paths.profile_index_file)?;
+        index.active = previous_active;
+        save_index(&paths.profile_index_file, &index)?;
+        return Err(err);
+    }
     if apply {
         cmd_render(ProfileRenderArgs {
             name: Some(args.name.clone()),
             output: None,
             no_mixin: false,
             follow_subscription_port: false,
+            extract_providers: false,
+            extract_min_rules: 20,
         })?;
         if !args.no_restart {
             restart_system_service(&args.service_name)?;
@@ -227,23 +294,25 @@ fn cmd_fetch(args: ProfileFetchArgs) -> Result<()> {
             .context("profile 不存在")?;
 
         let profile_path = paths.profile_dir.join(&profile.file);
-        if !args.force && profile.updated_at.is_some() && profile_path.exists() {
-            if utils::now_unix().saturating_sub(profile.updated_at.unwrap_or(0)) < 60 {
-                if is_json_mode() {
-                    return print_json(&serde_json::json!({
-                        "ok": true,
-                        "action": "profile.fetch",
-                        "name": args.name,
-                        "skipped": true,
-                        "reason": "recently updated",
-                    }));
-                }
-                println!("最近 60 秒内已更新，跳过拉取。可加 --force 强制更新。");
-                return Ok(());
+        if !args.force
+            && profile.updated_at.is_some()
+            && profile_path.exists()
+            && utils::now_unix().saturating_sub(profile.updated_at.unwrap_or(0)) < 60
+        {
+            if is_json_mode() {
+                return print_json(&serde_json::json!({
+                    "ok": true,
+                    "action": "profile.fetch",
+                    "name": args.name,
+                    "skipped": true,
+                    "reason": "recently updated",
+                }));
             }
+            println!("最近 60 秒内已更新，跳过拉取。可加 --force 强制更新。");
+            return Ok(());
         }
 
-        fetch_profile_entry(profile, &paths.profile_dir, args.force)?;
+        fetch_profile_entry(profile, &paths, args.force)?;
         profile.clone()
     };
 
@@ -253,7 +322,7 @@ fn cmd_fetch(args: ProfileFetchArgs) -> Result<()> {
         return print_json(&serde_json::json!({
             "ok": true,
             "action": "profile.fetch",
-            "profile": profile_snapshot,
+            "profile": profile_snapshot.for_display(),
         }));
     }
 
@@ -295,36 +364,210 @@ fn cmd_remove(args: ProfileRemoveArgs) -> Result<()> {
     Ok(())
 }
 
-fn cmd_render(args: ProfileRenderArgs) -> Result<()> {
+fn cmd_rename(args: ProfileRenameArgs) -> Result<()> {
+    validate_profile_name(&args.to)?;
     let paths = app_paths()?;
-    let index = load_index(&paths.profile_index_file)?;
-    let selected = select_profile(&index, args.name.as_deref())?;
-    let source_path = paths.profile_dir.join(&selected.file);
-    if !source_path.exists() {
-        bail!(
-            "profile 文件不存在: {}，请先执行 `clash profile fetch --name {}`",
-            source_path.display(),
-            selected.name
+    let mut index = load_index(&paths.profile_index_file)?;
+    if index.profiles.iter().any(|p| p.name == args.to) {
+        bail!("profile 已存在: {}", args.to);
+    }
+    let pos = index
+        .profiles
+        .iter()
+        .position(|p| p.name == args.name)
+        .context("profile 不存在")?;
+
+    let new_file = format!("{}.yaml", args.to);
+    let old_path = paths.profile_dir.join(&index.profiles[pos].file);
+    let new_path = paths.profile_dir.join(&new_file);
+    // 源文件缺失时同样检查，避免新条目指向一份无关的旧文件
+    if new_path != old_path && new_path.exists() {
+        bail!("目标 profile 文件已存在: {}", new_path.display());
+    }
+    let file_renamed = old_path.exists();
+    if file_renamed {
+        fs::rename(&old_path, &new_path).with_context(|| {
+            format!(
+                "重命名 profile 文件失败: {} -> {}",
+                old_path.display(),
+                new_path.display()
+            )
+        })?;
+    }
+
+    let entry = &mut index.profiles[pos];
+    entry.name = args.to.clone();
+    entry.file = new_file;
+    if index.active.as_deref() == Some(args.name.as_str()) {
+        index.active = Some(args.to.clone());
+    }
+    if let Err(err) = save_index(&paths.profile_index_file, &index) {
+        // 索引写入失败时回滚文件名，避免索引与文件脱节
+        if file_renamed {
+            let _ = fs::rename(&new_path, &old_path);
+        }
+        return Err(err);
+    }
+    // 网络规则与故障转移状态按名称引用 profile，一并改写
+    let rules_updated =
+        crate::auto_switch::rename_profile_references(&paths, &args.name, &args.to)?;
+    crate::failover::rename_profile_state(&paths, &args.name, &args.to)?;
+
+    if is_json_mode() {
+        return print_json(&serde_json::json!({
+            "ok": true,
+            "action": "profile.rename",
+            "from": args.name,
+            "to": args.to,
+            "file_renamed": file_renamed,
+            "active": index.active,
+            "auto_switch_rules_updated": rules_updated,
+        }));
+    }
+
+    println!("已重命名 profile: {} -> {}", args.name, args.to);
+    Ok(())
+}
+
+fn cmd_set_url(args: ProfileSetUrlArgs) -> Result<()> {
+    let paths = app_paths()?;
+    let mut index = load_index(&paths.profile_index_file)?;
+    let profile = index
+        .profiles
+        .iter_mut()
+        .find(|p| p.name == args.name)
+        .context("profile 不存在")?;
+    profile.url = args.url.trim().to_string();
+    if args.fetch {
+        fetch_profile_entry(profile, &paths, true)?;
+    } else {
+        // URL 变更后旧的流量信息不再可信
+        profile.subscription_userinfo = None;
+    }
+    let snapshot = profile.clone();
+    save_index(&paths.profile_index_file, &index)?;
+
+    if is_json_mode() {
+        return print_json(&serde_json::json!({
+            "ok": true,
+            "action": "profile.set-url",
+            "profile": snapshot.for_display(),
+            "fetched": args.fetch,
+        }));
+    }
+
+    println!(
+        "已更新订阅 URL: {} -> {}",
+        snapshot.name,
+        display_url(&snapshot.url)
+    );
+    if args.fetch {
+        println!("已拉取订阅内容。");
+    } else {
+        println!(
+            "提示: 执行 `clash profile fetch --name {}` 拉取新订阅",
+            snapshot.name
         );
     }
+    Ok(())
+}
 
-    let mut root = load_yaml(&source_path)?;
-    if !args.follow_subscription_port {
-        apply_local_listener_defaults(&mut root);
+fn cmd_set(args: ProfileSetArgs) -> Result<()> {
+    let paths = app_paths()?;
+    let mut index = load_index(&paths.profile_index_file)?;
+    let profile = index
+        .profiles
+        .iter_mut()
+        .find(|p| p.name == args.name)
+        .context("profile 不存在")?;
+
+    if args.clear_note {
+        profile.note = None;
     }
-    if !args.no_mixin && paths.profile_mixin_file.exists() {
-        let mixin = load_yaml(&paths.profile_mixin_file)?;
-        deep_merge(&mut root, &mixin);
+    if let Some(note) = &args.note {
+        let note = note.trim();
+        profile.note = (!note.is_empty()).then(|| note.to_string());
+    }
+    if args.clear_tags {
+        profile.tags.clear();
+    }
+    profile.tags.retain(|t| !args.remove_tags.contains(t));
+    for tag in &args.tags {
+        let tag = tag.trim();
+        if !tag.is_empty() && !profile.tags.iter().any(|t| t == tag) {
+            profile.tags.push(tag.to_string());
+        }
+    }
+    if args.clear_priority {
+        profile.priority = None;
+    }
+    if args.priority.is_some() {
+        profile.priority = args.priority;
+    }
+    if args.no_extract_providers {
+        profile.extract_min_rules = None;
+    }
+    if let Some(min_rules) = args.extract_providers {
+        profile.extract_min_rules = Some(min_rules.max(1));
     }
+    let snapshot = profile.clone();
+    save_index(&paths.profile_index_file, &index)?;
 
-    let output = args.output.unwrap_or(paths.runtime_config_file);
-    if let Some(parent) = output.parent() {
-        fs::create_dir_all(parent)
-            .with_context(|| format!("创建目录失败: {}", parent.display()))?;
+    if is_json_mode() {
+        return print_json(&serde_json::json!({
+            "ok": true,
+            "action": "profile.set",
+            "profile": snapshot.for_display(),
+        }));
     }
-    let rendered = serde_yaml::to_string(&root).context("序列化渲染结果失败")?;
-    fs::write(&output, rendered)
-        .with_context(|| format!("写入渲染配置失败: {}", output.display()))?;
+
+    println!("已更新 profile: {}", snapshot.name);
+    println!("note: {}", snapshot.note.as_deref().unwrap_or("-"));
+    println!(
+        "tags: {}",
+        if snapshot.tags.is_empty() {
+            "-".to_string()
+        } else {
+            snapshot.tags.join(", ")
+        }
+    );
+    println!(
+        "priority: {}",
+        snapshot
+            .priority
+            .map(|v| v.to_string())
+            .unwrap_or_else(|| "-".to_string())
+    );
+    println!(
+        "extract-providers: {}",
+        snapshot
+            .extract_min_rules
+            .map(|v| format!("min-rules={v}"))
+            .unwrap_or_else(|| "-".to_string())
+    );
+    Ok(())
+}
+
+fn cmd_render(args: ProfileRenderArgs) -> Result<()> {
+    let paths = app_paths()?;
+    let index = load_index(&paths.profile_index_file)?;
+    let selected = select_profile(&index, args.name.as_deref())?;
+    let rendered = render_profile(
+        &paths,
+        selected,
+        args.no_mixin,
+        args.follow_subscription_port,
+    )?;
+
+    let output = args.output.unwrap_or(paths.runtime_config_file);
+    // 未显式指定时沿用 profile 中保存的抽取设置
+    let extract_min_rules = if args.extract_providers {
+        Some(args.extract_min_rules)
+    } else {
+        selected.extract_min_rules
+    };
+    let (rendered, extracted) = write_rendered_extracting(&output, rendered, extract_min_rules)?;
+    let root = &rendered.root;
 
     if is_json_mode() {
         return print_json(&serde_json::json!({
@@ -333,6 +576,7 @@ fn cmd_render(args: ProfileRenderArgs) -> Result<()> {
             "profile": selected.name,
             "output": output.display().to_string(),
             "follow_subscription_port": args.follow_subscription_port,
+            "extracted": extracted,
         }));
     }
 
@@ -341,16 +585,182 @@ fn cmd_render(args: ProfileRenderArgs) -> Result<()> {
         selected.name,
         output.display()
     );
+    if let Some(summary) = &extracted {
+        println!(
+            "已抽取本地 provider: 规则 {} 条 -> {} 个 rule-provider，节点 {} 个{}",
+            summary.rules_extracted,
+            summary.rule_providers.len(),
+            summary.proxies_extracted,
+            summary
+                .proxy_provider
+                .as_deref()
+                .map(|name| format!(" -> proxy-provider {name}"))
+                .unwrap_or_default()
+        );
+        if let Some(reason) = &summary.proxies_skipped {
+            println!("未抽取内联节点: {reason}");
+        }
+    }
     if args.follow_subscription_port {
         println!("已保留订阅中的监听端口设置。");
     } else {
         println!(
-            "已应用本地默认值（mixed=7890, socks=7891, controller=127.0.0.1:9090, ui=metacubexd）。"
+            "已应用本地默认值（mixed=7890, socks=7891, controller=127.0.0.1:9090, ui={}）。",
+            root.get("external-ui-name")
+                .and_then(|v| v.as_str())
+                .unwrap_or(constants::DEFAULT_EXTERNAL_UI_NAME)
         );
     }
     Ok(())
 }
 
+/// 按默认参数重新渲染当前 active profile 到 runtime 配置；无 active 时返回 None。
+pub(crate) fn render_active_to_runtime(paths: &AppPaths) -> Result<Option<String>> {
+    let index = load_index(&paths.profile_index_file)?;
+    if index.active.is_none() {
+        return Ok(None);
+    }
+    let selected = select_profile(&index, None)?;
+    let rendered = render_profile(paths, selected, false, false)?;
+    write_rendered_extracting(
+        &paths.runtime_config_file,
+        rendered,
+        selected.extract_min_rules,
+    )?;
+    Ok(Some(selected.name.clone()))
+}
+
+/// 按需抽取本地 provider 后写出配置；provider 文件在配置写入成功后才替换。
+fn write_rendered_extracting(
+    output: &Path,
+    rendered: RenderedProfile,
+    extract_min_rules: Option<usize>,
+) -> Result<(RenderedProfile, Option<ExtractSummary>)> {
+    let Some(min_rules) = extract_min_rules else {
+        write_rendered(output, &rendered)?;
+        return Ok((rendered, None));
+    };
+    let mut root = rendered.into_value()?;
+    let workdir = output
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let staged = crate::extract::extract_inline_providers(&mut root, workdir, min_rules)?;
+    let rendered = RenderedProfile::from(root);
+    write_rendered(output, &rendered)?;
+    let summary = staged.commit()?;
+    Ok((rendered, Some(summary)))
+}
+
+fn write_rendered(output: &Path, rendered: &RenderedProfile) -> Result<()> {
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("创建目录失败: {}", parent.display()))?;
+    }
+    let content = rendered.to_yaml_string()?;
+    fs::write(output, content).with_context(|| format!("写入渲染配置失败: {}", output.display()))
+}
+
+fn cmd_encrypt() -> Result<()> {
+    let paths = app_paths()?;
+    let index = load_index(&paths.profile_index_file)?;
+    let (key, key_created) = match secret::load_key(&paths)? {
+        Some(key) => (key, false),
+        None => (secret::create_key(&paths)?, true),
+    };
+
+    let mut encrypted_files = 0usize;
+    for profile in &index.profiles {
+        let path = paths.profile_dir.join(&profile.file);
+        let Ok(content) = fs::read(&path) else {
+            continue;
+        };
+        if secret::is_encrypted_file(&content) {
+            continue;
+        }
+        secret::write_private_file(&path, &secret::encrypt_file_content(&key, &content)?)
+            .with_context(|| format!("写入 profile 文件失败: {}", path.display()))?;
+        encrypted_files += 1;
+    }
+    save_index_with_key(&paths.profile_index_file, &index, Some(&key))?;
+
+    let key_file = secret::key_file_path(&paths);
+    if is_json_mode() {
+        return print_json(&serde_json::json!({
+            "ok": true,
+            "action": "profile.encrypt",
+            "key_file": key_file.display().to_string(),
+            "key_created": key_created,
+            "profiles": index.profiles.len(),
+            "encrypted_files": encrypted_files,
+        }));
+    }
+
+    if key_created {
+        println!("已生成密钥文件: {}", key_file.display());
+    }
+    println!(
+        "已加密订阅 URL {} 个，profile 文件 {} 个。",
+        index.profiles.len(),
+        encrypted_files
+    );
+    println!("提示: 请妥善备份密钥文件，丢失后只能重新添加订阅。");
+    Ok(())
+}
+
+fn cmd_decrypt(args: ProfileDecryptArgs) -> Result<()> {
+    let paths = app_paths()?;
+    let key_file = secret::key_file_path(&paths);
+    let Some(key) = secret::load_key(&paths)? else {
+        bail!("未找到密钥文件: {}，profile 存储未加密", key_file.display());
+    };
+    let index = load_index(&paths.profile_index_file)?;
+
+    let mut decrypted_files = 0usize;
+    for profile in &index.profiles {
+        let path = paths.profile_dir.join(&profile.file);
+        let Ok(content) = fs::read(&path) else {
+            continue;
+        };
+        if !secret::is_encrypted_file(&content) {
+            continue;
+        }
+        let plain = secret::decrypt_file_content(&key, &content)
+            .with_context(|| format!("解密 profile 失败: {}", path.display()))?;
+        secret::write_private_file(&path, &plain)
+            .with_context(|| format!("写入 profile 文件失败: {}", path.display()))?;
+        decrypted_files += 1;
+    }
+    save_index_with_key(&paths.profile_index_file, &index, None)?;
+
+    let key_removed = !args.keep_key;
+    if key_removed {
+        fs::remove_file(&key_file)
+            .with_context(|| format!("删除密钥文件失败: {}", key_file.display()))?;
+    }
+
+    if is_json_mode() {
+        return print_json(&serde_json::json!({
+            "ok": true,
+            "action": "profile.decrypt",
+            "key_file": key_file.display().to_string(),
+            "key_removed": key_removed,
+            "profiles": index.profiles.len(),
+            "decrypted_files": decrypted_files,
+        }));
+    }
+
+    println!(
+        "已恢复明文存储: 订阅 URL {} 个，profile 文件 {} 个。",
+        index.profiles.len(),
+        decrypted_files
+    );
+    if key_removed {
+        println!("已删除密钥文件: {}", key_file.display());
+    }
+    Ok(())
+}
+
 fn cmd_validate(args: ProfileValidateArgs) -> Result<()> {
     let paths = app_paths()?;
     let index = load_index(&paths.profile_index_file)?;
@@ -364,7 +774,7 @@ fn cmd_validate(args: ProfileValidateArgs) -> Result<()> {
         );
     }
 
-    let root = load_yaml(&source_path)?;
+    let root = load_profile_yaml(&paths, &source_path)?;
     let has_proxies = key_exists(&root, "proxies") || key_exists(&root, "proxy-providers");
     let has_rules = key_exists(&root, "rules");
 
@@ -396,6 +806,56 @@ fn cmd_validate(args: ProfileValidateArgs) -> Result<()> {
     Ok(())
 }
 
+/// 按 render 的规则（本地监听默认值 + mixin）生成渲染后的配置，但不落盘。
+/// 渲染为完整 `Value`，供需要遍历全部规则的场景使用。
+pub(crate) fn render_profile_value(
+    paths: &AppPaths,
+    selected: &ProfileEntry,
+    no_mixin: bool,
+    follow_subscription_port: bool,
+) -> Result<Value> {
+    render_profile(paths, selected, no_mixin, follow_subscription_port)?.into_value()
+}
+
+/// 渲染 profile；大段 rules 以原文透传，写出时无需整体重新序列化。
+pub(crate) fn render_profile(
+    paths: &AppPaths,
+    selected: &ProfileEntry,
+    no_mixin: bool,
+    follow_subscription_port: bool,
+) -> Result<RenderedProfile> {
+    let source_path = paths.profile_dir.join(&selected.file);
+    if !source_path.exists() {
+        bail!(
+            "profile 文件不存在: {}，请先执行 `clash profile fetch --name {}`",
+            source_path.display(),
+            selected.name
+        );
+    }
+
+    let text = load_profile_text(paths, &source_path)?;
+    let mut rendered = RenderedProfile::parse(&text)
+        .with_context(|| format!("解析 YAML 失败: {}", source_path.display()))?;
+    drop(text);
+    if !follow_subscription_port {
+        apply_local_listener_defaults(&mut rendered.root);
+    }
+    crate::ui::apply_ui_selection(paths, &mut rendered.root)?;
+    if !no_mixin && paths.profile_mixin_file.exists() {
+        let mixin = load_yaml(&paths.profile_mixin_file)?;
+        // mixin 覆盖到的透传键需先解析，其余保持原文
+        if let Some(map) = mixin.as_mapping() {
+            for key in map.keys().filter_map(|k| k.as_str()) {
+                if rendered.has_passthrough(key) {
+                    rendered.materialize(key)?;
+                }
+            }
+        }
+        deep_merge(&mut rendered.root, &mixin);
+    }
+    Ok(rendered)
+}
+
 fn validate_profile_name(name: &str) -> Result<()> {
     if name.trim().is_empty() {
         bail!("profile 名称不能为空");
@@ -408,7 +868,28 @@ fn validate_profile_name(name: &str) -> Result<()> {
     Ok(())
 }
 
+/// 读取当前 home 的 profile 索引；订阅 URL 已加密时用当前 home 的密钥解密。
 pub(crate) fn load_index(path: &Path) -> Result<ProfileIndex> {
+    let index = read_index(path)?;
+    if !index_is_encrypted(&index) {
+        return Ok(index);
+    }
+    let key = secret::require_key(&app_paths()?)?;
+    decrypt_index(index, &key)
+}
+
+/// 使用指定密钥读取索引（如其他 home 的索引），已加密但未提供密钥时报错。
+pub(crate) fn load_index_with_key(path: &Path, key: Option<&StoreKey>) -> Result<ProfileIndex> {
+    let index = read_index(path)?;
+    if !index_is_encrypted(&index) {
+        return Ok(index);
+    }
+    let key =
+        key.with_context(|| format!("profile 索引已加密但未找到对应密钥: {}", path.display()))?;
+    decrypt_index(index, key)
+}
+
+fn read_index(path: &Path) -> Result<ProfileIndex> {
     if !path.exists() {
         return Ok(ProfileIndex::default());
     }
@@ -417,16 +898,80 @@ pub(crate) fn load_index(path: &Path) -> Result<ProfileIndex> {
     serde_json::from_str(&content).context("解析 profile 索引失败")
 }
 
+fn index_is_encrypted(index: &ProfileIndex) -> bool {
+    index
+        .profiles
+        .iter()
+        .any(|p| secret::is_encrypted_value(&p.url))
+}
+
+fn decrypt_index(mut index: ProfileIndex, key: &StoreKey) -> Result<ProfileIndex> {
+    for profile in &mut index.profiles {
+        if secret::is_encrypted_value(&profile.url) {
+            profile.url = secret::decrypt_value(key, &profile.url)
+                .with_context(|| format!("解密订阅 URL 失败: {}", profile.name))?;
+        }
+    }
+    Ok(index)
+}
+
+/// 存在密钥文件时订阅 URL 加密落盘；索引始终以 0600 写入。
 pub(crate) fn save_index(path: &Path, index: &ProfileIndex) -> Result<()> {
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)
-            .with_context(|| format!("创建目录失败: {}", parent.display()))?;
+    let key = secret::load_key(&app_paths()?)?;
+    save_index_with_key(path, index, key.as_ref())
+}
+
+pub(crate) fn save_index_with_key(
+    path: &Path,
+    index: &ProfileIndex,
+    key: Option<&StoreKey>,
+) -> Result<()> {
+    let mut stored = index.clone();
+    if let Some(key) = key {
+        for profile in &mut stored.profiles {
+            profile.url = secret::encrypt_value(key, &profile.url)?;
+        }
     }
-    let content = serde_json::to_string_pretty(index).context("序列化 profile 索引失败")?;
-    fs::write(path, content).with_context(|| format!("写入 profile 索引失败: {}", path.display()))
+    let content = serde_json::to_string_pretty(&stored).context("序列化 profile 索引失败")?;
+    secret::write_private_file(path, content.as_bytes())
+        .with_context(|| format!("写入 profile 索引失败: {}", path.display()))
 }
 
-fn fetch_profile_entry(entry: &mut ProfileEntry, profile_dir: &Path, _force: bool) -> Result<()> {
+/// 读取 profile 文件，已加密时用本地密钥解密。
+pub(crate) fn load_profile_yaml(paths: &AppPaths, path: &Path) -> Result<Value> {
+    let content = load_profile_text(paths, path)?;
+    serde_yaml::from_str(&content).with_context(|| format!("解析 YAML 失败: {}", path.display()))
+}
+
+/// 读取 profile 原文，必要时解密。
+fn load_profile_text(paths: &AppPaths, path: &Path) -> Result<String> {
+    let content = fs::read(path).with_context(|| format!("读取 YAML 失败: {}", path.display()))?;
+    let content = if secret::is_encrypted_file(&content) {
+        let key = secret::require_key(paths)?;
+        secret::decrypt_file_content(&key, &content)
+            .with_context(|| format!("解密 profile 失败: {}", path.display()))?
+    } else {
+        content
+    };
+    String::from_utf8(content)
+        .with_context(|| format!("profile 不是有效的 UTF-8: {}", path.display()))
+}
+
+fn write_profile_file(paths: &AppPaths, path: &Path, content: &[u8]) -> Result<()> {
+    let stored = match secret::load_key(paths)? {
+        Some(key) => secret::encrypt_file_content(&key, content)?,
+        None => content.to_vec(),
+    };
+    secret::write_private_file(path, &stored)
+        .with_context(|| format!("写入 profile 文件失败: {}", path.display()))
+}
+
+pub(crate) fn fetch_profile_entry(
+    entry: &mut ProfileEntry,
+    paths: &AppPaths,
+    _force: bool,
+) -> Result<()> {
+    let profile_dir = &paths.profile_dir;
     fs::create_dir_all(profile_dir)
         .with_context(|| format!("创建目录失败: {}", profile_dir.display()))?;
     let client = Client::builder()
@@ -435,23 +980,36 @@ fn fetch_profile_entry(entry: &mut ProfileEntry, profile_dir: &Path, _force: boo
         .build()
         .context("创建 HTTP 客户端失败")?;
 
+    let url = secret::expand_url_template(&entry.url, paths)
+        .map_err(|err| anyhow!("profile {} {err}", entry.name))?;
     let response = client
-        .get(entry.url.clone())
+        .get(url)
         .send()
-        .with_context(|| format!("请求订阅失败: {}", entry.url))?
+        .map_err(|e| e.without_url())
+        .with_context(|| format!("请求订阅失败: {}", display_url(&entry.url)))?
         .error_for_status()
-        .with_context(|| format!("订阅响应失败: {}", entry.url))?;
-
+        .map_err(|e| e.without_url())
+        .with_context(|| format!("订阅响应失败: {}", display_url(&entry.url)))?;
+
+    let userinfo = response
+        .headers()
+        .get("subscription-userinfo")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.trim().to_string());
     let body = response.text().context("读取订阅响应失败")?;
     let _: Value = serde_yaml::from_str(&body).context("订阅内容不是有效 YAML")?;
 
     let path = profile_dir.join(&entry.file);
-    fs::write(&path, body).with_context(|| format!("写入 profile 文件失败: {}", path.display()))?;
+    write_profile_file(paths, &path, body.as_bytes())?;
     entry.updated_at = Some(utils::now_unix());
+    entry.subscription_userinfo = userinfo;
     Ok(())
 }
 
-fn select_profile<'a>(index: &'a ProfileIndex, name: Option<&str>) -> Result<&'a ProfileEntry> {
+pub(crate) fn select_profile<'a>(
+    index: &'a ProfileIndex,
+    name: Option<&str>,
+) -> Result<&'a ProfileEntry> {
     let target = if let Some(v) = name {
         v.to_string()
     } else {
@@ -467,7 +1025,7 @@ fn select_profile<'a>(index: &'a ProfileIndex, name: Option<&str>) -> Result<&'a
         .with_context(|| format!("profile 不存在: {}", target))
 }
 
-fn load_yaml(path: &Path) -> Result<Value> {
+pub(crate) fn load_yaml(path: &Path) -> Result<Value> {
     let content =
         fs::read_to_string(path).with_context(|| format!("读取 YAML 失败: {}", path.display()))?;
     serde_yaml::from_str(&content).with_context(|| format!("解析 YAML 失败: {}", path.display()))
@@ -537,7 +1095,7 @@ fn key_exists(root: &Value, key: &str) -> bool {
         .unwrap_or(false)
 }
 
-fn restart_system_service(name: &str) -> Result<()> {
+pub(crate) fn restart_system_service(name: &str) -> Result<()> {
     let unit = utils::normalize_unit_name(name);
     let output = Command::new("systemctl")
         .arg("restart")
@@ -558,7 +1116,7 @@ fn restart_system_service(name: &str) -> Result<()> {
     Ok(())
 }
 
-fn ensure_service_runtime_home_matches_current(
+pub(crate) fn ensure_service_runtime_home_matches_current(
     service_name: &str,
     current_runtime_config: &Path,
 ) -> Result<()> {
@@ -603,6 +1161,23 @@ fn ensure_service_runtime_home_matches_current(
 }
 
 fn detect_service_runtime_config_path(unit: &str) -> Result<Option<PathBuf>> {
+    detect_service_exec_flag(unit, "-f")
+}
+
+fn detect_service_workdir(unit: &str) -> Result<Option<PathBuf>> {
+    detect_service_exec_flag(unit, "-d")
+}
+
+/// mihomo 工作目录：优先使用 service ExecStart 的 `-d`，探测不到时回退 runtime 目录。
+pub(crate) fn resolve_core_workdir(paths: &AppPaths, service_name: &str) -> PathBuf {
+    let unit = utils::normalize_unit_name(service_name);
+    detect_service_workdir(&unit)
+        .ok()
+        .flatten()
+        .unwrap_or_else(|| paths.runtime_dir.clone())
+}
+
+fn detect_service_exec_flag(unit: &str, flag: &str) -> Result<Option<PathBuf>> {
     let output = Command::new("systemctl")
         .arg("show")
         .arg("-p")
@@ -621,7 +1196,7 @@ fn detect_service_runtime_config_path(unit: &str) -> Result<Option<PathBuf>> {
         return Ok(None);
     }
 
-    let mut prev_is_f = false;
+    let mut prev_is_flag = false;
     for token in exec.split_whitespace() {
         let cleaned = token
             .trim_matches(|c| c == '"' || c == '\'')
@@ -630,10 +1205,10 @@ fn detect_service_runtime_config_path(unit: &str) -> Result<Option<PathBuf>> {
         if cleaned.is_empty() {
             continue;
         }
-        if prev_is_f {
+        if prev_is_flag {
             return Ok(Some(PathBuf::from(cleaned)));
         }
-        prev_is_f = cleaned == "-f";
+        prev_is_flag = cleaned == flag;
     }
 
     Ok(None)
@@ -671,19 +1246,18 @@ fn print_profile_home_hint(paths: &AppPaths) {
     println!("当前配置目录: {}", paths.config_dir.display());
     if let Ok(Some(service_runtime_config)) =
         detect_service_runtime_config_path(constants::DEFAULT_SYSTEM_SERVICE_UNIT)
+        && !path_eq(&service_runtime_config, &paths.runtime_config_file)
     {
-        if !path_eq(&service_runtime_config, &paths.runtime_config_file) {
+        println!(
+            "提示: {} 当前使用配置: {}",
+            constants::DEFAULT_SYSTEM_SERVICE_UNIT,
+            service_runtime_config.display()
+        );
+        if let Some(home) = infer_home_from_runtime_config(&service_runtime_config) {
             println!(
-                "提示: {} 当前使用配置: {}",
-                constants::DEFAULT_SYSTEM_SERVICE_UNIT,
-                service_runtime_config.display()
+                "如需管理该服务，请使用: sudo env CLASH_CLI_HOME={} clash profile list",
+                home.display()
             );
-            if let Some(home) = infer_home_from_runtime_config(&service_runtime_config) {
-                println!(
-                    "如需管理该服务，请使用: sudo env CLASH_CLI_HOME={} clash profile list",
-                    home.display()
-                );
-            }
         }
     }
 }
@@ -705,8 +1279,20 @@ fn profile_command_requires_write(command: &ProfileCommand) -> bool {
             | ProfileCommand::Use(_)
             | ProfileCommand::Fetch(_)
             | ProfileCommand::Remove(_)
+            | ProfileCommand::Rename(_)
+            | ProfileCommand::SetUrl(_)
+            | ProfileCommand::Set(_)
             | ProfileCommand::Render(_)
-    )
+            | ProfileCommand::Prefetch(_)
+            | ProfileCommand::Encrypt
+            | ProfileCommand::Decrypt(_)
+    ) || matches!(command, ProfileCommand::Serve(v) if v.install_service)
+        || matches!(
+            command,
+            ProfileCommand::Failover {
+                command: FailoverCommand::Check(v)
+            } if !v.dry_run
+        )
 }
 
 fn run_profile_with_sudo(command: &ProfileCommand) -> Result<()> {
@@ -755,6 +1341,10 @@ fn profile_command_to_cli_args(command: &ProfileCommand) -> Result<Vec<String>>
             if v.no_restart {
                 args.push("--no-restart".to_string());
             }
+            if let Some(cases) = &v.rule_cases {
+                args.push("--rule-cases".to_string());
+                args.push(cases.display().to_string());
+            }
         }
         ProfileCommand::Fetch(v) => {
             args.push("fetch".to_string());
@@ -782,6 +1372,11 @@ fn profile_command_to_cli_args(command: &ProfileCommand) -> Result<Vec<String>>
             if v.no_mixin {
                 args.push("--no-mixin".to_string());
             }
+            if v.extract_providers {
+                args.push("--extract-providers".to_string());
+                args.push("--extract-min-rules".to_string());
+                args.push(v.extract_min_rules.to_string());
+            }
             if v.follow_subscription_port {
                 args.push("--follow-subscription-port".to_string());
             }
@@ -793,6 +1388,180 @@ fn profile_command_to_cli_args(command: &ProfileCommand) -> Result<Vec<String>>
                 args.push(name.clone());
             }
         }
+        ProfileCommand::Export(v) => {
+            args.push("export".to_string());
+            if let Some(name) = &v.name {
+                args.push("--name".to_string());
+                args.push(name.clone());
+            }
+            args.push("--format".to_string());
+            args.push(v.format.as_str().to_string());
+            if let Some(output) = &v.output {
+                args.push("--output".to_string());
+                args.push(output.display().to_string());
+            }
+            if v.no_mixin {
+                args.push("--no-mixin".to_string());
+            }
+            if v.keep_provider_urls {
+                args.push("--keep-provider-urls".to_string());
+            }
+        }
+        ProfileCommand::Rename(v) => {
+            args.push("rename".to_string());
+            args.push("--name".to_string());
+            args.push(v.name.clone());
+            args.push("--to".to_string());
+            args.push(v.to.clone());
+        }
+        ProfileCommand::SetUrl(v) => {
+            args.push("set-url".to_string());
+            args.push("--name".to_string());
+            args.push(v.name.clone());
+            args.push("--url".to_string());
+            args.push(v.url.clone());
+            if v.fetch {
+                args.push("--fetch".to_string());
+            }
+        }
+        ProfileCommand::Set(v) => {
+            args.push("set".to_string());
+            args.push("--name".to_string());
+            args.push(v.name.clone());
+            if let Some(note) = &v.note {
+                args.push("--note".to_string());
+                args.push(note.clone());
+            }
+            if v.clear_note {
+                args.push("--clear-note".to_string());
+            }
+            for tag in &v.tags {
+                args.push("--tag".to_string());
+                args.push(tag.clone());
+            }
+            for tag in &v.remove_tags {
+                args.push("--remove-tag".to_string());
+                args.push(tag.clone());
+            }
+            if v.clear_tags {
+                args.push("--clear-tags".to_string());
+            }
+            if let Some(priority) = v.priority {
+                args.push("--priority".to_string());
+                args.push(priority.to_string());
+            }
+            if v.clear_priority {
+                args.push("--clear-priority".to_string());
+            }
+            if let Some(min_rules) = v.extract_providers {
+                args.push("--extract-providers".to_string());
+                args.push(min_rules.to_string());
+            }
+            if v.no_extract_providers {
+                args.push("--no-extract-providers".to_string());
+            }
+        }
+        ProfileCommand::Failover { command } => {
+            args.push("failover".to_string());
+            match command {
+                FailoverCommand::Check(v) => {
+                    args.push("check".to_string());
+                    args.push("--max-failures".to_string());
+                    args.push(v.max_failures.to_string());
+                    if v.no_probe {
+                        args.push("--no-probe".to_string());
+                    }
+                    args.push("--probe-url".to_string());
+                    args.push(v.probe_url.clone());
+                    args.push("--probe-timeout-ms".to_string());
+                    args.push(v.probe_timeout_ms.to_string());
+                    if v.dry_run {
+                        args.push("--dry-run".to_string());
+                    }
+                    args.push("--service-name".to_string());
+                    args.push(v.service_name.clone());
+                    if v.no_restart {
+                        args.push("--no-restart".to_string());
+                    }
+                    if let Some(controller) = &v.api.controller {
+                        args.push("--controller".to_string());
+                        args.push(controller.clone());
+                    }
+                    if let Some(secret) = &v.api.secret {
+                        args.push("--secret".to_string());
+                        args.push(secret.clone());
+                    }
+                    args.push("--timeout-secs".to_string());
+                    args.push(v.api.timeout_secs.to_string());
+                }
+                FailoverCommand::Status => args.push("status".to_string()),
+            }
+        }
+        ProfileCommand::Encrypt => {
+            args.push("encrypt".to_string());
+        }
+        ProfileCommand::Decrypt(v) => {
+            args.push("decrypt".to_string());
+            if v.keep_key {
+                args.push("--keep-key".to_string());
+            }
+        }
+        ProfileCommand::Serve(v) => {
+            args.push("serve".to_string());
+            if let Some(name) = &v.name {
+                args.push("--name".to_string());
+                args.push(name.clone());
+            }
+            args.push("--listen".to_string());
+            args.push(v.listen.clone());
+            if let Some(token) = &v.token {
+                args.push("--token".to_string());
+                args.push(token.clone());
+            }
+            if v.no_mixin {
+                args.push("--no-mixin".to_string());
+            }
+            if v.install_service {
+                args.push("--install-service".to_string());
+            }
+            args.push("--service-name".to_string());
+            args.push(v.service_name.clone());
+            if v.user {
+                args.push("--user".to_string());
+            }
+            if v.force {
+                args.push("--force".to_string());
+            }
+            if v.no_start {
+                args.push("--no-start".to_string());
+            }
+        }
+        ProfileCommand::Prefetch(v) => {
+            args.push("prefetch".to_string());
+            if let Some(config) = &v.config {
+                args.push("--config".to_string());
+                args.push(config.display().to_string());
+            }
+            if let Some(workdir) = &v.workdir {
+                args.push("--workdir".to_string());
+                args.push(workdir.display().to_string());
+            }
+            args.push("--service-name".to_string());
+            args.push(v.service_name.clone());
+            args.push("--mirror".to_string());
+            args.push(v.mirror.as_str().to_string());
+            args.push("--max-age-hours".to_string());
+            args.push(v.max_age_hours.to_string());
+            if v.force {
+                args.push("--force".to_string());
+            }
+            if v.rewrite_local {
+                args.push("--rewrite-local".to_string());
+            }
+            if v.no_geo {
+                args.push("--no-geo".to_string());
+            }
+        }
         ProfileCommand::Mixin { .. } => unreachable!(),
     }
     Ok(args)
@@ -961,6 +1730,11 @@ external-controller: 0.0.0.0:9091
                 file: "p1.yaml".to_string(),
                 created_at: 1,
                 updated_at: Some(2),
+                subscription_userinfo: None,
+                note: None,
+                tags: Vec::new(),
+                priority: None,
+                extract_min_rules: None,
             }],
         };
 
@@ -992,6 +1766,11 @@ external-controller: 0.0.0.0:9091
                     file: "active-p.yaml".to_string(),
                     created_at: 1,
                     updated_at: None,
+                    subscription_userinfo: None,
+                    note: None,
+                    tags: Vec::new(),
+                    priority: None,
+                    extract_min_rules: None,
                 },
                 ProfileEntry {
                     name: "other".to_string(),
@@ -999,6 +1778,11 @@ external-controller: 0.0.0.0:9091
                     file: "other.yaml".to_string(),
                     created_at: 2,
                     updated_at: None,
+                    subscription_userinfo: None,
+                    note: None,
+                    tags: Vec::new(),
+                    priority: None,
+                    extract_min_rules: None,
                 },
             ],
         };
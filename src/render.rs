@@ -0,0 +1,302 @@
+use anyhow::{Context, Result};
+use serde_yaml::{Mapping, Value};
+
+/// 以原文透传的顶层键：大型订阅中这些块序列通常有数万行。
+const PASSTHROUGH_KEYS: &[&str] = &["rules", "payload"];
+
+/// 块序列行数达到该阈值才透传，小配置仍走完整解析，输出格式保持不变。
+const PASSTHROUGH_MIN_LINES: usize = 1000;
+
+/// 未解析的顶层块序列原文（不含 `key:` 行本身）。
+#[derive(Debug, Clone)]
+struct RawSection {
+    key: String,
+    body: String,
+}
+
+/// 渲染结果：大段 rules/payload 保留原文，未被 mixin 改动时直接拼接输出，
+/// 避免整体解析为 `Value` 再重新序列化。
+#[derive(Debug, Clone)]
+pub(crate) struct RenderedProfile {
+    pub(crate) root: Value,
+    passthrough: Vec<RawSection>,
+}
+
+impl From<Value> for RenderedProfile {
+    fn from(root: Value) -> Self {
+        Self {
+            root,
+            passthrough: Vec::new(),
+        }
+    }
+}
+
+impl RenderedProfile {
+    /// 解析 YAML 文本；可透传的块序列只做行级切分，其余部分正常解析。
+    pub(crate) fn parse(text: &str) -> Result<Self> {
+        let (remainder, passthrough) = split_passthrough(text);
+        let root: Value = if passthrough.is_empty() {
+            serde_yaml::from_str(text).context("解析 YAML 失败")?
+        } else {
+            serde_yaml::from_str(&remainder).context("解析 YAML 失败")?
+        };
+        // 空文档或剩余部分为空时按空 mapping 处理，便于后续写入默认值
+        let root = match root {
+            Value::Null if !passthrough.is_empty() => Value::Mapping(Mapping::new()),
+            other => other,
+        };
+        Ok(Self { root, passthrough })
+    }
+
+    /// 将指定键的原文解析回 `root`，供 mixin 等需要修改该键的场景使用。
+    pub(crate) fn materialize(&mut self, key: &str) -> Result<()> {
+        let Some(pos) = self.passthrough.iter().position(|s| s.key == key) else {
+            return Ok(());
+        };
+        let section = self.passthrough.remove(pos);
+        let value = parse_section(&section)?;
+        if let Value::Mapping(map) = &mut self.root {
+            map.insert(Value::String(section.key), value);
+        }
+        Ok(())
+    }
+
+    /// 是否仍有以原文透传的键。
+    pub(crate) fn has_passthrough(&self, key: &str) -> bool {
+        self.passthrough.iter().any(|s| s.key == key)
+    }
+
+    /// 转为完整 `Value`（规则测试、导出等需要遍历规则的场景）。
+    pub(crate) fn into_value(mut self) -> Result<Value> {
+        let keys: Vec<String> = self.passthrough.iter().map(|s| s.key.clone()).collect();
+        for key in keys {
+            self.materialize(&key)?;
+        }
+        Ok(self.root)
+    }
+
+    /// 序列化为 YAML 文本；透传块按原文追加在末尾。
+    pub(crate) fn to_yaml_string(&self) -> Result<String> {
+        let mut out = serde_yaml::to_string(&self.root).context("序列化渲染结果失败")?;
+        if matches!(&self.root, Value::Mapping(map) if map.is_empty()) {
+            out.clear();
+        }
+        let extra: usize = self
+            .passthrough
+            .iter()
+            .map(|s| s.key.len() + s.body.len() + 2)
+            .sum();
+        out.reserve(extra);
+        for section in &self.passthrough {
+            if !out.is_empty() && !out.ends_with('\n') {
+                out.push('\n');
+            }
+            out.push_str(&section.key);
+            out.push_str(":\n");
+            out.push_str(&section.body);
+        }
+        Ok(out)
+    }
+}
+
+fn parse_section(section: &RawSection) -> Result<Value> {
+    let doc = format!("{}:\n{}", section.key, section.body);
+    let parsed: Value =
+        serde_yaml::from_str(&doc).with_context(|| format!("解析 {} 失败", section.key))?;
+    Ok(parsed
+        .get(section.key.as_str())
+        .cloned()
+        .unwrap_or(Value::Null))
+}
+
+/// 按行切出可透传的顶层块序列，返回剩余文本与切出的原文段。
+/// 遇到多文档、锚点/别名、重复键等无法安全切分的情况时不透传，交给完整解析。
+fn split_passthrough(text: &str) -> (String, Vec<RawSection>) {
+    if text
+        .lines()
+        .any(|l| l.starts_with("---") && !l.trim_end().eq("---"))
+        || text.lines().filter(|l| l.trim_end() == "---").count() > 1
+        || text.lines().any(|l| l.starts_with("..."))
+    {
+        return (String::new(), Vec::new());
+    }
+
+    let lines: Vec<&str> = text.split_inclusive('\n').collect();
+    let mut remainder = String::with_capacity(text.len().min(64 * 1024));
+    let mut sections: Vec<RawSection> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        let Some(key) = block_key(line) else {
+            remainder.push_str(line);
+            i += 1;
+            continue;
+        };
+
+        // 块内容：缩进行、顶格 `-` 列表项、空行与注释行
+        let start = i + 1;
+        let mut end = start;
+        while end < lines.len() && is_block_line(lines[end]) {
+            end += 1;
+        }
+        // 块末尾的空行/注释归还给剩余部分，保持原有结构
+        while end > start && is_blank_or_comment(lines[end - 1]) {
+            end -= 1;
+        }
+
+        let body = &lines[start..end];
+        let item_lines = body.iter().filter(|l| !is_blank_or_comment(l)).count();
+        if item_lines < PASSTHROUGH_MIN_LINES
+            || sections.iter().any(|s| s.key == key)
+            || !body.iter().any(|l| l.trim_start().starts_with('-'))
+            || body.iter().any(|l| has_anchor_or_alias(l))
+        {
+            remainder.push_str(line);
+            i += 1;
+            continue;
+        }
+
+        let mut section_body = body.concat();
+        if !section_body.ends_with('\n') {
+            section_body.push('\n');
+        }
+        sections.push(RawSection {
+            key: key.to_string(),
+            body: section_body,
+        });
+        i = end;
+    }
+
+    // 剩余部分若定义了同名键，或引用了锚点，说明切分不安全
+    if sections.is_empty()
+        || remainder.lines().any(|l| {
+            sections
+                .iter()
+                .any(|s| block_key(l).or_else(|| flow_key(l)) == Some(s.key.as_str()))
+        })
+    {
+        return (String::new(), Vec::new());
+    }
+    (remainder, sections)
+}
+
+/// 顶层 `key:`（值在后续行）的键名；仅识别透传键。
+fn block_key(line: &str) -> Option<&str> {
+    let trimmed = line.trim_end();
+    let (key, rest) = trimmed.split_once(':')?;
+    let rest = rest.trim();
+    if !(rest.is_empty() || rest.starts_with('#')) {
+        return None;
+    }
+    let key = key.trim_matches(|c| c == '"' || c == '\'');
+    PASSTHROUGH_KEYS.iter().copied().find(|k| *k == key)
+}
+
+/// 顶层 `key: value` 形式（流式写法）的键名；仅识别透传键。
+fn flow_key(line: &str) -> Option<&str> {
+    if line.starts_with([' ', '\t', '-', '#']) {
+        return None;
+    }
+    let (key, _) = line.split_once(':')?;
+    let key = key.trim().trim_matches(|c| c == '"' || c == '\'');
+    PASSTHROUGH_KEYS.iter().copied().find(|k| *k == key)
+}
+
+fn is_block_line(line: &str) -> bool {
+    line.starts_with([' ', '\t', '-']) || is_blank_or_comment(line)
+}
+
+fn is_blank_or_comment(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.is_empty() || trimmed.starts_with('#')
+}
+
+/// 粗略识别 YAML 锚点/别名（`&name` / `*name` 出现在值起始位置）。
+fn has_anchor_or_alias(line: &str) -> bool {
+    let trimmed = line.trim_start().trim_start_matches('-').trim_start();
+    let value = trimmed
+        .split_once(": ")
+        .map(|(_, v)| v.trim_start())
+        .unwrap_or(trimmed);
+    value.starts_with('&')
+        || value.starts_with('*')
+        || trimmed.starts_with('&')
+        || trimmed.starts_with('*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big_profile(rules: usize) -> String {
+        let mut text = String::from(
+            "mixed-port: 7890\n\
+             proxies:\n  - {name: a, type: ss, server: 1.1.1.1, port: 1, cipher: aes-128-gcm, password: p}\n\
+             proxy-groups:\n  - {name: PROXY, type: select, proxies: [a]}\n\
+             rules:\n",
+        );
+        for i in 0..rules {
+            text.push_str(&format!("  - DOMAIN-SUFFIX,d{i}.example.com,PROXY\n"));
+        }
+        text.push_str("  - MATCH,DIRECT\n");
+        text
+    }
+
+    #[test]
+    fn small_profile_should_not_passthrough() {
+        let rendered = RenderedProfile::parse(&big_profile(10)).expect("解析失败");
+        assert!(!rendered.has_passthrough("rules"));
+        assert_eq!(
+            rendered.root["rules"].as_sequence().map(|v| v.len()),
+            Some(11)
+        );
+    }
+
+    #[test]
+    fn passthrough_should_round_trip_and_materialize() {
+        let text = big_profile(2000);
+        let mut rendered = RenderedProfile::parse(&text).expect("解析失败");
+        assert!(rendered.has_passthrough("rules"));
+        assert!(rendered.root.get("rules").is_none());
+
+        let output = rendered.to_yaml_string().expect("序列化失败");
+        let reparsed: Value = serde_yaml::from_str(&output).expect("输出不是合法 YAML");
+        let original: Value = serde_yaml::from_str(&text).expect("原文不是合法 YAML");
+        assert_eq!(reparsed, original);
+
+        rendered.materialize("rules").expect("解析 rules 失败");
+        assert!(!rendered.has_passthrough("rules"));
+        assert_eq!(
+            rendered.root["rules"].as_sequence().map(|v| v.len()),
+            Some(2001)
+        );
+    }
+
+    #[test]
+    fn anchors_or_duplicate_keys_should_fall_back_to_full_parse() {
+        let text = format!("x: &anchor MATCH,DIRECT\n{}", big_profile(1500))
+            .replace("  - MATCH,DIRECT\n", "  - *anchor\n");
+        let rendered = RenderedProfile::parse(&text).expect("解析失败");
+        assert!(!rendered.has_passthrough("rules"));
+
+        let duplicated = format!("{}rules: []\n", big_profile(1500));
+        assert!(RenderedProfile::parse(&duplicated).is_err());
+    }
+
+    /// 10 万条规则：修改其他键后规则块不进入 `Value` 树，输出中的规则与原文逐字节一致。
+    #[test]
+    fn render_100k_rules_should_keep_rules_verbatim() {
+        let text = big_profile(100_000);
+        let mut rendered = RenderedProfile::parse(&text).expect("解析失败");
+        if let Value::Mapping(map) = &mut rendered.root {
+            map.insert(Value::from("mixed-port"), Value::from(7891));
+        }
+        let output = rendered.to_yaml_string().expect("序列化失败");
+
+        assert!(rendered.has_passthrough("rules"));
+        assert!(rendered.root.get("rules").is_none());
+        assert!(output.contains("mixed-port: 7891"));
+        let rules_block = &text[text.find("rules:\n").expect("缺少 rules")..];
+        assert!(output.ends_with(rules_block));
+    }
+}
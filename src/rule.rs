@@ -0,0 +1,775 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+
+use crate::cli::{RuleCommand, RuleTestArgs};
+use crate::constants;
+use crate::output::{is_json_mode, print_json};
+use crate::paths::{AppPaths, app_paths};
+use crate::profile::{
+    load_index, load_yaml, render_profile_value, resolve_core_workdir, select_profile,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct RuleCase {
+    pub(crate) destination: String,
+    pub(crate) expect: String,
+    #[serde(default)]
+    pub(crate) network: Option<String>,
+}
+
+/// 用例文件支持两种写法：`destination: policy` 映射，或带 network 的列表。
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum CaseFile {
+    List(Vec<RuleCase>),
+    Wrapped { cases: Vec<RuleCase> },
+    Map(serde_yaml::Mapping),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct CaseResult {
+    pub(crate) destination: String,
+    pub(crate) expect: String,
+    pub(crate) actual: Option<String>,
+    pub(crate) rule_index: Option<usize>,
+    pub(crate) rule: Option<String>,
+    pub(crate) skipped_rules: usize,
+    pub(crate) passed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum RuleKind {
+    Domain(String),
+    DomainSuffix(String),
+    DomainKeyword(String),
+    IpCidr { cidr: Cidr, no_resolve: bool },
+    DstPort(Vec<(u16, u16)>),
+    Network(String),
+    RuleSet(String),
+    Match,
+    Unsupported,
+}
+
+#[derive(Debug, Clone)]
+struct ParsedRule {
+    kind: RuleKind,
+    target: String,
+    raw: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Cidr {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+#[derive(Debug, Clone)]
+struct Destination {
+    host: Option<String>,
+    ip: Option<IpAddr>,
+    port: Option<u16>,
+    network: String,
+}
+
+/// 本地可求值的 rule-provider 内容（仅 file 类型或已落盘的 http 类型）。
+#[derive(Debug, Clone)]
+enum ProviderRules {
+    Domain(Vec<String>),
+    IpCidr(Vec<Cidr>),
+    Classical(Vec<RuleKind>),
+}
+
+enum Verdict {
+    Hit,
+    Miss,
+    Unknown,
+}
+
+pub fn run(command: RuleCommand) -> Result<()> {
+    match command {
+        RuleCommand::Test(args) => cmd_test(args),
+    }
+}
+
+fn cmd_test(args: RuleTestArgs) -> Result<()> {
+    let paths = app_paths()?;
+    let cases = load_cases(&args.cases)?;
+    let (source, root) = load_target_config(&paths, &args)?;
+    let results = evaluate_cases(
+        &root,
+        &resolve_core_workdir(&paths, constants::DEFAULT_SERVICE_NAME),
+        &cases,
+    );
+    let failed = results.iter().filter(|r| !r.passed).count();
+
+    if is_json_mode() {
+        return print_json(&serde_json::json!({
+            "ok": failed == 0,
+            "action": "rule.test",
+            "config": source,
+            "cases_file": args.cases.display().to_string(),
+            "summary": {
+                "total": results.len(),
+                "pass": results.len() - failed,
+                "fail": failed,
+            },
+            "results": results,
+        }));
+    }
+
+    println!("规则测试: 配置={}, 用例={}", source, results.len());
+    print_results(&results);
+    println!();
+    println!("测试汇总: PASS={} FAIL={}", results.len() - failed, failed);
+    if failed > 0 {
+        bail!("规则回归测试未通过: {} 个用例不符合预期", failed);
+    }
+    Ok(())
+}
+
+/// 渲染指定 profile 并执行用例，任一失败即返回错误，供 `profile use --apply` 阻断切换。
+pub(crate) fn ensure_profile_passes_cases(
+    paths: &AppPaths,
+    name: &str,
+    cases_path: &Path,
+) -> Result<()> {
+    let cases = load_cases(cases_path)?;
+    let index = load_index(&paths.profile_index_file)?;
+    let selected = select_profile(&index, Some(name))?;
+    let root = render_profile_value(paths, selected, false, false)?;
+    let results = evaluate_cases(
+        &root,
+        &resolve_core_workdir(paths, constants::DEFAULT_SERVICE_NAME),
+        &cases,
+    );
+    let failures = results.iter().filter(|r| !r.passed).collect::<Vec<_>>();
+    if failures.is_empty() {
+        if !is_json_mode() {
+            println!("规则回归测试通过: {} 个用例", results.len());
+        }
+        return Ok(());
+    }
+
+    let details = failures
+        .iter()
+        .map(|r| {
+            format!(
+                "- {}: 期望 {}，实际 {}",
+                r.destination,
+                r.expect,
+                r.actual.as_deref().unwrap_or("未命中")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    bail!(
+        "规则回归测试未通过（{}/{} 失败），已阻止应用 profile {}:\n{}",
+        failures.len(),
+        results.len(),
+        name,
+        details
+    )
+}
+
+fn load_target_config(paths: &AppPaths, args: &RuleTestArgs) -> Result<(String, Value)> {
+    if let Some(config) = &args.config {
+        return Ok((config.display().to_string(), load_yaml(config)?));
+    }
+    if args.name.is_none() && paths.runtime_config_file.exists() {
+        let root = load_yaml(&paths.runtime_config_file)?;
+        return Ok((paths.runtime_config_file.display().to_string(), root));
+    }
+    let index = load_index(&paths.profile_index_file)?;
+    let selected = select_profile(&index, args.name.as_deref())?;
+    let root = render_profile_value(paths, selected, false, false)?;
+    Ok((format!("profile:{}", selected.name), root))
+}
+
+pub(crate) fn load_cases(path: &Path) -> Result<Vec<RuleCase>> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("读取用例文件失败: {}", path.display()))?;
+    let parsed: CaseFile = serde_yaml::from_str(&content)
+        .with_context(|| format!("解析用例文件失败: {}", path.display()))?;
+    let cases = match parsed {
+        CaseFile::List(v) | CaseFile::Wrapped { cases: v } => v,
+        CaseFile::Map(map) => map
+            .iter()
+            .map(|(k, v)| {
+                Ok(RuleCase {
+                    destination: k.as_str().context("用例目标必须是字符串")?.to_string(),
+                    expect: v.as_str().context("期望策略必须是字符串")?.to_string(),
+                    network: None,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?,
+    };
+    if cases.is_empty() {
+        bail!("用例文件为空: {}", path.display());
+    }
+    Ok(cases)
+}
+
+pub(crate) fn evaluate_cases(root: &Value, base_dir: &Path, cases: &[RuleCase]) -> Vec<CaseResult> {
+    let rules = parse_rules(root);
+    let providers = load_local_providers(root, base_dir);
+    cases
+        .iter()
+        .map(|case| evaluate_case(&rules, &providers, case))
+        .collect()
+}
+
+fn evaluate_case(
+    rules: &[ParsedRule],
+    providers: &BTreeMap<String, ProviderRules>,
+    case: &RuleCase,
+) -> CaseResult {
+    let dest = parse_destination(&case.destination, case.network.as_deref());
+    let mut skipped_rules = 0usize;
+    let mut hit = None;
+
+    for (idx, rule) in rules.iter().enumerate() {
+        match match_rule(&rule.kind, &dest, providers) {
+            Verdict::Hit => {
+                hit = Some((idx, rule));
+                break;
+            }
+            Verdict::Miss => {}
+            Verdict::Unknown => skipped_rules += 1,
+        }
+    }
+
+    let actual = hit.map(|(_, r)| r.target.clone());
+    CaseResult {
+        destination: case.destination.clone(),
+        expect: case.expect.clone(),
+        passed: actual.as_deref() == Some(case.expect.as_str()),
+        actual,
+        rule_index: hit.map(|(idx, _)| idx),
+        rule: hit.map(|(_, r)| r.raw.clone()),
+        skipped_rules,
+    }
+}
+
+fn match_rule(
+    kind: &RuleKind,
+    dest: &Destination,
+    providers: &BTreeMap<String, ProviderRules>,
+) -> Verdict {
+    match kind {
+        RuleKind::RuleSet(name) => match providers.get(name) {
+            Some(provider) => match_provider(provider, dest, providers),
+            None => Verdict::Unknown,
+        },
+        RuleKind::Unsupported => Verdict::Unknown,
+        other => match_simple(other, dest),
+    }
+}
+
+fn match_simple(kind: &RuleKind, dest: &Destination) -> Verdict {
+    let host = dest.host.as_deref();
+    let hit = match kind {
+        RuleKind::Domain(v) => host == Some(v.as_str()),
+        RuleKind::DomainSuffix(v) => host.is_some_and(|h| domain_has_suffix(h, v)),
+        RuleKind::DomainKeyword(v) => host.is_some_and(|h| h.contains(v.as_str())),
+        RuleKind::IpCidr { cidr, no_resolve } => match dest.ip {
+            Some(ip) => cidr.contains(ip),
+            // 域名目标需要 DNS 解析后才能判断，本地无法确定
+            None if *no_resolve => false,
+            None => return Verdict::Unknown,
+        },
+        RuleKind::DstPort(ranges) => match dest.port {
+            Some(port) => ranges.iter().any(|(lo, hi)| (*lo..=*hi).contains(&port)),
+            None => return Verdict::Unknown,
+        },
+        RuleKind::Network(v) => v.eq_ignore_ascii_case(&dest.network),
+        RuleKind::Match => true,
+        RuleKind::RuleSet(_) | RuleKind::Unsupported => return Verdict::Unknown,
+    };
+    if hit { Verdict::Hit } else { Verdict::Miss }
+}
+
+fn match_provider(
+    provider: &ProviderRules,
+    dest: &Destination,
+    providers: &BTreeMap<String, ProviderRules>,
+) -> Verdict {
+    match provider {
+        ProviderRules::Domain(entries) => {
+            let Some(host) = dest.host.as_deref() else {
+                return Verdict::Miss;
+            };
+            if entries.iter().any(|e| domain_pattern_matches(e, host)) {
+                Verdict::Hit
+            } else {
+                Verdict::Miss
+            }
+        }
+        ProviderRules::IpCidr(entries) => match dest.ip {
+            Some(ip) if entries.iter().any(|c| c.contains(ip)) => Verdict::Hit,
+            Some(_) => Verdict::Miss,
+            None => Verdict::Unknown,
+        },
+        ProviderRules::Classical(kinds) => {
+            let mut unknown = false;
+            for kind in kinds {
+                match match_rule(kind, dest, providers) {
+                    Verdict::Hit => return Verdict::Hit,
+                    Verdict::Miss => {}
+                    Verdict::Unknown => unknown = true,
+                }
+            }
+            if unknown {
+                Verdict::Unknown
+            } else {
+                Verdict::Miss
+            }
+        }
+    }
+}
+
+fn parse_rules(root: &Value) -> Vec<ParsedRule> {
+    root.get("rules")
+        .and_then(|v| v.as_sequence())
+        .map(|seq| {
+            seq.iter()
+                .filter_map(|v| v.as_str())
+                .filter_map(parse_rule_line)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn parse_rule_line(line: &str) -> Option<ParsedRule> {
+    let parts = line.split(',').map(|s| s.trim()).collect::<Vec<_>>();
+    let rule_type = parts.first()?.to_ascii_uppercase();
+    if rule_type == "MATCH" || rule_type == "FINAL" {
+        return Some(ParsedRule {
+            kind: RuleKind::Match,
+            target: parts.get(1)?.to_string(),
+            raw: line.to_string(),
+        });
+    }
+    if parts.len() < 3 {
+        return None;
+    }
+    let kind = parse_rule_kind(&rule_type, parts[1], &parts[3..]);
+    Some(ParsedRule {
+        kind,
+        target: parts[2].to_string(),
+        raw: line.to_string(),
+    })
+}
+
+fn parse_rule_kind(rule_type: &str, payload: &str, options: &[&str]) -> RuleKind {
+    let no_resolve = options.iter().any(|o| o.eq_ignore_ascii_case("no-resolve"));
+    match rule_type {
+        "DOMAIN" => RuleKind::Domain(payload.to_ascii_lowercase()),
+        "DOMAIN-SUFFIX" => RuleKind::DomainSuffix(payload.to_ascii_lowercase()),
+        "DOMAIN-KEYWORD" => RuleKind::DomainKeyword(payload.to_ascii_lowercase()),
+        "IP-CIDR" | "IP-CIDR6" => match Cidr::parse(payload) {
+            Some(cidr) => RuleKind::IpCidr { cidr, no_resolve },
+            None => RuleKind::Unsupported,
+        },
+        "DST-PORT" => match parse_port_ranges(payload) {
+            Some(ranges) => RuleKind::DstPort(ranges),
+            None => RuleKind::Unsupported,
+        },
+        "NETWORK" => RuleKind::Network(payload.to_ascii_lowercase()),
+        "RULE-SET" => RuleKind::RuleSet(payload.to_string()),
+        _ => RuleKind::Unsupported,
+    }
+}
+
+fn parse_port_ranges(payload: &str) -> Option<Vec<(u16, u16)>> {
+    payload
+        .split('/')
+        .map(|part| match part.split_once('-') {
+            Some((lo, hi)) => Some((lo.trim().parse().ok()?, hi.trim().parse().ok()?)),
+            None => {
+                let port = part.trim().parse().ok()?;
+                Some((port, port))
+            }
+        })
+        .collect()
+}
+
+fn parse_destination(raw: &str, network: Option<&str>) -> Destination {
+    let raw = raw.trim();
+    let raw = raw
+        .split_once("://")
+        .map(|(_, rest)| rest)
+        .unwrap_or(raw)
+        .split('/')
+        .next()
+        .unwrap_or_default();
+
+    let (host, port) = if let Some(rest) = raw.strip_prefix('[') {
+        // [v6]:port
+        match rest.split_once(']') {
+            Some((h, tail)) => (h, tail.strip_prefix(':').and_then(|p| p.parse().ok())),
+            None => (rest, None),
+        }
+    } else if raw.matches(':').count() == 1 {
+        let (h, p) = raw.split_once(':').unwrap_or((raw, ""));
+        (h, p.parse().ok())
+    } else {
+        (raw, None)
+    };
+
+    let ip = host.parse::<IpAddr>().ok();
+    Destination {
+        host: if ip.is_some() {
+            None
+        } else {
+            Some(host.trim_end_matches('.').to_ascii_lowercase())
+        },
+        ip,
+        port,
+        network: network.unwrap_or("tcp").to_ascii_lowercase(),
+    }
+}
+
+fn domain_has_suffix(host: &str, suffix: &str) -> bool {
+    host == suffix
+        || host
+            .strip_suffix(suffix)
+            .is_some_and(|prefix| prefix.ends_with('.'))
+}
+
+/// 匹配 domain 行为的 rule-provider 条目：`+.a.com` / `.a.com` / `*.a.com` / `a.com`。
+fn domain_pattern_matches(pattern: &str, host: &str) -> bool {
+    let pattern = pattern.trim().to_ascii_lowercase();
+    if let Some(suffix) = pattern.strip_prefix("+.") {
+        return domain_has_suffix(host, suffix);
+    }
+    if let Some(suffix) = pattern.strip_prefix('.') {
+        return host != suffix && domain_has_suffix(host, suffix);
+    }
+    if let Some(suffix) = pattern.strip_prefix("*.") {
+        return host
+            .split_once('.')
+            .is_some_and(|(label, rest)| !label.is_empty() && rest == suffix);
+    }
+    host == pattern
+}
+
+impl Cidr {
+    fn parse(value: &str) -> Option<Self> {
+        let (addr, prefix) = match value.split_once('/') {
+            Some((a, p)) => (a.parse::<IpAddr>().ok()?, p.parse::<u8>().ok()?),
+            None => {
+                let addr = value.parse::<IpAddr>().ok()?;
+                let prefix = if addr.is_ipv4() { 32 } else { 128 };
+                (addr, prefix)
+            }
+        };
+        let max = if addr.is_ipv4() { 32 } else { 128 };
+        if prefix > max {
+            return None;
+        }
+        Some(Self { addr, prefix })
+    }
+
+    fn contains(&self, ip: IpAddr) -> bool {
+        match (self.addr, ip) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+fn load_local_providers(root: &Value, base_dir: &Path) -> BTreeMap<String, ProviderRules> {
+    let mut result = BTreeMap::new();
+    let Some(map) = root.get("rule-providers").and_then(|v| v.as_mapping()) else {
+        return result;
+    };
+    for (name, provider) in map {
+        let Some(name) = name.as_str() else {
+            continue;
+        };
+        if let Some(rules) = load_provider_rules(provider, base_dir) {
+            result.insert(name.to_string(), rules);
+        }
+    }
+    result
+}
+
+fn load_provider_rules(provider: &Value, base_dir: &Path) -> Option<ProviderRules> {
+    let behavior = provider.get("behavior")?.as_str()?.to_ascii_lowercase();
+    let format = provider
+        .get("format")
+        .and_then(|v| v.as_str())
+        .unwrap_or("yaml")
+        .to_ascii_lowercase();
+    if format == "mrs" {
+        return None;
+    }
+
+    let entries = if let Some(inline) = provider.get("payload").and_then(|v| v.as_sequence()) {
+        inline
+            .iter()
+            .filter_map(|v| v.as_str().map(|s| s.to_string()))
+            .collect::<Vec<_>>()
+    } else {
+        let path = resolve_provider_path(provider.get("path")?.as_str()?, base_dir);
+        let content = fs::read_to_string(path).ok()?;
+        parse_provider_payload(&content, &format)?
+    };
+
+    Some(match behavior.as_str() {
+        "domain" => ProviderRules::Domain(entries),
+        "ipcidr" => ProviderRules::IpCidr(entries.iter().filter_map(|e| Cidr::parse(e)).collect()),
+        "classical" => ProviderRules::Classical(
+            entries
+                .iter()
+                .map(|line| {
+                    let parts = line.split(',').map(|s| s.trim()).collect::<Vec<_>>();
+                    let rule_type = parts.first().copied().unwrap_or_default();
+                    let payload = parts.get(1).copied().unwrap_or_default();
+                    let options = parts.get(2..).unwrap_or_default();
+                    parse_rule_kind(&rule_type.to_ascii_uppercase(), payload, options)
+                })
+                .collect(),
+        ),
+        _ => return None,
+    })
+}
+
+fn parse_provider_payload(content: &str, format: &str) -> Option<Vec<String>> {
+    if format == "text" {
+        return Some(
+            content
+                .lines()
+                .map(|l| l.trim())
+                .filter(|l| !l.is_empty() && !l.starts_with('#'))
+                .map(|l| l.to_string())
+                .collect(),
+        );
+    }
+    let root: Value = serde_yaml::from_str(content).ok()?;
+    Some(
+        root.get("payload")?
+            .as_sequence()?
+            .iter()
+            .filter_map(|v| v.as_str().map(|s| s.to_string()))
+            .collect(),
+    )
+}
+
+pub(crate) fn resolve_provider_path(path: &str, base_dir: &Path) -> PathBuf {
+    let path = Path::new(path);
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        base_dir.join(path)
+    }
+}
+
+fn print_results(results: &[CaseResult]) {
+    for r in results {
+        let status = if r.passed { "PASS" } else { "FAIL" };
+        let rule = match (r.rule_index, r.rule.as_deref()) {
+            (Some(idx), Some(raw)) => format!("#{} {}", idx + 1, raw),
+            _ => "未命中任何规则".to_string(),
+        };
+        if r.passed {
+            println!("[{}] {} -> {} ({})", status, r.destination, r.expect, rule);
+        } else {
+            println!(
+                "[{}] {} -> 期望 {}，实际 {} ({})",
+                status,
+                r.destination,
+                r.expect,
+                r.actual.as_deref().unwrap_or("无"),
+                rule
+            );
+        }
+        if r.skipped_rules > 0 {
+            println!(
+                "        提示: 命中前跳过 {} 条无法本地求值的规则（GEOIP/进程/未落盘 RULE-SET 等）",
+                r.skipped_rules
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_yaml(input: &str) -> Value {
+        serde_yaml::from_str(input).expect("解析测试 YAML 失败")
+    }
+
+    fn case(destination: &str, expect: &str) -> RuleCase {
+        RuleCase {
+            destination: destination.to_string(),
+            expect: expect.to_string(),
+            network: None,
+        }
+    }
+
+    #[test]
+    fn evaluate_cases_should_follow_rule_order() {
+        let root = parse_yaml(
+            r#"
+rules:
+  - DOMAIN,exact.example.com,DIRECT
+  - DOMAIN-SUFFIX,example.com,PROXY
+  - DOMAIN-KEYWORD,google,PROXY
+  - IP-CIDR,10.0.0.0/8,DIRECT,no-resolve
+  - DST-PORT,22,SSH
+  - MATCH,FINAL-GROUP
+"#,
+        );
+        let cases = vec![
+            case("exact.example.com", "DIRECT"),
+            case("a.example.com:443", "PROXY"),
+            case("notexample.com", "FINAL-GROUP"),
+            case("www.google.co.jp", "PROXY"),
+            case("10.1.2.3", "DIRECT"),
+            case("192.168.1.1:22", "SSH"),
+        ];
+        let results = evaluate_cases(&root, Path::new("/nonexistent"), &cases);
+        for r in &results {
+            assert!(r.passed, "用例未通过: {:?}", r);
+        }
+    }
+
+    #[test]
+    fn evaluate_cases_should_report_mismatch_and_unknown_rules() {
+        let root = parse_yaml(
+            r#"
+rules:
+  - GEOIP,CN,DIRECT
+  - IP-CIDR,1.1.1.0/24,PROXY
+  - MATCH,DIRECT
+"#,
+        );
+        let results = evaluate_cases(
+            &root,
+            Path::new("/nonexistent"),
+            &[case("example.org", "PROXY")],
+        );
+        let r = &results[0];
+        assert!(!r.passed);
+        assert_eq!(r.actual.as_deref(), Some("DIRECT"));
+        assert_eq!(r.rule_index, Some(2));
+        // GEOIP 与未声明 no-resolve 的 IP-CIDR 对域名目标都无法本地判断
+        assert_eq!(r.skipped_rules, 2);
+    }
+
+    #[test]
+    fn evaluate_cases_should_use_inline_rule_provider_payload() {
+        let root = parse_yaml(
+            r#"
+rule-providers:
+  ads:
+    type: inline
+    behavior: domain
+    payload:
+      - "+.ads.example"
+  lan:
+    type: inline
+    behavior: ipcidr
+    payload:
+      - 192.168.0.0/16
+rules:
+  - RULE-SET,ads,REJECT
+  - RULE-SET,lan,DIRECT
+  - MATCH,PROXY
+"#,
+        );
+        let results = evaluate_cases(
+            &root,
+            Path::new("/nonexistent"),
+            &[
+                case("ads.example", "REJECT"),
+                case("x.ads.example", "REJECT"),
+                case("192.168.3.4", "DIRECT"),
+                case("8.8.8.8", "PROXY"),
+            ],
+        );
+        for r in &results {
+            assert!(r.passed, "用例未通过: {:?}", r);
+        }
+    }
+
+    #[test]
+    fn parse_destination_should_handle_ports_and_ipv6() {
+        let d = parse_destination("https://Example.com:8443/path", None);
+        assert_eq!(d.host.as_deref(), Some("example.com"));
+        assert_eq!(d.port, Some(8443));
+
+        let d = parse_destination("[2001:db8::1]:443", Some("udp"));
+        assert_eq!(d.ip, "2001:db8::1".parse().ok());
+        assert_eq!(d.port, Some(443));
+        assert_eq!(d.network, "udp");
+
+        let d = parse_destination("2001:db8::1", None);
+        assert!(d.ip.is_some());
+        assert_eq!(d.port, None);
+    }
+
+    #[test]
+    fn domain_pattern_matches_should_follow_mihomo_semantics() {
+        assert!(domain_pattern_matches("+.a.com", "a.com"));
+        assert!(domain_pattern_matches("+.a.com", "x.y.a.com"));
+        assert!(!domain_pattern_matches(".a.com", "a.com"));
+        assert!(domain_pattern_matches(".a.com", "x.a.com"));
+        assert!(domain_pattern_matches("*.a.com", "x.a.com"));
+        assert!(!domain_pattern_matches("*.a.com", "y.x.a.com"));
+        assert!(!domain_pattern_matches("a.com", "ba.com"));
+    }
+
+    fn ip(text: &str) -> IpAddr {
+        text.parse().expect("解析 IP 失败")
+    }
+
+    #[test]
+    fn cidr_contains_should_mask_prefix() {
+        let v4 = Cidr::parse("172.16.0.0/12").expect("解析 CIDR 失败");
+        assert!(v4.contains(ip("172.31.255.1")));
+        assert!(!v4.contains(ip("172.32.0.1")));
+        let v6 = Cidr::parse("2001:db8::/32").expect("解析 CIDR 失败");
+        assert!(v6.contains(ip("2001:db8:1::1")));
+        assert!(!v6.contains(ip("10.0.0.1")));
+        let all = Cidr::parse("0.0.0.0/0").expect("解析 CIDR 失败");
+        assert!(all.contains(ip("8.8.8.8")));
+    }
+
+    #[test]
+    fn load_cases_should_accept_mapping_and_list_forms() {
+        let dir = std::env::temp_dir().join(format!("clash_cli_rule_cases_{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("创建测试目录失败");
+        let map_file = dir.join("map.yaml");
+        fs::write(&map_file, "www.google.com: PROXY\nbaidu.com: DIRECT\n").expect("写入失败");
+        let list_file = dir.join("list.yaml");
+        fs::write(
+            &list_file,
+            "cases:\n  - destination: 8.8.8.8:53\n    expect: PROXY\n    network: udp\n",
+        )
+        .expect("写入失败");
+
+        let from_map = load_cases(&map_file).expect("解析映射用例失败");
+        assert_eq!(from_map.len(), 2);
+        assert_eq!(from_map[0].destination, "www.google.com");
+        let from_list = load_cases(&list_file).expect("解析列表用例失败");
+        assert_eq!(from_list[0].network.as_deref(), Some("udp"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,486 @@
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream, UdpSocket};
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Context, Result, anyhow};
+use clap::ValueEnum;
+use ring::rand::{SecureRandom, SystemRandom};
+use serde_yaml::Value;
+
+use crate::cli::{ExportFormat, ProfileServeArgs, ServiceTargetArgs};
+use crate::export::export_root;
+use crate::output::{is_json_mode, print_json};
+use crate::paths::{AppPaths, app_paths};
+use crate::profile::{load_index, render_profile, render_profile_value, select_profile};
+use crate::secret::write_private_file;
+use crate::service::install_custom_unit;
+use crate::utils::systemd_quote;
+
+/// 单个请求头的读取上限，避免局域网内异常客户端占用内存。
+const MAX_REQUEST_HEAD: usize = 16 * 1024;
+
+/// rendered 输出中需要去掉的控制器与认证字段（`external-controller*`、`external-ui*` 按前缀匹配）。
+const CONTROLLER_KEYS: &[&str] = &["secret", "authentication", "skip-auth-prefixes", "tls"];
+
+/// 订阅输出格式：导出格式之外额外支持完整渲染配置。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ServeFormat {
+    Rendered,
+    Export(ExportFormat),
+}
+
+struct Response {
+    status: u16,
+    reason: &'static str,
+    content_type: &'static str,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl Response {
+    fn text(status: u16, reason: &'static str, body: &str) -> Self {
+        Self {
+            status,
+            reason,
+            content_type: "text/plain; charset=utf-8",
+            headers: Vec::new(),
+            body: format!("{body}\n"),
+        }
+    }
+}
+
+pub fn run(args: ProfileServeArgs) -> Result<()> {
+    let paths = app_paths()?;
+    if args.install_service {
+        return install_service(&paths, &args);
+    }
+    let token = match &args.token {
+        Some(token) if !token.trim().is_empty() => token.trim().to_string(),
+        _ => load_or_create_token(&paths.serve_token_file)?,
+    };
+
+    let listener =
+        TcpListener::bind(&args.listen).with_context(|| format!("监听失败: {}", args.listen))?;
+    let urls = subscription_urls(&args.listen, &token);
+
+    if is_json_mode() {
+        print_json(&serde_json::json!({
+            "ok": true,
+            "action": "profile.serve",
+            "listen": args.listen,
+            "profile": args.name,
+            "urls": urls,
+            "formats": ["clash", "links", "base64", "sing-box", "rendered"],
+        }))?;
+    } else {
+        println!("订阅服务已启动: {}", args.listen);
+        for url in &urls {
+            println!("  {}", url);
+        }
+        println!("可通过 ?format=clash|links|base64|sing-box|rendered 选择输出格式，Ctrl+C 退出");
+    }
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                if let Err(err) = handle_connection(stream, &paths, &args, &token) {
+                    eprintln!("[serve] 处理请求失败: {err}");
+                }
+            }
+            Err(err) => eprintln!("[serve] 接受连接失败: {err}"),
+        }
+    }
+    Ok(())
+}
+
+fn handle_connection(
+    mut stream: TcpStream,
+    paths: &AppPaths,
+    args: &ProfileServeArgs,
+    token: &str,
+) -> Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+    stream.set_write_timeout(Some(Duration::from_secs(30)))?;
+    let peer = stream
+        .peer_addr()
+        .map(|v| v.ip().to_string())
+        .unwrap_or_else(|_| "-".to_string());
+
+    let request_line = read_request_head(&stream)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let target = parts.next().unwrap_or_default().to_string();
+
+    let response = if method != "GET" && method != "HEAD" {
+        Response::text(405, "Method Not Allowed", "method not allowed")
+    } else {
+        route(paths, args, token, &target)
+    };
+
+    eprintln!(
+        "[serve] {} {} {} -> {}",
+        peer,
+        method,
+        mask_token(&target, token),
+        response.status
+    );
+    write_response(&mut stream, &response, method == "HEAD")
+}
+
+/// 读取请求行并丢弃其余请求头。
+fn read_request_head(stream: &TcpStream) -> Result<String> {
+    let mut reader = BufReader::new(stream.take(MAX_REQUEST_HEAD as u64));
+    let mut request_line = String::new();
+    reader
+        .read_line(&mut request_line)
+        .context("读取请求行失败")?;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        let read = reader.read_line(&mut line).context("读取请求头失败")?;
+        if read == 0 || line == "\r\n" || line == "\n" {
+            break;
+        }
+    }
+    Ok(request_line.trim().to_string())
+}
+
+fn route(paths: &AppPaths, args: &ProfileServeArgs, token: &str, target: &str) -> Response {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let Some(request_token) = path.strip_prefix("/sub/") else {
+        return Response::text(404, "Not Found", "not found");
+    };
+    // 令牌错误与路径不存在返回相同结果，避免探测
+    if !constant_time_eq(request_token.as_bytes(), token.as_bytes()) {
+        return Response::text(404, "Not Found", "not found");
+    }
+
+    let format = match query_param(query, "format") {
+        Some(raw) => match parse_format(&raw) {
+            Some(v) => v,
+            None => {
+                return Response::text(
+                    400,
+                    "Bad Request",
+                    "unsupported format, expected clash|links|base64|sing-box|rendered",
+                );
+            }
+        },
+        None => ServeFormat::Export(ExportFormat::Clash),
+    };
+
+    match build_subscription(paths, args, format) {
+        Ok(response) => response,
+        Err(err) => {
+            eprintln!("[serve] 生成订阅失败: {err:#}");
+            Response::text(500, "Internal Server Error", "failed to build subscription")
+        }
+    }
+}
+
+fn build_subscription(
+    paths: &AppPaths,
+    args: &ProfileServeArgs,
+    format: ServeFormat,
+) -> Result<Response> {
+    // 每次请求重新读取索引，确保切换 active 或重新拉取后立即生效
+    let index = load_index(&paths.profile_index_file)?;
+    let selected = select_profile(&index, args.name.as_deref())?;
+
+    let (body, content_type, ext) = match format {
+        ServeFormat::Rendered => {
+            let mut rendered = render_profile(paths, selected, args.no_mixin, false)?;
+            strip_controller_keys(&mut rendered.root);
+            (
+                rendered.to_yaml_string()?,
+                "text/yaml; charset=utf-8",
+                "yaml",
+            )
+        }
+        ServeFormat::Export(export_format) => {
+            let root = render_profile_value(paths, selected, args.no_mixin, true)?;
+            let result = export_root(&root, export_format, false)?;
+            let (content_type, ext) = match export_format {
+                ExportFormat::Clash => ("text/yaml; charset=utf-8", "yaml"),
+                ExportFormat::SingBox => ("application/json; charset=utf-8", "json"),
+                ExportFormat::Links | ExportFormat::Base64 => ("text/plain; charset=utf-8", "txt"),
+            };
+            (result.content, content_type, ext)
+        }
+    };
+
+    let mut headers = vec![
+        (
+            "Content-Disposition".to_string(),
+            format!(
+                "attachment; filename*=UTF-8''{}.{}",
+                percent_encode(&selected.name),
+                ext
+            ),
+        ),
+        ("profile-update-interval".to_string(), "24".to_string()),
+        ("Cache-Control".to_string(), "no-store".to_string()),
+    ];
+    if let Some(userinfo) = &selected.subscription_userinfo {
+        headers.push(("subscription-userinfo".to_string(), userinfo.clone()));
+    }
+    Ok(Response {
+        status: 200,
+        reason: "OK",
+        content_type,
+        headers,
+        body,
+    })
+}
+
+fn write_response(stream: &mut TcpStream, response: &Response, head_only: bool) -> Result<()> {
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        response.reason,
+        response.content_type,
+        response.body.len()
+    );
+    for (key, value) in &response.headers {
+        head.push_str(&format!("{key}: {value}\r\n"));
+    }
+    head.push_str("\r\n");
+    stream
+        .write_all(head.as_bytes())
+        .context("写入响应头失败")?;
+    if !head_only {
+        stream
+            .write_all(response.body.as_bytes())
+            .context("写入响应体失败")?;
+    }
+    stream.flush().context("写入响应失败")
+}
+
+fn parse_format(raw: &str) -> Option<ServeFormat> {
+    if raw.eq_ignore_ascii_case("rendered") {
+        return Some(ServeFormat::Rendered);
+    }
+    ExportFormat::from_str(raw, true)
+        .ok()
+        .map(ServeFormat::Export)
+}
+
+fn query_param(query: &str, key: &str) -> Option<String> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('=').or(Some((pair, ""))))
+        .find(|(k, _)| *k == key)
+        .map(|(_, v)| v.to_string())
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn mask_token(target: &str, token: &str) -> String {
+    target.replace(token, "***")
+}
+
+fn percent_encode(input: &str) -> String {
+    input
+        .bytes()
+        .map(|b| {
+            if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b'~') {
+                (b as char).to_string()
+            } else {
+                format!("%{b:02X}")
+            }
+        })
+        .collect()
+}
+
+fn load_or_create_token(path: &Path) -> Result<String> {
+    if let Ok(content) = fs::read_to_string(path) {
+        let token = content.trim();
+        if !token.is_empty() {
+            return Ok(token.to_string());
+        }
+    }
+
+    let mut bytes = [0u8; 16];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| anyhow!("生成随机令牌失败"))?;
+    let token = bytes.iter().map(|b| format!("{b:02x}")).collect::<String>();
+    save_token(path, &token)?;
+    Ok(token)
+}
+
+fn save_token(path: &Path, token: &str) -> Result<()> {
+    write_private_file(path, format!("{token}\n").as_bytes())
+        .with_context(|| format!("写入令牌文件失败: {}", path.display()))
+}
+
+/// rendered 是完整运行配置，去掉控制器地址、密钥与认证信息后再下发给局域网客户端。
+fn strip_controller_keys(root: &mut Value) {
+    if let Value::Mapping(map) = root {
+        map.retain(|key, _| {
+            let key = key.as_str().unwrap_or_default();
+            !(CONTROLLER_KEYS.contains(&key)
+                || key.starts_with("external-controller")
+                || key.starts_with("external-ui"))
+        });
+    }
+}
+
+/// 监听全部地址时，用默认路由所在网卡的地址拼出局域网可访问 URL。
+fn subscription_urls(listen: &str, token: &str) -> Vec<String> {
+    let (host, port) = listen.rsplit_once(':').unwrap_or((listen, ""));
+    let mut hosts = Vec::new();
+    if host == "0.0.0.0" || host == "[::]" || host.is_empty() {
+        if let Some(ip) = primary_lan_ip() {
+            hosts.push(ip);
+        }
+        hosts.push("127.0.0.1".to_string());
+    } else {
+        hosts.push(host.to_string());
+    }
+    hosts
+        .into_iter()
+        .map(|h| format!("http://{h}:{port}/sub/{token}"))
+        .collect()
+}
+
+fn primary_lan_ip() -> Option<String> {
+    // UDP connect 只选路由，不会真正发包
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("8.8.8.8:80").ok()?;
+    socket.local_addr().ok().map(|addr| addr.ip().to_string())
+}
+
+fn install_service(paths: &AppPaths, args: &ProfileServeArgs) -> Result<()> {
+    let exe = std::env::current_exe().context("获取当前可执行文件路径失败")?;
+    let mut exec = format!(
+        "{} profile serve --listen {}",
+        systemd_quote(&exe.display().to_string()),
+        systemd_quote(&args.listen)
+    );
+    if let Some(name) = &args.name {
+        exec.push_str(&format!(" --name {}", systemd_quote(name)));
+    }
+    if args.no_mixin {
+        exec.push_str(" --no-mixin");
+    }
+    let wanted_by = if args.user {
+        "default.target"
+    } else {
+        "multi-user.target"
+    };
+    // 令牌不写入 ExecStart，服务通过 CLASH_CLI_HOME 读取同一份 serve.token
+    let token = match args.token.as_deref().map(str::trim) {
+        Some(token) if !token.is_empty() => {
+            save_token(&paths.serve_token_file, token)?;
+            token.to_string()
+        }
+        _ => load_or_create_token(&paths.serve_token_file)?,
+    };
+    let content = format!(
+        "[Unit]\n\
+         Description=clash-cli LAN subscription server\n\
+         After=network-online.target\n\
+         Wants=network-online.target\n\
+         \n\
+         [Service]\n\
+         Type=simple\n\
+         Environment={home}\n\
+         ExecStart={exec}\n\
+         Restart=on-failure\n\
+         RestartSec=3\n\
+         NoNewPrivileges=true\n\
+         \n\
+         [Install]\n\
+         WantedBy={wanted_by}\n",
+        home = systemd_quote(&format!("CLASH_CLI_HOME={}", paths.config_dir.display())),
+    );
+
+    let target = ServiceTargetArgs {
+        name: args.service_name.clone(),
+        user: args.user,
+    };
+    let unit_path = install_custom_unit(&target, &content, args.force, !args.no_start)?;
+    let urls = subscription_urls(&args.listen, &token);
+
+    if is_json_mode() {
+        return print_json(&serde_json::json!({
+            "ok": true,
+            "action": "profile.serve.install",
+            "unit_path": unit_path.display().to_string(),
+            "listen": args.listen,
+            "urls": urls,
+            "started": !args.no_start,
+        }));
+    }
+    println!("订阅服务 unit 安装完成: {}", unit_path.display());
+    if args.no_start {
+        println!("已启用开机自启，未立即启动。");
+    } else {
+        println!("服务已启动。");
+    }
+    for url in &urls {
+        println!("  {}", url);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_format_should_accept_export_formats_and_rendered() {
+        assert_eq!(
+            parse_format("sing-box"),
+            Some(ServeFormat::Export(ExportFormat::SingBox))
+        );
+        assert_eq!(
+            parse_format("Base64"),
+            Some(ServeFormat::Export(ExportFormat::Base64))
+        );
+        assert_eq!(parse_format("rendered"), Some(ServeFormat::Rendered));
+        assert_eq!(parse_format("surge"), None);
+        assert_eq!(
+            query_param("a=1&format=links", "format").as_deref(),
+            Some("links")
+        );
+        assert_eq!(query_param("format", "format").as_deref(), Some(""));
+    }
+
+    #[test]
+    fn mask_token_and_percent_encode_should_hide_and_escape() {
+        assert_eq!(
+            mask_token("/sub/abc?format=clash", "abc"),
+            "/sub/***?format=clash"
+        );
+        assert_eq!(percent_encode("香港 a"), "%E9%A6%99%E6%B8%AF%20a");
+        assert!(constant_time_eq(b"abc", b"abc"));
+        assert!(!constant_time_eq(b"abc", b"abd"));
+        assert!(!constant_time_eq(b"abc", b"ab"));
+    }
+
+    #[test]
+    fn strip_controller_keys_should_drop_secret_and_controller() {
+        let mut root: Value = serde_yaml::from_str(
+            "mixed-port: 7890\nsecret: s3cr3t\nexternal-controller: 0.0.0.0:9090\n\
+             external-controller-tls: 0.0.0.0:9443\nexternal-ui: ui\nexternal-ui-url: http://x\n\
+             authentication: [\"u:p\"]\nproxies: []\n",
+        )
+        .expect("解析测试 YAML 失败");
+        strip_controller_keys(&mut root);
+        let text = serde_yaml::to_string(&root).expect("序列化失败");
+        assert!(!text.contains("s3cr3t"));
+        assert!(!text.contains("external-"));
+        assert!(!text.contains("u:p"));
+        assert!(text.contains("mixed-port: 7890"));
+        assert_eq!(systemd_quote("a b%\"$x"), "\"a b%%\\\"$$x\"");
+    }
+}
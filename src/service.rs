@@ -1,16 +1,31 @@
 use std::fs;
+use std::os::unix::fs::PermissionsExt;
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::thread;
+use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result, bail};
 
+use crate::api::{
+    ApiContext, api_delete, api_get, api_get_with_status, api_path, build_client, load_api_context,
+    parse_proxies,
+};
 use crate::cli::{
-    ServiceCommand, ServiceInstallArgs, ServiceLogArgs, ServiceTargetArgs, ServiceUninstallArgs,
+    ServiceCommand, ServiceInstallArgs, ServiceLogArgs, ServiceResumeArgs, ServiceSleepHookArgs,
+    ServiceTargetArgs, ServiceUninstallArgs, SleepHookCommand,
 };
 use crate::constants;
 use crate::output::{is_json_mode, print_json};
 use crate::paths::app_paths;
-use crate::utils::{ensure_linux_host, normalize_unit_name};
+use crate::utils::{ensure_linux_host, normalize_unit_name, shell_quote};
+
+/// system-sleep 钩子脚本第二行标记，用于识别由 clash-cli 写入的脚本。
+const SLEEP_HOOK_MARKER: &str = "# clash-cli sleep hook";
+/// 唤醒后需要重新测速才会刷新选择的策略组类型。
+const AUTO_GROUP_KINDS: &[&str] = &["URLTest", "Fallback", "LoadBalance"];
+/// 唤醒后策略组测速的超时（毫秒）。
+const RESUME_DELAY_TIMEOUT_MS: u64 = 5000;
 
 #[derive(Debug, Clone)]
 struct CmdCapturedOutput {
@@ -29,6 +44,12 @@ pub fn run(command: ServiceCommand) -> Result<()> {
         ServiceCommand::Restart(target) => cmd_simple_action(target, "restart"),
         ServiceCommand::Status(target) => cmd_status(target),
         ServiceCommand::Log(args) => cmd_log(args),
+        ServiceCommand::SleepHook { command } => match command {
+            SleepHookCommand::Install(args) => cmd_sleep_hook_install(args),
+            SleepHookCommand::Uninstall(target) => cmd_sleep_hook_uninstall(target),
+            SleepHookCommand::Status(target) => cmd_sleep_hook_status(target),
+        },
+        ServiceCommand::Resume(args) => cmd_resume(args),
     }
 }
 
@@ -99,6 +120,15 @@ fn cmd_install(args: ServiceInstallArgs) -> Result<()> {
         }
     }
 
+    let mut sleep_hook = None;
+    if args.sleep_hook {
+        let path = install_sleep_hook(&args.target, args.force)?;
+        if !is_json_mode() {
+            println!("已安装 system-sleep 钩子: {}", path.display());
+        }
+        sleep_hook = Some(path.display().to_string());
+    }
+
     let mut started = false;
     if created_template {
         if !is_json_mode() {
@@ -124,7 +154,8 @@ fn cmd_install(args: ServiceInstallArgs) -> Result<()> {
             "binary": binary.display().to_string(),
             "enabled": enabled,
             "started": started,
-            "template_created": created_template
+            "template_created": created_template,
+            "sleep_hook": sleep_hook
         }));
     }
 
@@ -164,6 +195,18 @@ fn cmd_uninstall(args: ServiceUninstallArgs) -> Result<()> {
         println!("已完成 systemd daemon-reload。");
     }
 
+    // 仅清理 clash-cli 写入的钩子，避免误删用户脚本
+    let hook_path = sleep_hook_path(&args.target.name);
+    let mut sleep_hook_deleted = false;
+    if !args.target.user && is_managed_sleep_hook(&hook_path) {
+        fs::remove_file(&hook_path)
+            .with_context(|| format!("删除 system-sleep 钩子失败: {}", hook_path.display()))?;
+        sleep_hook_deleted = true;
+        if !is_json_mode() {
+            println!("已删除 system-sleep 钩子: {}", hook_path.display());
+        }
+    }
+
     let mut runtime_purged = false;
     if args.purge {
         if paths.runtime_dir.exists() {
@@ -192,7 +235,8 @@ fn cmd_uninstall(args: ServiceUninstallArgs) -> Result<()> {
             "unit_path": unit_path.display().to_string(),
             "unit_deleted": unit_deleted,
             "purge_requested": args.purge,
-            "runtime_purged": runtime_purged
+            "runtime_purged": runtime_purged,
+            "sleep_hook_deleted": sleep_hook_deleted
         }));
     }
 
@@ -227,10 +271,7 @@ fn cmd_status(target: ServiceTargetArgs) -> Result<()> {
     ensure_linux_host()?;
     let unit = normalize_unit_name(&target.name);
 
-    let mut args = Vec::new();
-    args.push("status".to_string());
-    args.push(unit);
-    args.push("--no-pager".to_string());
+    let args = vec!["status".to_string(), unit, "--no-pager".to_string()];
     let output = run_systemctl_raw(target.user, &args)?;
     if is_json_mode() {
         return print_json(&serde_json::json!({
@@ -297,6 +338,264 @@ fn cmd_log(args: ServiceLogArgs) -> Result<()> {
     Ok(())
 }
 
+fn cmd_sleep_hook_install(args: ServiceSleepHookArgs) -> Result<()> {
+    ensure_linux_host()?;
+    let path = install_sleep_hook(&args.target, args.force)?;
+    if is_json_mode() {
+        return print_json(&serde_json::json!({
+            "ok": true,
+            "action": "service.sleep-hook.install",
+            "unit": normalize_unit_name(&args.target.name),
+            "path": path.display().to_string()
+        }));
+    }
+    println!("已安装 system-sleep 钩子: {}", path.display());
+    println!(
+        "唤醒后将关闭旧连接并触发 provider 健康检查，controller 无响应时重启 {}。",
+        normalize_unit_name(&args.target.name)
+    );
+    Ok(())
+}
+
+fn cmd_sleep_hook_uninstall(target: ServiceTargetArgs) -> Result<()> {
+    ensure_linux_host()?;
+    let path = sleep_hook_path(&target.name);
+    let existed = path.exists();
+    if existed {
+        if !is_managed_sleep_hook(&path) {
+            bail!("{} 不是 clash-cli 写入的脚本，已跳过删除", path.display());
+        }
+        fs::remove_file(&path)
+            .with_context(|| format!("删除 system-sleep 钩子失败: {}", path.display()))?;
+    }
+    if is_json_mode() {
+        return print_json(&serde_json::json!({
+            "ok": true,
+            "action": "service.sleep-hook.uninstall",
+            "path": path.display().to_string(),
+            "removed": existed
+        }));
+    }
+    if existed {
+        println!("已删除 system-sleep 钩子: {}", path.display());
+    } else {
+        println!("system-sleep 钩子不存在，无需删除: {}", path.display());
+    }
+    Ok(())
+}
+
+fn cmd_sleep_hook_status(target: ServiceTargetArgs) -> Result<()> {
+    let path = sleep_hook_path(&target.name);
+    let installed = is_managed_sleep_hook(&path);
+    if is_json_mode() {
+        return print_json(&serde_json::json!({
+            "ok": true,
+            "action": "service.sleep-hook.status",
+            "unit": normalize_unit_name(&target.name),
+            "path": path.display().to_string(),
+            "installed": installed
+        }));
+    }
+    println!(
+        "system-sleep 钩子: {} ({})",
+        if installed { "已安装" } else { "未安装" },
+        path.display()
+    );
+    Ok(())
+}
+
+/// 唤醒后恢复流程：controller 在等待时间内无响应则重启服务，否则关闭连接并触发健康检查。
+fn cmd_resume(args: ServiceResumeArgs) -> Result<()> {
+    ensure_linux_host()?;
+    let unit = normalize_unit_name(&args.target.name);
+    let ctx = load_api_context(&args.api)?;
+    // 客户端超时需覆盖策略组测速超时
+    let client = build_client(
+        args.api
+            .timeout_secs
+            .max(RESUME_DELAY_TIMEOUT_MS.div_ceil(1000) + 5),
+    )?;
+
+    if !wait_controller(&ctx, args.wait_secs) {
+        run_systemctl_unit_action(&args.target, "restart")?;
+        if is_json_mode() {
+            return print_json(&serde_json::json!({
+                "ok": true,
+                "action": "service.resume",
+                "unit": unit,
+                "responsive": false,
+                "restarted": true
+            }));
+        }
+        println!(
+            "controller 在 {} 秒内无响应，已重启 {}",
+            args.wait_secs, unit
+        );
+        return Ok(());
+    }
+
+    let connections_closed = api_delete(&client, &ctx, "/connections").is_ok();
+    let mut checked = Vec::new();
+    let mut failed = Vec::new();
+    // 列表读取失败只记入失败项，继续后续步骤并照常输出汇总
+    let providers = proxy_provider_names(&client, &ctx).unwrap_or_else(|err| {
+        failed.push(format!("读取 proxy provider 列表失败: {err:#}"));
+        Vec::new()
+    });
+    for name in providers {
+        let path = api_path(&["providers", "proxies", &name, "healthcheck"], &[]);
+        match api_get(&client, &ctx, &path) {
+            Ok(_) => checked.push(name),
+            Err(err) => failed.push(format!("{name}: {err:#}")),
+        }
+    }
+    let mut groups_tested = Vec::new();
+    let timeout = RESUME_DELAY_TIMEOUT_MS.to_string();
+    let query = [
+        ("url", constants::DEFAULT_PROBE_URL),
+        ("timeout", timeout.as_str()),
+    ];
+    let groups = auto_group_names(&client, &ctx).unwrap_or_else(|err| {
+        failed.push(format!("读取策略组列表失败: {err:#}"));
+        Vec::new()
+    });
+    for name in groups {
+        let path = api_path(&["group", &name, "delay"], &query);
+        match api_get_with_status(&client, &ctx, &path) {
+            Ok((200, _)) => groups_tested.push(name),
+            Ok((504, _)) => failed.push(format!("{name}: 全部成员超时")),
+            Ok((status, _)) => failed.push(format!("{name}: HTTP {status}")),
+            Err(err) => failed.push(format!("{name}: {err:#}")),
+        }
+    }
+
+    if is_json_mode() {
+        return print_json(&serde_json::json!({
+            "ok": true,
+            "action": "service.resume",
+            "unit": unit,
+            "responsive": true,
+            "restarted": false,
+            "connections_closed": connections_closed,
+            "healthchecked": checked,
+            "groups_tested": groups_tested,
+            "healthcheck_failed": failed
+        }));
+    }
+    println!(
+        "controller 正常，{}",
+        if connections_closed {
+            "已关闭全部连接"
+        } else {
+            "关闭连接失败"
+        }
+    );
+    println!("已触发 provider 健康检查: {} 个", checked.len());
+    println!("已触发策略组测速: {} 个", groups_tested.len());
+    for item in &failed {
+        eprintln!("警告: 健康检查失败: {item}");
+    }
+    Ok(())
+}
+
+/// 轮询 /version，直到响应或超时；单次请求使用短超时避免挂起的内核拖住流程。
+fn wait_controller(ctx: &ApiContext, wait_secs: u64) -> bool {
+    let Ok(client) = build_client(2) else {
+        return false;
+    };
+    let deadline = Instant::now() + Duration::from_secs(wait_secs);
+    loop {
+        if api_get(&client, ctx, "/version").is_ok() {
+            return true;
+        }
+        if Instant::now() >= deadline {
+            return false;
+        }
+        thread::sleep(Duration::from_secs(1));
+    }
+}
+
+/// 需要健康检查的 proxy provider；`default` 为内置兜底 provider，跳过。
+fn proxy_provider_names(
+    client: &reqwest::blocking::Client,
+    ctx: &ApiContext,
+) -> Result<Vec<String>> {
+    let response = api_get(client, ctx, "/providers/proxies")?;
+    let mut names: Vec<String> = response
+        .get("providers")
+        .and_then(|v| v.as_object())
+        .map(|m| m.keys().filter(|k| *k != "default").cloned().collect())
+        .unwrap_or_default();
+    names.sort();
+    Ok(names)
+}
+
+/// url-test/fallback/load-balance 策略组；测速会让内核按最新延迟重新选择节点。
+fn auto_group_names(client: &reqwest::blocking::Client, ctx: &ApiContext) -> Result<Vec<String>> {
+    let proxies = parse_proxies(&api_get(client, ctx, "/proxies")?);
+    Ok(proxies
+        .into_values()
+        .filter(|info| AUTO_GROUP_KINDS.contains(&info.kind.as_str()))
+        .map(|info| info.name)
+        .collect())
+}
+
+fn sleep_hook_path(service_name: &str) -> PathBuf {
+    let unit = normalize_unit_name(service_name);
+    let stem = unit.strip_suffix(".service").unwrap_or(&unit);
+    Path::new(constants::SYSTEM_SLEEP_DIR).join(format!("clash-cli-{stem}"))
+}
+
+fn is_managed_sleep_hook(path: &Path) -> bool {
+    fs::read_to_string(path)
+        .map(|content| content.lines().nth(1) == Some(SLEEP_HOOK_MARKER))
+        .unwrap_or(false)
+}
+
+fn install_sleep_hook(target: &ServiceTargetArgs, force: bool) -> Result<PathBuf> {
+    if target.user {
+        bail!("system-sleep 钩子仅支持系统级服务（不支持 --user）");
+    }
+    let paths = app_paths()?;
+    let exe = std::env::current_exe().context("获取当前可执行文件路径失败")?;
+    let path = sleep_hook_path(&target.name);
+    if path.exists() && !is_managed_sleep_hook(&path) && !force {
+        bail!(
+            "{} 已存在且不是 clash-cli 写入的脚本，可加 --force 覆盖",
+            path.display()
+        );
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("创建目录失败: {}", parent.display()))?;
+    }
+    let content = build_sleep_hook_script(&exe, &paths.config_dir, &target.name);
+    fs::write(&path, content)
+        .with_context(|| format!("写入 system-sleep 钩子失败: {}", path.display()))?;
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755))
+        .with_context(|| format!("设置钩子权限失败: {}", path.display()))?;
+    Ok(path)
+}
+
+/// systemd-sleep 以 `pre|post <类型>` 调用；仅在唤醒（post）时后台执行 resume，避免阻塞休眠流程。
+fn build_sleep_hook_script(exe: &Path, home: &Path, service_name: &str) -> String {
+    let command = format!(
+        "env CLASH_CLI_HOME={} {} service resume --name {}",
+        shell_quote(&home.display().to_string()),
+        shell_quote(&exe.display().to_string()),
+        shell_quote(service_name)
+    );
+    format!(
+        "#!/bin/sh\n\
+         {SLEEP_HOOK_MARKER}\n\
+         # 由 `clash service sleep-hook install` 生成，请勿手动修改\n\
+         [ \"$1\" = \"post\" ] || exit 0\n\
+         if command -v logger >/dev/null 2>&1; then\n  ({command} 2>&1 | logger -t clash-cli-sleep) >/dev/null 2>&1 &\n\
+         else\n  {command} >/dev/null 2>&1 &\nfi\n\
+         exit 0\n"
+    )
+}
+
 fn run_systemctl_unit_action(target: &ServiceTargetArgs, action: &str) -> Result<()> {
     let unit = normalize_unit_name(&target.name);
     let args = vec![action.to_string(), unit];
@@ -304,10 +603,10 @@ fn run_systemctl_unit_action(target: &ServiceTargetArgs, action: &str) -> Result
 }
 
 fn run_systemctl_unit_action_best_effort(target: &ServiceTargetArgs, action: &str, msg: &str) {
-    if let Err(err) = run_systemctl_unit_action(target, action) {
-        if !is_json_mode() {
-            eprintln!("警告: {}: {}", msg, err);
-        }
+    if let Err(err) = run_systemctl_unit_action(target, action)
+        && !is_json_mode()
+    {
+        eprintln!("警告: {}: {}", msg, err);
     }
 }
 
@@ -342,6 +641,34 @@ fn run_systemctl_raw(user: bool, args: &[String]) -> Result<CmdCapturedOutput> {
     Ok(CmdCapturedOutput { stdout, stderr })
 }
 
+/// 写入自定义 unit（如订阅服务），daemon-reload 后启用并按需启动。
+pub(crate) fn install_custom_unit(
+    target: &ServiceTargetArgs,
+    content: &str,
+    force: bool,
+    start: bool,
+) -> Result<PathBuf> {
+    ensure_linux_host()?;
+    let unit_name = normalize_unit_name(&target.name);
+    let unit_path = resolve_unit_path(target, &unit_name)?;
+    if unit_path.exists() && !force {
+        bail!("unit 已存在: {}，如需覆盖请加 --force", unit_path.display());
+    }
+    if let Some(parent) = unit_path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("创建目录失败: {}", parent.display()))?;
+    }
+    fs::write(&unit_path, content)
+        .with_context(|| format!("写入 unit 文件失败: {}", unit_path.display()))?;
+
+    run_systemctl_raw(target.user, &["daemon-reload".to_string()])?;
+    run_systemctl_unit_action(target, "enable")?;
+    if start {
+        run_systemctl_unit_action(target, "restart")?;
+    }
+    Ok(unit_path)
+}
+
 fn resolve_unit_path(target: &ServiceTargetArgs, unit_name: &str) -> Result<PathBuf> {
     if target.user {
         let home = dirs::home_dir().context("无法获取 home 目录")?;
@@ -407,3 +734,31 @@ fn default_runtime_config() -> String {
         constants::DEFAULT_CONTROLLER,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sleep_hook_should_run_resume_only_after_wakeup() {
+        assert_eq!(
+            sleep_hook_path("clash-mihomo"),
+            Path::new(constants::SYSTEM_SLEEP_DIR).join("clash-cli-clash-mihomo")
+        );
+        assert_eq!(
+            sleep_hook_path("clash-mihomo.service"),
+            sleep_hook_path("clash-mihomo")
+        );
+
+        let script = build_sleep_hook_script(
+            Path::new("/usr/local/bin/clash"),
+            Path::new("/etc/clash-cli"),
+            "clash-mihomo",
+        );
+        assert_eq!(script.lines().nth(1), Some(SLEEP_HOOK_MARKER));
+        assert!(script.contains("[ \"$1\" = \"post\" ] || exit 0"));
+        assert!(script.contains(
+            "env CLASH_CLI_HOME='/etc/clash-cli' '/usr/local/bin/clash' service resume --name 'clash-mihomo'"
+        ));
+    }
+}
@@ -18,7 +18,10 @@ use crate::cli::{
 use crate::core;
 use crate::output::is_json_mode;
 use crate::paths::app_paths;
-use crate::profile::{self, ProfileEntry, ProfileIndex, load_index, save_index};
+use crate::profile::{
+    self, ProfileEntry, ProfileIndex, load_index, load_index_with_key, save_index,
+};
+use crate::secret::{self, StoreKey};
 use crate::service;
 use crate::tun;
 use crate::utils;
@@ -73,6 +76,8 @@ fn cmd_init(args: SetupInitArgs) -> Result<()> {
         output: None,
         no_mixin: false,
         follow_subscription_port: false,
+        extract_providers: false,
+        extract_min_rules: 20,
     }))?;
 
     service::run(ServiceCommand::Install(ServiceInstallArgs {
@@ -86,6 +91,7 @@ fn cmd_init(args: SetupInitArgs) -> Result<()> {
         force: true,
         no_enable: false,
         no_start: false,
+        sleep_hook: false,
     }))?;
 
     if args.no_tun {
@@ -125,6 +131,12 @@ struct UnifyStats {
     missing_files: usize,
 }
 
+/// 来源与目标 home 各自的 profile 存储密钥（未加密时为 None）。
+struct ProfileKeys<'a> {
+    source: Option<&'a StoreKey>,
+    dest: Option<&'a StoreKey>,
+}
+
 #[derive(Default)]
 struct LinkStats {
     linked: usize,
@@ -148,6 +160,7 @@ fn cmd_unify(args: SetupUnifyArgs) -> Result<()> {
     fs::create_dir_all(&paths.profile_dir).context("创建目标 profile 目录失败")?;
 
     let mut index = load_index(&paths.profile_index_file)?;
+    let dest_key = secret::load_key(&paths)?;
     let mut stats = UnifyStats::default();
     let mut warnings = Vec::<String>::new();
     let source_dirs = discover_source_config_dirs(&paths.config_dir)?;
@@ -164,8 +177,17 @@ fn cmd_unify(args: SetupUnifyArgs) -> Result<()> {
         if !src_index_file.exists() {
             continue;
         }
-        let src_index = load_index(&src_index_file)
+        // 来源 home 使用自己的 store.key；没有时回退到当前 home 的密钥（如共用 CLASH_CLI_KEY_FILE）
+        let src_key = match secret::load_key_from(&src_dir.join("store.key"))? {
+            Some(key) => Some(key),
+            None => secret::load_key(&paths)?,
+        };
+        let src_index = load_index_with_key(&src_index_file, src_key.as_ref())
             .with_context(|| format!("读取来源 profile 索引失败: {}", src_index_file.display()))?;
+        let keys = ProfileKeys {
+            source: src_key.as_ref(),
+            dest: dest_key.as_ref(),
+        };
         println!("发现来源目录: {}", src_dir.display());
         if candidate_active.is_none() {
             candidate_active = src_index.active.clone();
@@ -176,6 +198,7 @@ fn cmd_unify(args: SetupUnifyArgs) -> Result<()> {
                 &entry,
                 &src_profile_dir,
                 &paths.profile_dir,
+                &keys,
                 &mut index,
                 &mut stats,
                 &mut warnings,
@@ -183,12 +206,11 @@ fn cmd_unify(args: SetupUnifyArgs) -> Result<()> {
         }
     }
 
-    if index.active.is_none() {
-        if let Some(active) = candidate_active {
-            if index.profiles.iter().any(|p| p.name == active) {
-                index.active = Some(active);
-            }
-        }
+    if index.active.is_none()
+        && let Some(active) = candidate_active
+        && index.profiles.iter().any(|p| p.name == active)
+    {
+        index.active = Some(active);
     }
     if index.active.is_none() && !index.profiles.is_empty() {
         index.active = Some(index.profiles[0].name.clone());
@@ -231,6 +253,7 @@ fn cmd_unify(args: SetupUnifyArgs) -> Result<()> {
         fetch: true,
         service_name: args.service_name.clone(),
         no_restart: false,
+        rule_cases: None,
     }))?;
     println!(
         "已拉取最新订阅并渲染重启服务: {}.service",
@@ -261,6 +284,7 @@ fn ensure_profile_ready(name: &str, url: &str, service_name: &str) -> Result<()>
                     fetch: false,
                     service_name: service_name.to_string(),
                     no_restart: true,
+                    rule_cases: None,
                 }))?;
                 return Ok(());
             }
@@ -407,13 +431,14 @@ fn amd64_variant_str(v: Amd64Variant) -> &'static str {
 fn discover_source_config_dirs(dest_dir: &Path) -> Result<Vec<PathBuf>> {
     let mut result = Vec::<PathBuf>::new();
 
-    if let Ok(user) = env::var("SUDO_USER") {
-        if !user.is_empty() && user != "root" {
-            if let Some(home) = lookup_home_by_user(&user)? {
-                result.push(home.join(".config").join("clash-cli"));
-            } else {
-                result.push(PathBuf::from(format!("/home/{user}/.config/clash-cli")));
-            }
+    if let Ok(user) = env::var("SUDO_USER")
+        && !user.is_empty()
+        && user != "root"
+    {
+        if let Some(home) = lookup_home_by_user(&user)? {
+            result.push(home.join(".config").join("clash-cli"));
+        } else {
+            result.push(PathBuf::from(format!("/home/{user}/.config/clash-cli")));
         }
     }
 
@@ -460,6 +485,7 @@ fn merge_profile_entry(
     entry: &ProfileEntry,
     src_profile_dir: &Path,
     dest_profile_dir: &Path,
+    keys: &ProfileKeys,
     index: &mut ProfileIndex,
     stats: &mut UnifyStats,
     warnings: &mut Vec<String>,
@@ -489,13 +515,7 @@ fn merge_profile_entry(
         }
         let dst_file = dest_profile_dir.join(&existing.file);
         if !dst_file.exists() {
-            fs::copy(&src_file, &dst_file).with_context(|| {
-                format!(
-                    "复制 profile 文件失败: {} -> {}",
-                    src_file.display(),
-                    dst_file.display()
-                )
-            })?;
+            import_profile_file(&src_file, &dst_file, keys)?;
         }
         stats.existed += 1;
         return Ok(());
@@ -508,16 +528,36 @@ fn merge_profile_entry(
         imported.file = format!("{}-imported.yaml", imported.name);
         dst_file = dest_profile_dir.join(&imported.file);
     }
-    fs::copy(&src_file, &dst_file).with_context(|| {
+    import_profile_file(&src_file, &dst_file, keys)?;
+    index.profiles.push(imported);
+    stats.imported += 1;
+    Ok(())
+}
+
+/// 用来源密钥解密、按目标 home 的密钥重新加密，并以 0600 写入目标 profile 文件。
+fn import_profile_file(src_file: &Path, dst_file: &Path, keys: &ProfileKeys) -> Result<()> {
+    let content = fs::read(src_file)
+        .with_context(|| format!("读取来源 profile 文件失败: {}", src_file.display()))?;
+    let plain = if secret::is_encrypted_file(&content) {
+        let key = keys
+            .source
+            .with_context(|| format!("来源 profile 已加密但未找到密钥: {}", src_file.display()))?;
+        secret::decrypt_file_content(key, &content)
+            .with_context(|| format!("解密来源 profile 失败: {}", src_file.display()))?
+    } else {
+        content
+    };
+    let stored = match keys.dest {
+        Some(key) => secret::encrypt_file_content(key, &plain)?,
+        None => plain,
+    };
+    secret::write_private_file(dst_file, &stored).with_context(|| {
         format!(
             "复制 profile 文件失败: {} -> {}",
             src_file.display(),
             dst_file.display()
         )
-    })?;
-    index.profiles.push(imported);
-    stats.imported += 1;
-    Ok(())
+    })
 }
 
 fn link_source_dirs_to_system(
@@ -606,3 +646,59 @@ fn path_eq(a: &Path, b: &Path) -> bool {
         _ => false,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::fs::PermissionsExt;
+
+    use super::*;
+
+    fn test_key(dir: &Path, name: &str, byte: u8) -> StoreKey {
+        let path = dir.join(name);
+        fs::write(&path, format!("{}\n", format!("{byte:02x}").repeat(32)))
+            .expect("写入测试密钥失败");
+        secret::load_key_from(&path)
+            .expect("读取测试密钥失败")
+            .expect("测试密钥不存在")
+    }
+
+    #[test]
+    fn import_profile_file_should_reencrypt_with_destination_key() {
+        let dir = std::env::temp_dir().join(format!("clash_cli_unify_{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("创建测试目录失败");
+        let src_key = test_key(&dir, "src.key", 0x11);
+        let dest_key = test_key(&dir, "dest.key", 0x22);
+        let src = dir.join("src.yaml");
+        let dst = dir.join("dst.yaml");
+        fs::write(
+            &src,
+            secret::encrypt_file_content(&src_key, b"proxies: []\n").expect("加密失败"),
+        )
+        .expect("写入来源文件失败");
+
+        let keys = ProfileKeys {
+            source: Some(&src_key),
+            dest: Some(&dest_key),
+        };
+        import_profile_file(&src, &dst, &keys).expect("导入 profile 失败");
+        let stored = fs::read(&dst).expect("读取导入文件失败");
+        assert!(secret::decrypt_file_content(&src_key, &stored).is_err());
+        assert_eq!(
+            secret::decrypt_file_content(&dest_key, &stored).expect("解密失败"),
+            b"proxies: []\n"
+        );
+        let mode = fs::metadata(&dst)
+            .expect("读取文件元数据失败")
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        // 来源已加密但缺少密钥时报错，而不是原样复制密文
+        let missing = ProfileKeys {
+            source: None,
+            dest: None,
+        };
+        assert!(import_profile_file(&src, &dir.join("other.yaml"), &missing).is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
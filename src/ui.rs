@@ -0,0 +1,493 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+
+use crate::cli::{MirrorSource, UiCommand, UiInstallArgs, UiTargetArgs, UiUseArgs};
+use crate::constants;
+use crate::http::{
+    build_http_client, download_candidates, download_with_fallback, ensure_non_empty,
+};
+use crate::output::{is_json_mode, print_json};
+use crate::paths::{AppPaths, app_paths};
+use crate::profile::{render_active_to_runtime, resolve_core_workdir};
+
+struct Dashboard {
+    name: &'static str,
+    url: &'static str,
+}
+
+const DASHBOARDS: &[Dashboard] = &[
+    Dashboard {
+        name: "metacubexd",
+        url: constants::UI_METACUBEXD_URL,
+    },
+    Dashboard {
+        name: "yacd-meta",
+        url: constants::UI_YACD_META_URL,
+    },
+    Dashboard {
+        name: "zashboard",
+        url: constants::UI_ZASHBOARD_URL,
+    },
+];
+
+/// 面板选择状态：render 时据此覆盖 external-ui-name/url。
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct UiState {
+    selected: Option<String>,
+    /// 面板名 -> 安装来源地址（写入 external-ui-url，供内核后续升级面板）
+    #[serde(default)]
+    sources: BTreeMap<String, String>,
+}
+
+pub fn run(command: UiCommand) -> Result<()> {
+    match command {
+        UiCommand::List(args) => cmd_list(args),
+        UiCommand::Install(args) => cmd_install(args),
+        UiCommand::Use(args) => cmd_use(args),
+    }
+}
+
+fn cmd_list(args: UiTargetArgs) -> Result<()> {
+    let paths = app_paths()?;
+    let ui_root = ui_root_dir(&paths, &args);
+    let state = load_state(&paths.ui_state_file)?;
+    let installed = installed_dashboards(&ui_root);
+
+    let mut names = DASHBOARDS
+        .iter()
+        .map(|d| d.name.to_string())
+        .collect::<Vec<_>>();
+    for name in installed.iter().chain(state.sources.keys()) {
+        if !names.contains(name) {
+            names.push(name.clone());
+        }
+    }
+    let selected = selected_name(&state);
+
+    if is_json_mode() {
+        let items = names
+            .iter()
+            .map(|name| {
+                serde_json::json!({
+                    "name": name,
+                    "builtin": builtin_dashboard(name).is_some(),
+                    "installed": installed.contains(name),
+                    "selected": *name == selected,
+                    "path": ui_root.join(name).display().to_string(),
+                    "url": source_url(&state, name),
+                })
+            })
+            .collect::<Vec<_>>();
+        return print_json(&serde_json::json!({
+            "ok": true,
+            "action": "ui.list",
+            "ui_dir": ui_root.display().to_string(),
+            "selected": selected,
+            "items": items,
+        }));
+    }
+
+    println!("面板目录: {}", ui_root.display());
+    for name in &names {
+        let mut tags = Vec::new();
+        if builtin_dashboard(name).is_some() {
+            tags.push("内置");
+        }
+        tags.push(if installed.contains(name) {
+            "已安装"
+        } else {
+            "未安装"
+        });
+        println!(
+            "{} {:<12} ({})",
+            if *name == selected { "*" } else { " " },
+            name,
+            tags.join(", ")
+        );
+    }
+    Ok(())
+}
+
+fn cmd_install(args: UiInstallArgs) -> Result<()> {
+    validate_ui_name(&args.name)?;
+    let paths = app_paths()?;
+    let ui_root = ui_root_dir(&paths, &args.target);
+    fs::create_dir_all(&ui_root)
+        .with_context(|| format!("创建面板目录失败: {}", ui_root.display()))?;
+    let dest = ui_root.join(&args.name);
+
+    let builtin = builtin_dashboard(&args.name);
+    let remote_url = args.url.clone().or(builtin.map(|d| d.url.to_string()));
+    let (source, downloaded) = match &args.archive {
+        Some(archive) => {
+            extract_dashboard(archive, &dest)?;
+            (archive.display().to_string(), None)
+        }
+        None => {
+            let Some(url) = remote_url.as_deref() else {
+                bail!(
+                    "未知面板: {}，自定义面板请通过 --archive 或 --url 指定 zip 包",
+                    args.name
+                );
+            };
+            let archive = ui_root.join(format!(".{}.{}.zip", args.name, std::process::id()));
+            let client = build_http_client()?;
+            let candidates = download_candidates(url, args.mirror);
+            let used = download_with_fallback(&client, &candidates, &archive, ensure_non_empty)?;
+            let result = extract_dashboard(&archive, &dest);
+            let _ = fs::remove_file(&archive);
+            result?;
+            (used.clone(), Some(used))
+        }
+    };
+
+    let mut state = load_state(&paths.ui_state_file)?;
+    if let Some(url) = &remote_url {
+        // 与默认值保持一致：github 地址走 ghfast，便于内核自行升级面板
+        let config_url = download_candidates(url, MirrorSource::Auto)
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| url.clone());
+        state.sources.insert(args.name.clone(), config_url);
+    }
+    if args.use_ui {
+        state.selected = Some(args.name.clone());
+    }
+    save_state(&paths.ui_state_file, &state)?;
+    let rendered = if args.use_ui {
+        render_active_to_runtime(&paths)?
+    } else {
+        None
+    };
+
+    if is_json_mode() {
+        return print_json(&serde_json::json!({
+            "ok": true,
+            "action": "ui.install",
+            "name": args.name,
+            "path": dest.display().to_string(),
+            "source": source,
+            "downloaded_from": downloaded,
+            "selected": args.use_ui,
+            "rendered_profile": rendered,
+        }));
+    }
+
+    println!("面板已安装: {} -> {}", args.name, dest.display());
+    println!("来源: {}", source);
+    if args.use_ui {
+        print_use_result(&args.name, rendered.as_deref());
+    } else {
+        println!(
+            "提示: 执行 `clash ui use --name {}` 切换到该面板",
+            args.name
+        );
+    }
+    Ok(())
+}
+
+fn cmd_use(args: UiUseArgs) -> Result<()> {
+    validate_ui_name(&args.name)?;
+    let paths = app_paths()?;
+    let ui_root = ui_root_dir(&paths, &args.target);
+    let mut state = load_state(&paths.ui_state_file)?;
+
+    let installed = ui_root.join(&args.name).join("index.html").exists();
+    let url = source_url(&state, &args.name);
+    if !installed && url.is_none() {
+        bail!(
+            "面板未安装: {}，请先执行 `clash ui install --name {} --archive <zip>`",
+            args.name,
+            args.name
+        );
+    }
+
+    state.selected = Some(args.name.clone());
+    save_state(&paths.ui_state_file, &state)?;
+    let rendered = if args.no_render {
+        None
+    } else {
+        render_active_to_runtime(&paths)?
+    };
+
+    if is_json_mode() {
+        return print_json(&serde_json::json!({
+            "ok": true,
+            "action": "ui.use",
+            "name": args.name,
+            "installed": installed,
+            "url": url,
+            "rendered_profile": rendered,
+        }));
+    }
+
+    print_use_result(&args.name, rendered.as_deref());
+    if !installed {
+        println!(
+            "提示: 面板尚未安装，内核将按 external-ui-url 自行下载；离线环境请使用 `clash ui install --archive`"
+        );
+    }
+    Ok(())
+}
+
+fn print_use_result(name: &str, rendered: Option<&str>) {
+    println!("已切换面板: {}", name);
+    match rendered {
+        Some(profile) => {
+            println!("已重新渲染 runtime 配置: profile={}", profile);
+            println!("提示: 重启服务后生效（clash service restart）");
+        }
+        None => println!("提示: 下次执行 `clash profile render` 时生效"),
+    }
+}
+
+/// render 时调用：按已选择的面板覆盖 external-ui 相关字段。
+pub(crate) fn apply_ui_selection(paths: &AppPaths, root: &mut Value) -> Result<()> {
+    if !paths.ui_state_file.exists() {
+        return Ok(());
+    }
+    let state = load_state(&paths.ui_state_file)?;
+    let Some(name) = state.selected.clone() else {
+        return Ok(());
+    };
+    let Some(map) = root.as_mapping_mut() else {
+        return Ok(());
+    };
+    map.insert(
+        Value::String("external-ui".to_string()),
+        Value::String(constants::DEFAULT_EXTERNAL_UI.to_string()),
+    );
+    map.insert(
+        Value::String("external-ui-name".to_string()),
+        Value::String(name.clone()),
+    );
+    match source_url(&state, &name) {
+        Some(url) => {
+            map.insert(
+                Value::String("external-ui-url".to_string()),
+                Value::String(url),
+            );
+        }
+        // 本地导入的自定义面板没有远程来源，避免内核用错误地址覆盖
+        None => {
+            map.remove("external-ui-url");
+        }
+    }
+    Ok(())
+}
+
+fn ui_root_dir(paths: &AppPaths, args: &UiTargetArgs) -> PathBuf {
+    args.workdir
+        .clone()
+        .unwrap_or_else(|| resolve_core_workdir(paths, &args.service_name))
+        .join(constants::DEFAULT_EXTERNAL_UI)
+}
+
+fn builtin_dashboard(name: &str) -> Option<&'static Dashboard> {
+    DASHBOARDS.iter().find(|d| d.name == name)
+}
+
+fn selected_name(state: &UiState) -> String {
+    state
+        .selected
+        .clone()
+        .unwrap_or_else(|| constants::DEFAULT_EXTERNAL_UI_NAME.to_string())
+}
+
+fn source_url(state: &UiState, name: &str) -> Option<String> {
+    state.sources.get(name).cloned().or_else(|| {
+        builtin_dashboard(name).map(|d| {
+            download_candidates(d.url, MirrorSource::Auto)
+                .into_iter()
+                .next()
+                .unwrap_or_else(|| d.url.to_string())
+        })
+    })
+}
+
+fn validate_ui_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.');
+    if !valid {
+        bail!("面板名称无效: {name}（仅允许字母、数字、-、_、.）");
+    }
+    Ok(())
+}
+
+fn installed_dashboards(ui_root: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(ui_root) else {
+        return Vec::new();
+    };
+    let mut names = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().join("index.html").is_file())
+        .filter_map(|e| e.file_name().to_str().map(|s| s.to_string()))
+        .filter(|name| !name.starts_with('.'))
+        .collect::<Vec<_>>();
+    names.sort();
+    names
+}
+
+fn load_state(path: &Path) -> Result<UiState> {
+    if !path.exists() {
+        return Ok(UiState::default());
+    }
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("读取面板状态失败: {}", path.display()))?;
+    serde_json::from_str(&content).with_context(|| format!("解析面板状态失败: {}", path.display()))
+}
+
+fn save_state(path: &Path, state: &UiState) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("创建目录失败: {}", parent.display()))?;
+    }
+    let content = serde_json::to_string_pretty(state).context("序列化面板状态失败")?;
+    fs::write(path, content).with_context(|| format!("写入面板状态失败: {}", path.display()))
+}
+
+/// 解压到同级临时目录，定位 index.html 所在目录后整体替换目标目录。
+fn extract_dashboard(archive: &Path, dest: &Path) -> Result<()> {
+    let file =
+        File::open(archive).with_context(|| format!("打开 zip 包失败: {}", archive.display()))?;
+    let mut zip = zip::ZipArchive::new(file)
+        .with_context(|| format!("解析 zip 包失败: {}", archive.display()))?;
+
+    let name = dest
+        .file_name()
+        .and_then(|v| v.to_str())
+        .context("面板目录名无效")?;
+    let staging = dest.with_file_name(format!(".{}.{}.extract", name, std::process::id()));
+    if staging.exists() {
+        fs::remove_dir_all(&staging)
+            .with_context(|| format!("清理临时目录失败: {}", staging.display()))?;
+    }
+
+    let result = (|| -> Result<()> {
+        for i in 0..zip.len() {
+            let mut entry = zip.by_index(i).context("读取 zip 条目失败")?;
+            // 跳过绝对路径或包含 .. 的条目，防止写出目标目录
+            let Some(relative) = entry.enclosed_name() else {
+                continue;
+            };
+            let out_path = staging.join(relative);
+            if entry.is_dir() {
+                fs::create_dir_all(&out_path)
+                    .with_context(|| format!("创建目录失败: {}", out_path.display()))?;
+                continue;
+            }
+            if let Some(parent) = out_path.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("创建目录失败: {}", parent.display()))?;
+            }
+            let mut out = File::create(&out_path)
+                .with_context(|| format!("创建文件失败: {}", out_path.display()))?;
+            io::copy(&mut entry, &mut out)
+                .with_context(|| format!("解压文件失败: {}", out_path.display()))?;
+        }
+
+        let Some(ui_dir) = find_index_dir(&staging, 3) else {
+            bail!("zip 包中未找到 index.html: {}", archive.display());
+        };
+        if dest.exists() {
+            fs::remove_dir_all(dest)
+                .with_context(|| format!("删除旧面板失败: {}", dest.display()))?;
+        }
+        fs::rename(&ui_dir, dest).with_context(|| format!("安装面板失败: {}", dest.display()))
+    })();
+
+    if staging.exists() {
+        let _ = fs::remove_dir_all(&staging);
+    }
+    result
+}
+
+fn find_index_dir(dir: &Path, depth: usize) -> Option<PathBuf> {
+    if dir.join("index.html").is_file() {
+        return Some(dir.to_path_buf());
+    }
+    if depth == 0 {
+        return None;
+    }
+    let mut subdirs = fs::read_dir(dir)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_dir() && !p.ends_with("__MACOSX"))
+        .collect::<Vec<_>>();
+    subdirs.sort();
+    subdirs
+        .iter()
+        .find_map(|sub| find_index_dir(sub, depth - 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    fn write_zip(path: &Path, entries: &[(&str, &str)]) {
+        let file = File::create(path).expect("创建测试 zip 失败");
+        let mut writer = zip::ZipWriter::new(file);
+        let options = zip::write::SimpleFileOptions::default();
+        for (name, content) in entries {
+            writer
+                .start_file(*name, options)
+                .expect("写入 zip 条目失败");
+            writer
+                .write_all(content.as_bytes())
+                .expect("写入 zip 内容失败");
+        }
+        writer.finish().expect("完成 zip 失败");
+    }
+
+    #[test]
+    fn extract_dashboard_should_strip_top_dir_and_skip_unsafe_entries() {
+        let dir = std::env::temp_dir().join(format!("clash_cli_ui_{}", std::process::id()));
+        fs::create_dir_all(&dir).expect("创建临时目录失败");
+        let archive = dir.join("ui.zip");
+        write_zip(
+            &archive,
+            &[
+                ("metacubexd-gh-pages/index.html", "<html></html>"),
+                ("metacubexd-gh-pages/assets/app.js", "js"),
+                ("../escape.txt", "nope"),
+            ],
+        );
+
+        let dest = dir.join("ui").join("metacubexd");
+        fs::create_dir_all(dest.parent().unwrap()).expect("创建 ui 目录失败");
+        extract_dashboard(&archive, &dest).expect("解压面板失败");
+
+        assert!(dest.join("index.html").is_file());
+        assert!(dest.join("assets").join("app.js").is_file());
+        assert!(!dir.join("escape.txt").exists());
+        assert_eq!(installed_dashboards(&dir.join("ui")), vec!["metacubexd"]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn source_url_should_fall_back_to_builtin_dashboards() {
+        let state = UiState {
+            selected: Some("custom".to_string()),
+            sources: BTreeMap::new(),
+        };
+        assert_eq!(source_url(&state, "custom"), None);
+        assert_eq!(
+            source_url(&state, "metacubexd").as_deref(),
+            Some(constants::DEFAULT_EXTERNAL_UI_URL)
+        );
+        assert!(validate_ui_name("../etc").is_err());
+        assert!(validate_ui_name("yacd-meta").is_ok());
+    }
+}
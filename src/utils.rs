@@ -1,4 +1,5 @@
 use std::env;
+use std::path::Path;
 use std::process::{Command, Stdio};
 use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -50,3 +51,121 @@ pub(crate) fn normalize_unit_name(name: &str) -> String {
         format!("{name}.service")
     }
 }
+
+/// 文件距今的修改时长（秒），文件不存在或无法读取时返回 None。
+pub(crate) fn file_age_secs(path: &Path) -> Option<u64> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    let secs = modified.duration_since(UNIX_EPOCH).ok()?.as_secs();
+    Some(now_unix().saturating_sub(secs))
+}
+
+pub(crate) fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+pub(crate) fn format_age(secs: u64) -> String {
+    match secs {
+        0..60 => format!("{secs} 秒前"),
+        60..3600 => format!("{} 分钟前", secs / 60),
+        3600..86400 => format!("{} 小时前", secs / 3600),
+        _ => format!("{} 天前", secs / 86400),
+    }
+}
+
+/// 时长的紧凑表示，例如 `45s`、`3m05s`、`2h10m`、`3d04h`。
+pub(crate) fn format_duration(secs: u64) -> String {
+    match secs {
+        0..60 => format!("{secs}s"),
+        60..3600 => format!("{}m{:02}s", secs / 60, secs % 60),
+        3600..86400 => format!("{}h{:02}m", secs / 3600, secs % 3600 / 60),
+        _ => format!("{}d{:02}h", secs / 86400, secs % 86400 / 3600),
+    }
+}
+
+/// 解析 RFC 3339 时间（mihomo 返回的 `2026-10-18T10:00:00.123+08:00` 等）为 Unix 秒。
+pub(crate) fn parse_rfc3339(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let (date, rest) = value.split_once(['T', 't', ' '])?;
+    let mut date_parts = date.splitn(3, '-');
+    let year: i64 = date_parts.next()?.parse().ok()?;
+    let month: i64 = date_parts.next()?.parse().ok()?;
+    let day: i64 = date_parts.next()?.parse().ok()?;
+
+    let (time, offset_secs) = if let Some(time) = rest.strip_suffix(['Z', 'z']) {
+        (time, 0)
+    } else {
+        let pos = rest.rfind(['+', '-'])?;
+        let (time, offset) = rest.split_at(pos);
+        let sign = if offset.starts_with('-') { -1 } else { 1 };
+        let (oh, om) = offset[1..].split_once(':')?;
+        (
+            time,
+            sign * (oh.parse::<i64>().ok()? * 3600 + om.parse::<i64>().ok()? * 60),
+        )
+    };
+    let time = time.split('.').next()?;
+    let mut time_parts = time.splitn(3, ':');
+    let hour: i64 = time_parts.next()?.parse().ok()?;
+    let minute: i64 = time_parts.next()?.parse().ok()?;
+    let second: i64 = time_parts.next()?.parse().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    // 公历日期转 Unix 天数（Howard Hinnant 的 days_from_civil 算法）
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+
+    let secs = days * 86400 + hour * 3600 + minute * 60 + second - offset_secs;
+    u64::try_from(secs).ok()
+}
+
+/// 按 POSIX shell 单引号规则转义，用于生成钩子脚本。
+pub(crate) fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// 按 systemd unit 文件规则加双引号转义；`%` 与 `$` 需要写成 `%%`、`$$`，避免被展开。
+pub(crate) fn systemd_quote(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('%', "%%")
+        .replace('$', "$$");
+    format!("\"{escaped}\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_rfc3339_should_handle_offsets_and_fractions() {
+        assert_eq!(parse_rfc3339("1970-01-01T00:00:00Z"), Some(0));
+        assert_eq!(
+            parse_rfc3339("2026-10-18T10:00:00.123456789Z"),
+            Some(1_792_317_600)
+        );
+        assert_eq!(
+            parse_rfc3339("2026-10-18T18:00:00+08:00"),
+            Some(1_792_317_600)
+        );
+        assert_eq!(parse_rfc3339("not a date"), None);
+    }
+}
@@ -196,3 +196,176 @@ fn json_mixin_reset_should_clear() {
 
     let _ = fs::remove_dir_all(&home);
 }
+
+#[test]
+fn json_rule_test_should_report_case_results() {
+    let home = temp_home("rule_test");
+    fs::create_dir_all(&home).expect("创建测试目录失败");
+    let config = home.join("config.yaml");
+    fs::write(
+        &config,
+        "rules:\n  - DOMAIN-SUFFIX,google.com,PROXY\n  - MATCH,DIRECT\n",
+    )
+    .expect("写入测试配置失败");
+    let cases = home.join("cases.yaml");
+    fs::write(&cases, "www.google.com: PROXY\nexample.com: PROXY\n").expect("写入用例失败");
+
+    let output = run_with_home(
+        &home,
+        &[
+            "--json",
+            "rule",
+            "test",
+            "--cases",
+            cases.to_str().expect("路径不是 UTF-8"),
+            "--config",
+            config.to_str().expect("路径不是 UTF-8"),
+        ],
+    );
+    assert!(output.status.success());
+    let text = String::from_utf8_lossy(&output.stdout);
+    let value: serde_json::Value = serde_json::from_str(&text).expect("输出不是合法 JSON");
+    assert_eq!(value["ok"], false);
+    assert_eq!(value["action"], "rule.test");
+    assert_eq!(value["summary"]["pass"], 1);
+    assert_eq!(value["summary"]["fail"], 1);
+
+    let _ = fs::remove_dir_all(&home);
+}
+
+#[test]
+fn json_profile_rename_and_set_should_keep_active_consistent() {
+    let home = temp_home("profile_rename");
+    fs::create_dir_all(&home).expect("创建测试目录失败");
+    let add = run_with_home(
+        &home,
+        &[
+            "profile",
+            "add",
+            "--name",
+            "old",
+            "--url",
+            "https://example.com/sub.yaml",
+            "--use-profile",
+            "--no-fetch",
+        ],
+    );
+    assert!(add.status.success());
+
+    let rename = run_with_home(
+        &home,
+        &[
+            "--json", "profile", "rename", "--name", "old", "--to", "new",
+        ],
+    );
+    assert!(rename.status.success());
+    let value: serde_json::Value =
+        serde_json::from_slice(&rename.stdout).expect("输出不是合法 JSON");
+    assert_eq!(value["action"], "profile.rename");
+    assert_eq!(value["active"], "new");
+
+    let set = run_with_home(
+        &home,
+        &[
+            "--json", "profile", "set", "--name", "new", "--note", "主力", "--tag", "a", "--tag",
+            "b",
+        ],
+    );
+    assert!(set.status.success());
+    let value: serde_json::Value = serde_json::from_slice(&set.stdout).expect("输出不是合法 JSON");
+    assert_eq!(value["profile"]["note"], "主力");
+    assert_eq!(value["profile"]["tags"], serde_json::json!(["a", "b"]));
+
+    let list = run_with_home(&home, &["--json", "profile", "list"]);
+    let value: serde_json::Value = serde_json::from_slice(&list.stdout).expect("输出不是合法 JSON");
+    assert_eq!(value["active"], "new");
+    assert_eq!(value["profiles"][0]["name"], "new");
+    assert_eq!(value["profiles"][0]["file"], "new.yaml");
+
+    // 源文件未拉取时，目标文件名被占用也应拒绝
+    fs::write(home.join("profiles").join("stray.yaml"), "proxies: []\n").expect("写入失败");
+    let clash = run_with_home(
+        &home,
+        &["profile", "rename", "--name", "new", "--to", "stray"],
+    );
+    assert!(!clash.status.success());
+    let list = run_with_home(&home, &["--json", "profile", "list"]);
+    let value: serde_json::Value = serde_json::from_slice(&list.stdout).expect("输出不是合法 JSON");
+    assert_eq!(value["profiles"][0]["name"], "new");
+
+    let _ = fs::remove_dir_all(&home);
+}
+
+#[test]
+fn profile_rename_should_update_auto_switch_rules_and_failover_state() {
+    let home = temp_home("rename_refs");
+    fs::create_dir_all(&home).expect("创建测试目录失败");
+    let add = run_with_home(
+        &home,
+        &[
+            "profile",
+            "add",
+            "--name",
+            "home",
+            "--url",
+            "https://example.com/sub.yaml",
+            "--no-fetch",
+        ],
+    );
+    assert!(add.status.success());
+    let rule = run_with_home(
+        &home,
+        &[
+            "auto-switch",
+            "add",
+            "--name",
+            "wifi",
+            "--ssid",
+            "HomeWifi",
+            "--profile",
+            "home",
+        ],
+    );
+    assert!(rule.status.success());
+    fs::write(home.join("failover.json"), r#"{"failures":{"home":2}}"#)
+        .expect("写入故障转移状态失败");
+
+    let rename = run_with_home(
+        &home,
+        &[
+            "--json", "profile", "rename", "--name", "home", "--to", "office",
+        ],
+    );
+    assert!(rename.status.success());
+    let value: serde_json::Value =
+        serde_json::from_slice(&rename.stdout).expect("输出不是合法 JSON");
+    assert_eq!(value["auto_switch_rules_updated"], 1);
+
+    let list = run_with_home(&home, &["--json", "auto-switch", "list"]);
+    let value: serde_json::Value = serde_json::from_slice(&list.stdout).expect("输出不是合法 JSON");
+    assert_eq!(value["rules"][0]["profile"], "office");
+
+    let state = fs::read_to_string(home.join("failover.json")).expect("读取故障转移状态失败");
+    let state: serde_json::Value = serde_json::from_str(&state).expect("状态不是合法 JSON");
+    assert_eq!(state["failures"], serde_json::json!({"office": 2}));
+
+    let _ = fs::remove_dir_all(&home);
+}
+
+#[test]
+fn json_hooks_run_should_report_controller_failure_as_step() {
+    let home = temp_home("hooks_run");
+    fs::create_dir_all(home.join("runtime")).expect("创建测试目录失败");
+    fs::write(home.join("runtime").join("config.yaml"), "a: [\n").expect("写入配置失败");
+
+    let output = run_with_home(&home, &["--json", "hooks", "run", "--auto-switch"]);
+    assert!(output.status.success());
+    let value: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("输出不是合法 JSON");
+    assert_eq!(value["ok"], false);
+    assert_eq!(value["steps"][0]["step"], "auto_switch");
+    assert_eq!(value["steps"][1]["step"], "controller");
+    assert_eq!(value["steps"][1]["ok"], false);
+
+    let _ = fs::remove_dir_all(&home);
+}